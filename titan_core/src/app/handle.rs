@@ -0,0 +1,103 @@
+//! Handle which controls the application while it is running.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use egui::CtxRef;
+
+use crate::graphics::camera::Camera;
+
+/// Handle which controls the application from the callback of [`run`](super::Application::run).
+///
+/// Application is owned by the event loop while it is running, so the game could not call
+/// its methods from the callback. Requests made through the handle are applied by the
+/// application after the current event was delivered.
+///
+/// Can be obtained with [`Application::handle`](super::Application::handle)
+/// before the application is run, and cloned freely.
+///
+#[derive(Clone)]
+pub struct AppHandle {
+    shared: Rc<Shared>,
+}
+
+/// State which is shared between the application and all of its handles.
+#[derive(Default)]
+struct Shared {
+    redraw_requested: Cell<bool>,
+    shutdown_requested: Cell<bool>,
+    camera: Cell<Option<Camera>>,
+    ui_context: RefCell<Option<CtxRef>>,
+}
+
+/// Requests which were made through the handles since they were taken last time.
+pub(super) struct Requests {
+    pub redraw: bool,
+    pub shutdown: bool,
+    pub camera: Option<Camera>,
+}
+
+impl AppHandle {
+    pub(super) fn new() -> Self {
+        Self {
+            shared: Rc::new(Shared::default()),
+        }
+    }
+
+    /// Requests rendering of new frame (see [`Application::request_redraw`]).
+    ///
+    /// [`Application::request_redraw`]: super::Application::request_redraw
+    ///
+    pub fn request_redraw(&self) {
+        self.shared.redraw_requested.set(true);
+    }
+
+    /// Requests shutdown of the application (see [`Application::request_shutdown`]).
+    ///
+    /// [`Application::request_shutdown`]: super::Application::request_shutdown
+    ///
+    pub fn request_shutdown(&self) {
+        self.shared.shutdown_requested.set(true);
+    }
+
+    /// Sets camera which will be used for rendering (see [`Application::set_camera`]).
+    ///
+    /// [`Application::set_camera`]: super::Application::set_camera
+    ///
+    pub fn set_camera(&self, camera: Camera) {
+        self.shared.camera.set(Some(camera));
+    }
+
+    /// Returns UI context as of the last delivered event, or `None` if application was not run yet.
+    pub fn egui_context(&self) -> Option<CtxRef> {
+        self.shared.ui_context.borrow().clone()
+    }
+
+    /// Returns if UI wants pointer and keyboard input respectively
+    /// (see [`Application::ui_wants_input`]).
+    ///
+    /// [`Application::ui_wants_input`]: super::Application::ui_wants_input
+    ///
+    pub fn ui_wants_input(&self) -> (bool, bool) {
+        self.egui_context().map_or((false, false), |context| {
+            (
+                context.wants_pointer_input(),
+                context.wants_keyboard_input(),
+            )
+        })
+    }
+
+    /// Takes all the requests which were made since the last call.
+    pub(super) fn take_requests(&self) -> Requests {
+        Requests {
+            redraw: self.shared.redraw_requested.take(),
+            shutdown: self.shared.shutdown_requested.take(),
+            camera: self.shared.camera.take(),
+        }
+    }
+
+    /// Updates UI context which is visible through the handles.
+    pub(super) fn set_ui_context(&self, context: CtxRef) {
+        *self.shared.ui_context.borrow_mut() = Some(context);
+    }
+}
@@ -0,0 +1,222 @@
+//! Recording and replaying of input events for reproducible tests.
+//!
+//! Input events are stored as text lines of the following format:
+//! `<frame> <milliseconds since start> <kind> <payload...>`, where payload is one of:
+//!
+//! - `text <code point>` for [`Event::TextInput`];
+//! - `key <key> <state>` for [`Event::Keyboard`];
+//! - `mouse <button> <state>` for [`Event::MouseButton`];
+//! - `cursor <x> <y>` for [`Event::CursorMoved`].
+//!
+//! Events are replayed by frame number, so replay does not depend on the speed of rendering.
+//!
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::window::{ElementState, Event, MouseButton, VirtualKeyCode};
+
+/// Error of loading recorded input events.
+#[derive(Debug, Error)]
+pub enum InputReplayError {
+    #[error("failed to read recorded input: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("recorded input is malformed at line {line}")]
+    Malformed { line: usize },
+}
+
+/// Writes input events into the file as they are delivered to the game.
+pub(super) struct InputRecorder {
+    writer: BufWriter<File>,
+}
+
+impl InputRecorder {
+    /// Creates (or truncates) the file to record input events into.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let writer = BufWriter::new(File::create(path)?);
+        Ok(Self { writer })
+    }
+
+    /// Records given event if it is an input event, other events are ignored.
+    pub fn record(&mut self, frame: u64, time: Duration, event: &Event) -> io::Result<()> {
+        let payload = match event {
+            Event::TextInput(character) => format!("text {}", u32::from(*character)),
+            Event::Keyboard { key, state } => {
+                format!("key {:?} {}", key, self::state_name(*state))
+            }
+            Event::MouseButton { button, state } => {
+                let button = match button {
+                    MouseButton::Left => "left".to_string(),
+                    MouseButton::Right => "right".to_string(),
+                    MouseButton::Middle => "middle".to_string(),
+                    MouseButton::Other(button) => button.to_string(),
+                };
+                format!("mouse {} {}", button, self::state_name(*state))
+            }
+            // Floating point numbers are displayed precisely, so they are parsed back as is.
+            Event::CursorMoved(x, y) => format!("cursor {} {}", x, y),
+            _ => return Ok(()),
+        };
+        let millis = time.as_millis();
+        writeln!(self.writer, "{} {} {}", frame, millis, payload)
+    }
+
+    /// Writes all buffered events into the file.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Input events which were loaded from the file to be replayed.
+pub(super) struct InputReplay {
+    events: VecDeque<(u64, RecordedInput)>,
+}
+
+impl InputReplay {
+    /// Loads all input events recorded into the file.
+    pub fn load(path: &Path) -> Result<Self, InputReplayError> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut events = VecDeque::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let frame = parts.next().and_then(|frame| frame.parse().ok());
+            let _millis = parts.next();
+            let input = RecordedInput::parse(parts);
+            match (frame, input) {
+                (Some(frame), Some(input)) => events.push_back((frame, input)),
+                _ => return Err(InputReplayError::Malformed { line: index + 1 }),
+            }
+        }
+        Ok(Self { events })
+    }
+
+    /// Returns next event which should be delivered at given frame, if any.
+    pub fn next(&mut self, frame: u64) -> Option<Event> {
+        match self.events.front() {
+            Some(&(event_frame, _)) if event_frame <= frame => {
+                self.events.pop_front().map(|(_, input)| input.into())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Input event which could be recorded and replayed.
+enum RecordedInput {
+    Text(char),
+    Keyboard {
+        key: VirtualKeyCode,
+        state: ElementState,
+    },
+    MouseButton {
+        button: MouseButton,
+        state: ElementState,
+    },
+    CursorMoved(f64, f64),
+}
+
+impl RecordedInput {
+    /// Parses kind and payload of the recorded input event.
+    fn parse<'a>(mut parts: impl Iterator<Item = &'a str>) -> Option<Self> {
+        let input = match parts.next()? {
+            "text" => {
+                let character = parts.next()?.parse().ok()?;
+                Self::Text(char::from_u32(character)?)
+            }
+            "key" => {
+                let key = parts.next()?;
+                let key = *KEYS.iter().find(|code| format!("{:?}", code) == key)?;
+                let state = self::parse_state(parts.next()?)?;
+                Self::Keyboard { key, state }
+            }
+            "mouse" => {
+                let button = match parts.next()? {
+                    "left" => MouseButton::Left,
+                    "right" => MouseButton::Right,
+                    "middle" => MouseButton::Middle,
+                    button => MouseButton::Other(button.parse().ok()?),
+                };
+                let state = self::parse_state(parts.next()?)?;
+                Self::MouseButton { button, state }
+            }
+            "cursor" => {
+                let x = parts.next()?.parse().ok()?;
+                let y = parts.next()?.parse().ok()?;
+                Self::CursorMoved(x, y)
+            }
+            _ => return None,
+        };
+        // Trailing data means that the line was written by something else.
+        parts.next().is_none().then(|| input)
+    }
+}
+
+impl From<RecordedInput> for Event {
+    fn from(input: RecordedInput) -> Self {
+        match input {
+            RecordedInput::Text(character) => Event::TextInput(character),
+            RecordedInput::Keyboard { key, state } => Event::Keyboard { key, state },
+            RecordedInput::MouseButton { button, state } => Event::MouseButton { button, state },
+            RecordedInput::CursorMoved(x, y) => Event::CursorMoved(x, y),
+        }
+    }
+}
+
+/// Name of the state of key or button in the recorded input.
+fn state_name(state: ElementState) -> &'static str {
+    match state {
+        ElementState::Pressed => "pressed",
+        ElementState::Released => "released",
+    }
+}
+
+/// Parses state of key or button from the recorded input.
+fn parse_state(state: &str) -> Option<ElementState> {
+    match state {
+        "pressed" => Some(ElementState::Pressed),
+        "released" => Some(ElementState::Released),
+        _ => None,
+    }
+}
+
+/// All the keys which could be recorded, so they could be found by their names.
+#[rustfmt::skip]
+const KEYS: [VirtualKeyCode; 163] = {
+    use VirtualKeyCode::*;
+    [
+        Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0,
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+        Escape,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+        F13, F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24,
+        Snapshot, Scroll, Pause,
+        Insert, Home, Delete, End, PageDown, PageUp,
+        Left, Up, Right, Down,
+        Back, Return, Space, Compose, Caret, Numlock,
+        Numpad0, Numpad1, Numpad2, Numpad3, Numpad4,
+        Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+        NumpadAdd, NumpadDivide, NumpadDecimal, NumpadComma, NumpadEnter,
+        NumpadEquals, NumpadMultiply, NumpadSubtract,
+        AbntC1, AbntC2, Apostrophe, Apps, Asterisk, At, Ax, Backslash, Calculator, Capital,
+        Colon, Comma, Convert, Equals, Grave, Kana, Kanji,
+        LAlt, LBracket, LControl, LShift, LWin,
+        Mail, MediaSelect, MediaStop, Minus, Mute, MyComputer,
+        NavigateForward, NavigateBackward, NextTrack, NoConvert, OEM102,
+        Period, PlayPause, Plus, Power, PrevTrack,
+        RAlt, RBracket, RControl, RShift, RWin,
+        Semicolon, Slash, Sleep, Stop, Sysrq, Tab, Underline, Unlabeled,
+        VolumeDown, VolumeUp, Wake,
+        WebBack, WebFavorites, WebForward, WebHome, WebRefresh, WebSearch, WebStop,
+        Yen, Copy, Paste, Cut,
+    ]
+};
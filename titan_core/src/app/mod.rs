@@ -1,23 +1,59 @@
 //! Utilities for engine initialization.
 
+use std::collections::HashMap;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use egui::TextureId;
+use egui::{CtxRef, TextureId};
 use egui_winit_platform::{Platform, PlatformDescriptor};
+#[cfg(feature = "gamepad")]
+use gilrs::Gilrs;
 use image::RgbaImage;
+use palette::Srgba;
 use thiserror::Error;
-use ultraviolet::{Mat4, Vec3};
+use titan_ecs::Schedule;
+use ultraviolet::{Mat3, Mat4, Vec2, Vec3};
+use vulkano::descriptor_set::DescriptorSetsCollection;
+use vulkano::device::{Device, Queue};
+use vulkano::pipeline::ComputePipeline;
+use vulkano::sync::{FenceSignalFuture, GpuFuture};
 use winit::event::{Event, StartCause, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::Window;
 
+use self::input::{InputRecorder, InputReplay, InputReplayError};
+#[cfg(feature = "audio")]
+use crate::audio::AudioEngine;
 use crate::{
-    config::Config,
-    graphics::{camera::CameraUBO, error::ImageRegisterError, Renderer, RendererCreationError},
-    window::{Event as MyEvent, Size},
+    config::{Config, FullscreenMode, RedrawMode},
+    graphics::{
+        camera::{Camera, CameraUBO},
+        compute::ComputeLayout,
+        error::{
+            CaptureError, ComputePipelineLoadError, DepthReadError, DispatchError,
+            ImageRegisterError, RenderError, RenderToImageError,
+        },
+        light::DirectionalLight,
+        mesh::{Topology, Vertex},
+        DeviceLimits, Frame, FrameTiming, Material, MaterialCreationError, MaterialHandle,
+        ObjectDrawError, ObjectDrawSystemCreationError, RenderInstance, Renderer,
+        RendererCreationError, SamplerDesc, UploadTicket,
+    },
+    scene::{Scene, SceneLoadError},
+    window::{self, CloseRequest, Event as MyEvent, MonitorInfo, Rect, Size},
 };
 
+pub use self::handle::AppHandle;
+
+pub mod input;
+
+mod handle;
+mod tests;
+
 pub type Result<T> = std::result::Result<T, AppCreationError>;
 
 #[derive(Debug, Error)]
@@ -27,20 +63,52 @@ pub enum AppCreationError {
 
     #[error("graphics initialization error: {0}")]
     Graphics(#[from] RendererCreationError),
+
+    #[error("input replay loading error: {0}")]
+    InputReplay(#[from] InputReplayError),
 }
 
 /// Type which represents duration between two frames.
 pub type DeltaTime = Duration;
 
+/// Max count of fixed updates which are delivered per frame if fixed time step is enabled.
+pub const MAX_CATCH_UP_STEPS: u32 = 5;
+
+/// Interval of gamepad polling if redraws are on demand,
+/// because gamepad input does not wake the event loop up.
+#[cfg(feature = "gamepad")]
+const GAMEPAD_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 /// General context of game engine.
 ///
 /// Can be created using [`init`] function.
 ///
 pub struct Application {
-    _config: Config,
+    config: Config,
     renderer: Renderer,
     egui: Option<Platform>,
     event_loop: Option<EventLoop<()>>,
+    scenes: HashMap<String, Scene>,
+    active_scene: Option<String>,
+    schedule: Schedule,
+    start_time: Instant,
+    created: bool,
+    redraw_requested: bool,
+    ui_scale_changed: bool,
+    shutdown_requested: bool,
+    fullscreen: bool,
+    maximized: bool,
+    frame: u64,
+    last_frame: Option<Instant>,
+    accumulator: Duration,
+    camera: Option<Camera>,
+    handle: AppHandle,
+    input_recorder: Option<InputRecorder>,
+    input_replay: Option<InputReplay>,
+    #[cfg(feature = "audio")]
+    audio: Option<AudioEngine>,
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<Gilrs>,
 }
 
 impl Application {
@@ -48,7 +116,9 @@ fn new(config: Config) -> Result<Self> {
         let event_loop = EventLoop::with_user_event();
         let renderer = Renderer::new(&config, &event_loop)?;
 
-        let window = renderer.window();
+        let window = renderer
+            .window()
+            .expect("renderer of application has a window");
         let size = window.inner_size();
         let egui = Platform::new(PlatformDescriptor {
             physical_width: size.width,
@@ -56,20 +126,593 @@ fn new(config: Config) -> Result<Self> {
             scale_factor: window.scale_factor(),
             ..Default::default()
         });
+        let fullscreen = window.fullscreen().is_some();
+        let maximized = window.is_maximized();
+        let input_replay = config.replay_input().map(InputReplay::load).transpose()?;
+
+        #[cfg(feature = "audio")]
+        let audio = AudioEngine::new()
+            .map_err(|error| log::warn!("audio is not available: {}", error))
+            .ok();
+        #[cfg(feature = "gamepad")]
+        let gilrs = Gilrs::new()
+            .map_err(|error| log::warn!("gamepads are not available: {}", error))
+            .ok();
 
         Ok(Self {
             renderer,
             egui: Some(egui),
-            _config: config,
+            config,
             event_loop: Some(event_loop),
+            scenes: HashMap::new(),
+            active_scene: None,
+            schedule: Schedule::new(),
+            start_time: Instant::now(),
+            created: false,
+            redraw_requested: true,
+            ui_scale_changed: false,
+            shutdown_requested: false,
+            fullscreen,
+            maximized,
+            frame: 0,
+            last_frame: None,
+            accumulator: Duration::ZERO,
+            camera: None,
+            handle: AppHandle::new(),
+            input_recorder: None,
+            input_replay,
+            #[cfg(feature = "audio")]
+            audio,
+            #[cfg(feature = "gamepad")]
+            gilrs,
         })
     }
 
+    /// Names of all physical devices (GPUs) of the system, in order of their indices.
+    ///
+    /// Could be called before the application is created to choose preferred device
+    /// (see [`Config::set_preferred_device`]).
+    ///
+    pub fn enumerate_device_names() -> Vec<String> {
+        Renderer::enumerate_device_names()
+    }
+
+    /// Returns configuration which this application was created with.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
     /// Returns underlying window of this application.
     pub fn window(&self) -> &Window {
-        self.renderer.window()
+        // Application always creates the renderer with the window.
+        self.renderer
+            .window()
+            .expect("renderer of application has a window")
+    }
+
+    /// Returns current size of the client area of the window in physical pixels.
+    pub fn window_size(&self) -> Size {
+        self.window().inner_size().into()
+    }
+
+    /// Returns current scale factor of the window,
+    /// which maps logical pixels into physical ones.
+    pub fn scale_factor(&self) -> f64 {
+        self.window().scale_factor()
+    }
+
+    /// Returns UI context, which could be used outside of [`MyEvent::UI`] event
+    /// (for example, to query UI state).
+    ///
+    /// Returns `None` if UI is being updated right now.
+    /// Use [`AppHandle::egui_context`] while the application is running.
+    ///
+    pub fn egui_context(&self) -> Option<CtxRef> {
+        self.egui.as_ref().map(Platform::context)
+    }
+
+    /// Returns if UI wants pointer and keyboard input respectively.
+    ///
+    /// Game should not handle input which is wanted by UI
+    /// (for example, click on the button should not rotate the camera).
+    ///
+    pub fn ui_wants_input(&self) -> (bool, bool) {
+        self.egui_context().map_or((false, false), |context| {
+            (
+                context.wants_pointer_input(),
+                context.wants_keyboard_input(),
+            )
+        })
+    }
+
+    /// Returns logical device which is used for rendering,
+    /// so custom resources could be created on it.
+    pub fn device(&self) -> Arc<Device> {
+        self.renderer.device()
+    }
+
+    /// Returns queue which is used to render frames.
+    pub fn graphics_queue(&self) -> Arc<Queue> {
+        self.renderer.graphics_queue()
+    }
+
+    /// Returns queue which is used to upload data into GPU.
+    pub fn transfer_queue(&self) -> Arc<Queue> {
+        self.renderer.transfer_queue()
+    }
+
+    /// Returns queue which is used to dispatch compute work.
+    pub fn compute_queue(&self) -> Arc<Queue> {
+        self.renderer.compute_queue()
+    }
+
+    /// Returns all the queues which were created to dispatch compute work
+    /// (see [`Config::set_queue_config`]).
+    pub fn compute_queues(&self) -> &[Arc<Queue>] {
+        self.renderer.compute_queues()
+    }
+
+    /// Creates compute pipeline from SPIR-V binary with `main` entry point
+    /// and given layout of its resources.
+    pub fn create_compute_pipeline(
+        &self,
+        spirv: &[u8],
+        layout: ComputeLayout,
+    ) -> std::result::Result<Arc<ComputePipeline>, ComputePipelineLoadError> {
+        self.renderer.create_compute_pipeline(spirv, layout)
+    }
+
+    /// Dispatches compute work which will be completed before the next frame is rendered.
+    pub fn dispatch<S>(
+        &mut self,
+        pipeline: Arc<ComputePipeline>,
+        descriptor_sets: S,
+        groups: [u32; 3],
+    ) -> std::result::Result<(), DispatchError>
+    where
+        S: DescriptorSetsCollection,
+    {
+        self.renderer.dispatch(pipeline, descriptor_sets, groups)
+    }
+
+    /// Dispatches compute work which is independent of rendering
+    /// (see [`Renderer::dispatch_async`]).
+    pub fn dispatch_async<S>(
+        &self,
+        pipeline: Arc<ComputePipeline>,
+        descriptor_sets: S,
+        groups: [u32; 3],
+    ) -> std::result::Result<FenceSignalFuture<Box<dyn GpuFuture + Send + Sync>>, DispatchError>
+    where
+        S: DescriptorSetsCollection,
+    {
+        self.renderer
+            .dispatch_async(pipeline, descriptor_sets, groups)
+    }
+
+    /// Reads normalized depth of the last rendered frame at given position
+    /// (in physical pixels relative to the top left corner of the window).
+    ///
+    /// Stalls rendering until GPU finishes, so it should not be called every frame
+    /// (see [`Renderer::read_depth_at`]). Depth readback must be enabled by config.
+    ///
+    pub fn read_depth_at(&mut self, x: u32, y: u32) -> std::result::Result<f32, DepthReadError> {
+        self.renderer.read_depth_at(x, y)
+    }
+
+    /// Requests to capture the next presented frame into PNG image at given path
+    /// (see [`Renderer::capture_frame`]).
+    pub fn capture_frame(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> std::result::Result<(), CaptureError> {
+        self.renderer.capture_frame(path)
+    }
+
+    /// Renders game objects into the image of given size instead of the window.
+    ///
+    /// Submitted game objects are kept for the next frame.
+    /// Stalls rendering until GPU finishes, so it should not be called every frame
+    /// (see [`Renderer::render_to_image`]).
+    ///
+    pub fn render_to_image(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> std::result::Result<RgbaImage, RenderToImageError> {
+        self.renderer.render_to_image(width, height)
+    }
+
+    /// Presents one frame into the window, drawn by given closure instead of the game objects
+    /// and UI (see [`Renderer::present_frame`]).
+    ///
+    /// Application is owned by the event loop while it is running, so this is useful
+    /// before [`run`](Self::run) is called, for example, to present a loading screen.
+    ///
+    pub fn present_frame<F>(&mut self, draw: F) -> std::result::Result<(), RenderError>
+    where
+        F: FnOnce(&mut Frame) -> std::result::Result<(), RenderError>,
+    {
+        self.renderer.present_frame(draw)
+    }
+
+    /// Starts recording of input events delivered to the game into given file.
+    ///
+    /// Recorded input could be replayed later with [`Config::set_replay_input`]:
+    /// events are replayed at the same frames as they were recorded.
+    ///
+    /// Gamepad events are not recorded: they are always delivered live.
+    ///
+    pub fn record_input(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.input_recorder = Some(InputRecorder::create(path.as_ref())?);
+        Ok(())
+    }
+
+    /// Returns limits of the device which should be respected when creating resources.
+    pub fn limits(&self) -> DeviceLimits {
+        self.renderer.limits()
+    }
+
+    /// Returns audio engine of this application,
+    /// or `None` if audio output device is not available.
+    #[cfg(feature = "audio")]
+    pub fn audio(&self) -> Option<&AudioEngine> {
+        self.audio.as_ref()
+    }
+
+    /// Returns mutable audio engine of this application,
+    /// or `None` if audio output device is not available.
+    #[cfg(feature = "audio")]
+    pub fn audio_mut(&mut self) -> Option<&mut AudioEngine> {
+        self.audio.as_mut()
+    }
+
+    /// Returns information about all monitors which are connected to the system.
+    ///
+    /// Indices of monitors and their video modes are used by [`FullscreenMode`].
+    ///
+    pub fn available_monitors(&self) -> Vec<MonitorInfo> {
+        self.window()
+            .available_monitors()
+            .map(MonitorInfo::from)
+            .collect()
+    }
+
+    /// Switches window into given fullscreen mode, or into windowed mode if `None`.
+    ///
+    /// If monitor does not exist, warning is logged and nothing is changed.
+    /// If video mode of exclusive fullscreen does not exist, borderless fullscreen is used instead.
+    ///
+    /// Swapchain is recreated before the next frame to match the new size of the window.
+    ///
+    pub fn set_fullscreen(&mut self, mode: Option<FullscreenMode>) {
+        let window = self.window();
+        let fullscreen = match mode {
+            Some(mode) => match window::fullscreen_of(window, mode) {
+                Some(fullscreen) => Some(fullscreen),
+                None => {
+                    log::warn!("fullscreen mode {:?} is not available", mode);
+                    return;
+                }
+            },
+            None => None,
+        };
+        window.set_fullscreen(fullscreen);
+        self.renderer.request_resize();
+    }
+
+    /// Returns if window is in fullscreen mode.
+    pub fn is_fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
+    /// Returns if window is being resized, i.e. rendering resources are out of date.
+    ///
+    /// Could be used to defer expensive work during continuous resizing.
+    ///
+    pub fn is_resizing(&self) -> bool {
+        self.renderer.needs_resize()
+    }
+
+    /// Returns timing statistics of the last rendered frame.
+    ///
+    /// Can be used to find out whether rendering is CPU-bound or GPU-bound.
+    ///
+    pub fn frame_timing(&self) -> FrameTiming {
+        self.renderer.timing()
+    }
+
+    /// Returns view and projection matrices of the camera used for rendering.
+    pub fn camera_matrices(&self) -> (Mat4, Mat4) {
+        let ubo = self.renderer.camera_ubo();
+        (ubo.view, ubo.projection)
+    }
+
+    /// Requests shutdown of the application.
+    ///
+    /// Event loop will exit on the next event: GPU finishes its work,
+    /// then [`MyEvent::Destroyed`] is fired and after that GPU resources are freed.
+    /// Use [`AppHandle::request_shutdown`] while the application is running.
+    ///
+    pub fn request_shutdown(&mut self) {
+        self.shutdown_requested = true;
+    }
+
+    /// Requests rendering of new frame.
+    ///
+    /// Should be called when something was changed in the game
+    /// if [`RedrawMode::OnDemand`] is used, otherwise does nothing useful.
+    /// Use [`AppHandle::request_redraw`] while the application is running.
+    ///
+    pub fn request_redraw(&mut self) {
+        self.redraw_requested = true;
+    }
+
+    /// Returns handle which controls this application from the callback of [`run`](Self::run),
+    /// because the application itself is moved into the event loop.
+    pub fn handle(&self) -> AppHandle {
+        self.handle.clone()
+    }
+
+    /// Prepares built-in pipelines for rendering to avoid stutter on the first frames.
+    ///
+    /// Should be called before [`run`](Self::run), for example, while loading.
+    ///
+    pub fn warm_up(&mut self) -> std::result::Result<(), RenderError> {
+        self.renderer.warm_up()
+    }
+
+    /// Submits instances of game objects to be rendered in the next frame.
+    ///
+    /// Instances are rendered only once, so they should be submitted before each frame.
+    ///
+    pub fn submit(&mut self, instances: impl IntoIterator<Item = RenderInstance>) {
+        self.renderer.submit(instances);
+        self.redraw_requested = true;
+    }
+
+    /// Submits instances of game objects to be rendered in the next frame
+    /// into given region of the window with its own camera.
+    ///
+    /// Could be called many times per frame, for example, to implement split-screen.
+    /// Depth of instances is mapped into given depth range,
+    /// whose bounds are clamped into `0.0..=1.0`.
+    ///
+    /// Camera UBO is built with aspect ratio of the region in the window,
+    /// so instances are not stretched if the region is not square.
+    ///
+    pub fn submit_to_viewport(
+        &mut self,
+        instances: impl IntoIterator<Item = RenderInstance>,
+        region: Rect,
+        depth_range: Range<f32>,
+        camera: &Camera,
+    ) {
+        let size = Size::from(self.window().inner_size());
+        let aspect = size.aspect_ratio() * region.width / region.height;
+        let camera_ubo = camera.ubo_for_aspect(aspect);
+        self.renderer
+            .submit_to_viewport(instances, region, depth_range, camera_ubo);
+        self.redraw_requested = true;
+    }
+
+    /// Loads mesh from Wavefront OBJ file which replaces the mesh of game objects.
+    ///
+    /// Positions with optional per-vertex colors, texture coordinates and normals are loaded.
+    /// Current mesh is kept if loading fails.
+    ///
+    pub fn load_obj(&mut self, path: impl AsRef<Path>) -> std::result::Result<(), ObjectDrawError> {
+        self.renderer.load_obj(path.as_ref())?;
+        self.redraw_requested = true;
+        Ok(())
+    }
+
+    /// Replaces the mesh of game objects with given vertices and indices,
+    /// which are assembled into triangles with given topology (see [`Renderer::set_mesh`]).
+    ///
+    /// Current mesh is kept if it could not be uploaded.
+    ///
+    pub fn set_mesh(
+        &mut self,
+        vertices: Vec<Vertex>,
+        indices: Vec<u32>,
+        topology: Topology,
+    ) -> std::result::Result<(), ObjectDrawError> {
+        self.renderer.set_mesh(vertices, indices, topology)?;
+        self.redraw_requested = true;
+        Ok(())
+    }
+
+    /// Replaces shaders which render game objects with given SPIR-V binaries
+    /// (with `main` entry points), for example, to experiment with post-processing.
+    ///
+    /// Vertex input stays the same, and shaders must declare the same resources,
+    /// inputs and outputs as built-in `default.vert` and `default.frag` shaders do.
+    /// Current shaders are kept if the binaries are not SPIR-V.
+    ///
+    /// # Safety
+    ///
+    /// Binaries must be valid SPIR-V modules with the interface described above,
+    /// because it is not checked (see [`Renderer::set_object_shaders`]).
+    ///
+    pub unsafe fn set_object_shaders(
+        &mut self,
+        vertex: &[u32],
+        fragment: &[u32],
+    ) -> std::result::Result<(), ObjectDrawSystemCreationError> {
+        self.renderer.set_object_shaders(vertex, fragment)?;
+        self.redraw_requested = true;
+        Ok(())
+    }
+
+    /// Uploads given sRGB image as the texture of game objects with default material
+    /// (see [`Renderer::set_object_texture`]).
+    pub fn set_object_texture(
+        &mut self,
+        image: Option<&RgbaImage>,
+    ) -> std::result::Result<(), MaterialCreationError> {
+        self.renderer.set_object_texture(image)?;
+        self.redraw_requested = true;
+        Ok(())
+    }
+
+    /// Creates new material which could be referenced by instances of game objects.
+    pub fn create_material(
+        &mut self,
+        material: Material,
+    ) -> std::result::Result<MaterialHandle, MaterialCreationError> {
+        self.renderer.create_material(material)
+    }
+
+    /// Removes material which was created earlier.
+    ///
+    /// Instances which still reference it are drawn with default material.
+    ///
+    pub fn remove_material(&mut self, handle: MaterialHandle) {
+        self.renderer.remove_material(handle);
+        self.redraw_requested = true;
+    }
+
+    /// Draws outline of given color and relative thickness around game objects
+    /// in the next rendered frame.
+    ///
+    /// Requires depth format with stencil aspect (see [`Config::set_depth_format`]).
+    ///
+    pub fn draw_outline(&mut self, color: Srgba, thickness: f32) {
+        self.renderer.draw_outline(color, thickness);
+        self.redraw_requested = true;
+    }
+
+    /// Sets width of lines which edges of game objects are drawn with in wireframe mode
+    /// (see [`Renderer::set_debug_line_width`]).
+    pub fn set_debug_line_width(&mut self, width: f32) {
+        self.renderer.set_debug_line_width(width);
+        self.redraw_requested = true;
+    }
+
+    /// Returns camera which is used for rendering, if it was set.
+    pub fn camera(&self) -> Option<&Camera> {
+        self.camera.as_ref()
+    }
+
+    /// Sets camera which will be used for rendering.
+    ///
+    /// Camera UBO is rebuilt before each frame with aspect ratio of the window,
+    /// so there is no need to update the camera on resize.
+    /// Use [`AppHandle::set_camera`] while the application is running.
+    ///
+    pub fn set_camera(&mut self, camera: Camera) {
+        self.camera = Some(camera);
+        self.redraw_requested = true;
+    }
+
+    /// Sets camera uniform buffer object which will be used for rendering.
+    ///
+    /// Could be built from the camera using aspect ratio of the window
+    /// with [`Camera::ubo_for_aspect`](crate::camera::Camera::ubo_for_aspect).
+    /// Camera which was set by [`set_camera`](Application::set_camera) is not used anymore.
+    ///
+    pub fn set_camera_ubo(&mut self, ubo: CameraUBO) {
+        self.camera = None;
+        self.renderer.set_camera_ubo(ubo);
+        self.redraw_requested = true;
+    }
+
+    /// Directional light which lits all the game objects.
+    pub fn light(&self) -> DirectionalLight {
+        self.renderer.light()
+    }
+
+    /// Sets directional light which lits all the game objects.
+    pub fn set_light(&mut self, light: DirectionalLight) {
+        self.renderer.set_light(light);
+        self.redraw_requested = true;
+    }
+
+    /// Sets color which the window is cleared with before rendering (opaque black by default).
+    pub fn set_clear_color(&mut self, color: Srgba) {
+        self.renderer.set_clear_color(color);
+        self.redraw_requested = true;
+    }
+
+    /// Enables or disables drawing of game objects (see [`Renderer::set_draw_objects`]).
+    pub fn set_draw_objects(&mut self, draw_objects: bool) {
+        self.renderer.set_draw_objects(draw_objects);
+        self.redraw_requested = true;
+    }
+
+    /// Enables or disables drawing of UI (see [`Renderer::set_draw_ui`]).
+    ///
+    /// UI is still updated, so the game could keep handling [`UI`](MyEvent::UI) events.
+    ///
+    pub fn set_draw_ui(&mut self, draw_ui: bool) {
+        self.renderer.set_draw_ui(draw_ui);
+        self.redraw_requested = true;
+    }
+
+    /// Scale factor of UI which is used instead of the scale factor of the window, if any.
+    pub fn ui_scale_override(&self) -> Option<f32> {
+        self.renderer.ui_scale_override()
+    }
+
+    /// Overrides scale factor of UI regardless of DPI of the display.
+    ///
+    /// If `None`, scale factor of the window is used (which is the default),
+    /// so UI is scaled again when the window is moved to the display with another DPI.
+    ///
+    pub fn set_ui_scale_override(&mut self, scale_factor: Option<f32>) {
+        self.renderer.set_ui_scale_override(scale_factor);
+        self.ui_scale_changed = true;
+        self.redraw_requested = true;
+    }
+
+    /// Makes UI platform use the scale factor of UI instead of the scale factor of the window.
+    fn apply_ui_scale(&self, egui: &mut Platform) {
+        let window = self.window();
+        let mut size = window.inner_size();
+        let event = Event::WindowEvent {
+            window_id: window.id(),
+            event: WindowEvent::ScaleFactorChanged {
+                scale_factor: self.renderer.ui_scale_factor() as f64,
+                new_inner_size: &mut size,
+            },
+        };
+        egui.handle_event::<()>(&event);
     }
 
+    /// Projects point in the world onto the window.
+    ///
+    /// Returns position in physical pixels relative to the top left corner of the window,
+    /// or `None` if the point is behind the camera or out of the depth range.
+    ///
+    /// Position is in un-rotated window space, as the window is seen by the user:
+    /// pre-rotation of the surface (on mobile devices) is applied only to rendered images,
+    /// and [`camera_matrices`](Self::camera_matrices) do not include it.
+    ///
+    pub fn world_to_screen(&self, point: Vec3) -> Option<Vec2> {
+        let (view, projection) = self.camera_matrices();
+        let clip = projection * view * point.into_homogeneous_point();
+        if clip.w <= 0.0 {
+            return None;
+        }
+        let ndc = clip.xyz() / clip.w;
+        if !(0.0..=1.0).contains(&ndc.z) {
+            return None;
+        }
+
+        let size = self.window().inner_size();
+        let x = (ndc.x + 1.0) * 0.5 * size.width as f32;
+        let y = (ndc.y + 1.0) * 0.5 * size.height as f32;
+        Some(Vec2::new(x, y))
+    }
+
+    /// Registers new image to be drawn in UI, blocking until it is uploaded.
+    ///
+    /// Images cannot be registered once the application started to close
+    /// (for example, on [`Destroyed`](crate::window::Event::Destroyed) event).
+    /// Copy of the image is kept in CPU memory while it is registered,
+    /// so it could be uploaded again if the device is recreated.
+    ///
     pub fn register_ui_image(
         &mut self,
         image: &RgbaImage,
@@ -77,130 +720,589 @@ pub fn register_ui_image(
         self.renderer.register_ui_image(image)
     }
 
+    /// Registers new image with generated mipmaps to be drawn in UI,
+    /// blocking until it is uploaded (see [`Renderer::register_ui_image_mipmapped`]).
+    pub fn register_ui_image_mipmapped(
+        &mut self,
+        image: &RgbaImage,
+    ) -> std::result::Result<TextureId, ImageRegisterError> {
+        self.renderer.register_ui_image_mipmapped(image)
+    }
+
+    /// Registers new image to be drawn in UI with given sampler,
+    /// blocking until it is uploaded (see [`Renderer::register_ui_image_with_sampler`]).
+    pub fn register_ui_image_with_sampler(
+        &mut self,
+        image: &RgbaImage,
+        sampler: SamplerDesc,
+    ) -> std::result::Result<TextureId, ImageRegisterError> {
+        self.renderer.register_ui_image_with_sampler(image, sampler)
+    }
+
+    /// Loads image from the file and registers it to be drawn in UI.
+    ///
+    /// Image is treated as sRGB-encoded if `srgb` is `true`, otherwise as linear.
+    ///
+    pub fn load_texture(
+        &mut self,
+        path: &Path,
+        srgb: bool,
+    ) -> std::result::Result<TextureId, ImageRegisterError> {
+        self.renderer.load_texture(path, srgb)
+    }
+
+    /// Registers new image to be drawn in UI without waiting for its upload.
+    ///
+    /// Returned ticket should be waited or polled before image could be used in UI.
+    ///
+    pub fn register_ui_image_async(
+        &mut self,
+        image: &RgbaImage,
+    ) -> std::result::Result<UploadTicket<TextureId>, ImageRegisterError> {
+        self.renderer.register_ui_image_async(image)
+    }
+
+    /// Registers the scene so it could be loaded later by its name.
+    ///
+    /// Returns previously registered scene with the same name, if any.
+    ///
+    pub fn register_scene(&mut self, scene: Scene) -> Option<Scene> {
+        let name = scene.name().to_string();
+        if self.active_scene.as_ref() == Some(&name) {
+            self.unload_scene();
+        }
+        self.scenes.insert(name, scene)
+    }
+
+    /// Loads registered scene with given name and makes it active.
+    ///
+    /// Resources of previously active scene are freed.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if there is no registered scene with such name
+    /// or resources of the scene could not be uploaded.
+    ///
+    pub fn load_scene(&mut self, name: &str) -> std::result::Result<(), SceneLoadError> {
+        if !self.scenes.contains_key(name) {
+            return Err(SceneLoadError::NotRegistered(name.to_string()));
+        }
+        self.unload_scene();
+
+        let scene = self.scenes.get_mut(name).unwrap();
+        let mut textures = Vec::with_capacity(scene.ui_images().len());
+        for image in scene.ui_images() {
+            match self.renderer.register_ui_image(image) {
+                Ok(texture_id) => textures.push(texture_id),
+                Err(error) => {
+                    // Free already uploaded images of the scene.
+                    for texture_id in textures {
+                        self.renderer.unregister_ui_image(texture_id);
+                    }
+                    return Err(error.into());
+                }
+            }
+        }
+        scene.set_ui_textures(textures);
+        self.active_scene = Some(name.to_string());
+        self.redraw_requested = true;
+        Ok(())
+    }
+
+    /// Currently active (loaded) scene, if any.
+    pub fn scene(&self) -> Option<&Scene> {
+        let name = self.active_scene.as_ref()?;
+        self.scenes.get(name)
+    }
+
+    /// Mutable currently active (loaded) scene, if any.
+    pub fn scene_mut(&mut self) -> Option<&mut Scene> {
+        let name = self.active_scene.as_ref()?;
+        self.scenes.get_mut(name)
+    }
+
+    /// Systems which are run on the world of active scene after each [`Update`](MyEvent::Update).
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+
+    /// Mutable systems which are run on the world of active scene after each update.
+    pub fn schedule_mut(&mut self) -> &mut Schedule {
+        &mut self.schedule
+    }
+
+    /// Runs scheduled systems on the world of active scene, if any,
+    /// then updates events of the world.
+    fn run_systems(&mut self) {
+        let scene = match self.active_scene.as_ref() {
+            Some(name) => self.scenes.get_mut(name),
+            None => None,
+        };
+        if let Some(scene) = scene {
+            let world = scene.world_mut();
+            self.schedule.run(world);
+            // Events sent by systems are readable during this and the next update.
+            world.update_events();
+        }
+    }
+
+    /// Frees resources of currently active scene.
+    fn unload_scene(&mut self) {
+        let name = match self.active_scene.take() {
+            Some(name) => name,
+            None => return,
+        };
+        if let Some(scene) = self.scenes.get_mut(&name) {
+            for texture_id in scene.take_ui_textures() {
+                self.renderer.unregister_ui_image(texture_id);
+            }
+        }
+    }
+
     /// Starts execution of game engine.
     pub fn run(mut self, mut callback: impl FnMut(MyEvent) + 'static) -> ! {
         let event_loop = self.event_loop.take().unwrap();
 
-        let mut start_time = Instant::now();
+        // Have the closure take ownership of `self`.
+        // `event_loop.run` never returns, therefore resources are dropped explicitly
+        // after the loop was destroyed to ensure they are properly cleaned up.
+        let mut application = Some(self);
         event_loop.run(move |event, _, control_flow| {
-            // Have the closure take ownership of `self`.
-            // `event_loop.run` never returns, therefore we must do this to ensure
-            // the resources are properly cleaned up.
-            let _ = &self;
+            let this = match application.as_mut() {
+                Some(this) => this,
+                None => return,
+            };
+
+            // Event loop sleeps until new events arrive if redraws are on demand.
+            *control_flow = match this.config.redraw_mode() {
+                RedrawMode::Continuous => ControlFlow::Poll,
+                #[cfg(feature = "gamepad")]
+                RedrawMode::OnDemand if this.gilrs.is_some() => {
+                    ControlFlow::WaitUntil(Instant::now() + GAMEPAD_POLL_INTERVAL)
+                }
+                RedrawMode::OnDemand => ControlFlow::Wait,
+            };
+            let destroyed = matches!(event, Event::LoopDestroyed);
+            this.handle_event(event, control_flow, &mut callback);
+            if destroyed {
+                application = None;
+            }
+        })
+    }
 
+    /// Starts execution of game engine with given game state.
+    ///
+    /// State is owned by the event loop and passed into each callback invocation,
+    /// so the callback does not need to capture game state.
+    ///
+    pub fn run_with_state<S>(
+        self,
+        mut state: S,
+        mut callback: impl FnMut(&mut S, MyEvent) + 'static,
+    ) -> !
+    where
+        S: 'static,
+    {
+        self.run(move |event| callback(&mut state, event))
+    }
+
+    /// Processes all pending events and renders at most one frame,
+    /// then returns control to the caller immediately.
+    ///
+    /// Useful when game engine should be driven by an external main loop.
+    /// Returns `false` if application was closed and should not be pumped anymore.
+    ///
+    /// # Panic
+    ///
+    /// This function could panic if invoked after [`run`](Application::run).
+    ///
+    #[cfg(not(any(target_os = "ios", target_arch = "wasm32")))]
+    pub fn pump_events(&mut self, mut callback: impl FnMut(MyEvent)) -> bool {
+        use winit::platform::run_return::EventLoopExtRunReturn;
+
+        let mut event_loop = self
+            .event_loop
+            .take()
+            .expect("event loop was consumed by `run`");
+
+        let mut running = true;
+        event_loop.run_return(|event, _, control_flow| {
             *control_flow = ControlFlow::Poll;
+            match event {
+                // Frame was rendered (if needed), so give control back to the caller.
+                Event::RedrawEventsCleared => {
+                    *control_flow = ControlFlow::Exit;
+                    return;
+                }
+                // Loop is destroyed after each pump, but application is still alive.
+                Event::LoopDestroyed if running => return,
+                _ => (),
+            }
+            self.handle_event(event, control_flow, &mut callback);
+            if *control_flow == ControlFlow::Exit {
+                running = false;
+            }
+        });
+
+        self.event_loop = Some(event_loop);
+        running
+    }
+
+    /// Checks if window was switched into (or out of) fullscreen or maximized mode.
+    ///
+    /// There are no separate events for these changes, so they are inferred
+    /// from resize and move events of the window.
+    ///
+    fn update_window_state<F>(&mut self, callback: &mut F)
+    where
+        F: FnMut(MyEvent),
+    {
+        let window = self.window();
+        let fullscreen = window.fullscreen().is_some();
+        let maximized = window.is_maximized();
+        if fullscreen != self.fullscreen {
+            self.fullscreen = fullscreen;
+            callback(MyEvent::FullscreenChanged(fullscreen));
+        }
+        if maximized != self.maximized {
+            self.maximized = maximized;
+            callback(MyEvent::Maximized(maximized));
+        }
+    }
+
+    /// Delivers fixed updates for the time elapsed since the previous frame,
+    /// then [`Render`](MyEvent::Render) event with interpolation factor.
+    ///
+    /// At most [`MAX_CATCH_UP_STEPS`] updates are delivered, the rest of elapsed time is dropped.
+    ///
+    fn fixed_update<F>(&mut self, step: Duration, elapsed: Duration, callback: &mut F)
+    where
+        F: FnMut(MyEvent),
+    {
+        self.accumulator += elapsed;
+        let mut steps = 0;
+        while self.accumulator >= step {
+            if steps == MAX_CATCH_UP_STEPS {
+                log::debug!("game is updated slower than real time, dropping elapsed time");
+                self.accumulator = Duration::ZERO;
+                break;
+            }
+            callback(MyEvent::Update(step));
+            self.run_systems();
+            self.accumulator -= step;
+            steps += 1;
+        }
+        let interpolation = self.accumulator.as_secs_f32() / step.as_secs_f32();
+        callback(MyEvent::Render(interpolation));
+    }
+
+    /// Delivers live input event to the game, recording it if needed.
+    ///
+    /// Live input is ignored if recorded input is replayed.
+    ///
+    fn deliver_input<F>(&mut self, event: MyEvent, callback: &mut F)
+    where
+        F: FnMut(MyEvent),
+    {
+        if self.input_replay.is_some() {
+            return;
+        }
+        if let Some(input_recorder) = self.input_recorder.as_mut() {
+            let time = self.start_time.elapsed();
+            if let Err(error) = input_recorder.record(self.frame, time, &event) {
+                log::error!("failed to record input, recording is stopped: {}", error);
+                self.input_recorder = None;
+            }
+        }
+        callback(event);
+    }
+
+    /// Delivers pending events of gamepads to the game.
+    ///
+    /// Gamepad events are delivered directly, as focus events are: they could not be recorded
+    /// because gamepad identifiers could not be restored from the recorded input.
+    ///
+    #[cfg(feature = "gamepad")]
+    fn poll_gamepads<F>(&mut self, callback: &mut F)
+    where
+        F: FnMut(MyEvent),
+    {
+        use crate::window::ElementState;
+        use gilrs::EventType;
 
-            // Take `Platform` object from `self` to workaround about borrow checker.
-            let mut egui = self.egui.take().unwrap();
+        let context = match self.gilrs.as_mut() {
+            Some(context) => context,
+            None => return,
+        };
+        let mut events = Vec::new();
+        while let Some(gilrs::Event { id, event, .. }) = context.next_event() {
+            let event = match event {
+                EventType::Connected => MyEvent::GamepadConnected(id),
+                EventType::Disconnected => MyEvent::GamepadDisconnected(id),
+                EventType::ButtonPressed(button, _) => MyEvent::GamepadButton {
+                    id,
+                    button,
+                    state: ElementState::Pressed,
+                },
+                EventType::ButtonReleased(button, _) => MyEvent::GamepadButton {
+                    id,
+                    button,
+                    state: ElementState::Released,
+                },
+                EventType::AxisChanged(axis, value, _) => MyEvent::GamepadAxis { id, axis, value },
+                _ => continue,
+            };
+            events.push(event);
+        }
+
+        if !events.is_empty() {
+            self.redraw_requested = true;
+        }
+        events.into_iter().for_each(callback);
+    }
+
+    /// Handles one event of the event loop: the body of each event loop iteration.
+    fn handle_event<F>(
+        &mut self,
+        event: Event<()>,
+        control_flow: &mut ControlFlow,
+        callback: &mut F,
+    ) where
+        F: FnMut(MyEvent),
+    {
+        // Take `Platform` object from `self` to workaround about borrow checker.
+        let mut egui = self.egui.take().unwrap();
+        self.handle.set_ui_context(egui.context());
+        let redraw_event = matches!(event, Event::RedrawRequested(_));
 
-            // Have this closure to early return if needed (for example if error is occurred).
-            // Closure is needed because `label_break_value` feature is unstable.
-            let action = || {
+        // Have this closure to early return if needed (for example if error is occurred).
+        // Closure is needed because `label_break_value` feature is unstable.
+        let action = || {
+            if self.ui_scale_changed {
+                self.ui_scale_changed = false;
+                self.apply_ui_scale(&mut egui);
+            }
+            // Overridden scale factor of UI must not be replaced by the scale factor of the window.
+            let scale_overridden = self.renderer.ui_scale_override().is_some()
+                && matches!(
+                    event,
+                    Event::WindowEvent {
+                        event: WindowEvent::ScaleFactorChanged { .. },
+                        ..
+                    }
+                );
+            if !scale_overridden {
                 egui.handle_event(&event);
-                egui.update_time(start_time.elapsed().as_secs_f64());
-
-                let window = self.window();
-                match event {
-                    Event::NewEvents(StartCause::Init) => {
-                        start_time = Instant::now();
-                        callback(MyEvent::Created);
-                        window.set_visible(true);
-                    }
-                    Event::WindowEvent { event, window_id } if window_id == window.id() => {
-                        match event {
-                            WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-                            WindowEvent::Resized(size) => {
-                                if size.width == 0 || size.height == 0 {
-                                    callback(MyEvent::Resized(Size::default()));
-                                    return;
-                                }
-                                if let Err(error) = self.renderer.resize() {
-                                    log::error!("window resizing error: {}", error);
-                                    *control_flow = ControlFlow::Exit;
-                                    return;
-                                }
-                                let size = (size.width, size.height);
-                                callback(MyEvent::Resized(size.into()));
+            }
+            egui.update_time(self.start_time.elapsed().as_secs_f64());
+
+            #[cfg(feature = "gamepad")]
+            if matches!(event, Event::MainEventsCleared) {
+                self.poll_gamepads(callback);
+            }
+            let window = self.window();
+            match event {
+                Event::NewEvents(StartCause::Init) if !self.created => {
+                    callback(MyEvent::Created);
+                    window.set_visible(true);
+                    self.start_time = Instant::now();
+                    self.created = true;
+                }
+                Event::WindowEvent { event, window_id } if window_id == window.id() => {
+                    // Any window event (input, resize etc.) could change the frame.
+                    self.redraw_requested = true;
+                    // Input is routed to the UI first, so the game should not handle it again.
+                    if self::consumed_by_ui(&egui.context(), &event) {
+                        return;
+                    }
+                    match event {
+                        WindowEvent::CloseRequested => {
+                            let (request, cancelled) = CloseRequest::new();
+                            callback(MyEvent::CloseRequested(request));
+                            if cancelled.get() {
+                                log::info!("closing was cancelled by the game");
+                                return;
                             }
-                            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                                let size = *new_inner_size;
-                                if size.width == 0 || size.height == 0 {
-                                    callback(MyEvent::Resized(Size::default()));
-                                    return;
-                                }
-                                if let Err(error) = self.renderer.resize() {
-                                    log::error!("window resizing error: {}", error);
-                                    *control_flow = ControlFlow::Exit;
-                                    return;
-                                }
-                                let size = (size.width, size.height);
-                                callback(MyEvent::Resized(size.into()));
+                            *control_flow = ControlFlow::Exit;
+                        }
+                        WindowEvent::Resized(size) => {
+                            self.update_window_state(callback);
+                            if size.width == 0 || size.height == 0 {
+                                callback(MyEvent::Resized(Size::default()));
+                                return;
                             }
-                            _ => (),
+                            // Recreation is deferred until the next frame,
+                            // so continuous resizing does not recreate swapchain for each event.
+                            self.renderer.request_resize();
+                            callback(MyEvent::Resized(size.into()));
                         }
-                    }
-                    Event::MainEventsCleared => {
-                        let size = window.inner_size();
-                        if size.width == 0 || size.height == 0 {
-                            return;
+                        WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                            let size = *new_inner_size;
+                            if size.width == 0 || size.height == 0 {
+                                callback(MyEvent::Resized(Size::default()));
+                                return;
+                            }
+                            // Recreation is deferred until the next frame,
+                            // so continuous resizing does not recreate swapchain for each event.
+                            self.renderer.request_resize();
+                            callback(MyEvent::Resized(size.into()));
+                        }
+                        WindowEvent::Moved(_) => self.update_window_state(callback),
+                        WindowEvent::ReceivedCharacter(character) => {
+                            self.deliver_input(MyEvent::TextInput(character), callback)
+                        }
+                        WindowEvent::KeyboardInput { input, .. } => {
+                            // Keys without virtual key code cannot be handled by the game anyway.
+                            if let Some(key) = input.virtual_keycode {
+                                let state = input.state;
+                                self.deliver_input(MyEvent::Keyboard { key, state }, callback)
+                            }
                         }
+                        WindowEvent::MouseInput { button, state, .. } => {
+                            self.deliver_input(MyEvent::MouseButton { button, state }, callback)
+                        }
+                        WindowEvent::CursorMoved { position, .. } => {
+                            let event = MyEvent::CursorMoved(position.x, position.y);
+                            self.deliver_input(event, callback)
+                        }
+                        // These events are never consumed by the UI, which has already seen them,
+                        // and are not recorded as input, because they depend on the window system.
+                        WindowEvent::Focused(focused) => callback(MyEvent::Focused(focused)),
+                        WindowEvent::CursorEntered { .. } => callback(MyEvent::CursorEntered(true)),
+                        WindowEvent::CursorLeft { .. } => callback(MyEvent::CursorEntered(false)),
+                        _ => (),
+                    }
+                }
+                Event::MainEventsCleared => {
+                    let size = window.inner_size();
+                    if size.width == 0 || size.height == 0 {
+                        return;
+                    }
+                    let redraw_mode = self.config.redraw_mode();
+                    if redraw_mode == RedrawMode::Continuous || self.redraw_requested {
                         window.request_redraw();
                     }
-                    Event::RedrawRequested(window_id) if window_id == window.id() => {
-                        let size = window.inner_size();
-                        if size.width == 0 || size.height == 0 {
-                            return;
+                }
+                Event::RedrawRequested(window_id) if window_id == window.id() => {
+                    let size = window.inner_size();
+                    if size.width == 0 || size.height == 0 {
+                        return;
+                    }
+                    let frame_start = Instant::now();
+
+                    if let Some(input_replay) = self.input_replay.as_mut() {
+                        while let Some(event) = input_replay.next(self.frame) {
+                            callback(event);
                         }
-                        let frame_start = Instant::now();
+                    }
 
-                        egui.begin_frame();
-                        let context = egui.context();
-                        callback(MyEvent::UI(context.clone()));
-                        let (_output, shapes) = egui.end_frame(Some(window));
-                        let meshes = context.tessellate(shapes);
-                        let texture = context.texture();
+                    let elapsed = self
+                        .last_frame
+                        .map_or(Duration::ZERO, |last_frame| frame_start - last_frame);
+                    self.last_frame = Some(frame_start);
+                    if let Some(step) = self.config.fixed_timestep() {
+                        self.fixed_update(step, elapsed, callback);
+                    }
 
-                        if let Err(error) = self.renderer.render(Some((meshes, texture))) {
-                            log::error!("rendering error: {}", error);
-                            *control_flow = ControlFlow::Exit;
-                            return;
-                        }
+                    egui.begin_frame();
+                    let context = egui.context();
+                    callback(MyEvent::UI(context.clone()));
+                    let (output, shapes) = egui.end_frame(Some(self.window()));
+                    // UI could request repaint (for example, for animations).
+                    self.redraw_requested = output.needs_repaint;
+                    let meshes = context.tessellate(shapes);
+                    let texture = context.texture();
+
+                    let aspect = Size::from(size).aspect_ratio();
+                    if let Some(camera) = self.camera.as_ref() {
+                        self.renderer.set_camera_ubo(camera.ubo_for_aspect(aspect));
+                    }
+
+                    if let Err(error) = self.renderer.render(Some((meshes, texture))) {
+                        log::error!("rendering error: {}", error);
+                        *control_flow = ControlFlow::Exit;
+                        return;
+                    }
+                    if self.renderer.take_device_recreated() {
+                        callback(MyEvent::DeviceRecreated);
+                    }
+                    if let Some(size) = self.renderer.take_swapchain_recreated() {
+                        callback(MyEvent::SwapchainRecreated(size));
+                    }
+                    if self.config.fixed_timestep().is_none() {
                         let delta_time = Instant::now().duration_since(frame_start);
                         callback(MyEvent::Update(delta_time));
+                        self.run_systems();
+                    }
+                    self.frame += 1;
 
-                        let ubo = {
-                            let duration = Instant::now().duration_since(start_time);
-                            let elapsed = duration.as_millis() as f32;
-
-                            use ultraviolet::projection::perspective_vk as perspective;
-                            let projection = perspective(
-                                45f32.to_radians(),
-                                (size.width as f32) / (size.height as f32),
-                                1.0,
-                                10.0,
-                            );
-                            let model = Mat4::from_rotation_z(elapsed * 0.1f32.to_radians());
-                            let view = Mat4::look_at(
-                                Vec3::new(2.0, 2.0, 2.0),
-                                Vec3::zero(),
-                                Vec3::unit_z(),
-                            );
-                            CameraUBO::new(projection, model, view)
-                        };
-                        self.renderer.set_camera_ubo(ubo);
-                    }
-                    Event::LoopDestroyed => {
-                        callback(MyEvent::Destroyed);
-                        log::info!("closing this application");
-                    }
-                    _ => (),
+                    // Camera of the game takes precedence over the demo one.
+                    if !self.config.demo_scene() || self.camera.is_some() {
+                        return;
+                    }
+                    let elapsed = Instant::now().duration_since(self.start_time);
+                    self.renderer
+                        .set_camera_ubo(self::demo_camera_ubo(elapsed, aspect));
                 }
-            };
-            action();
+                Event::LoopDestroyed => {
+                    // GPU must finish all the work before the game saves its state
+                    // and GPU resources are freed.
+                    if let Err(error) = self.renderer.shut_down() {
+                        log::error!("waiting for GPU to finish its work failed: {}", error);
+                    }
+                    callback(MyEvent::Destroyed);
+                    if let Some(mut input_recorder) = self.input_recorder.take() {
+                        if let Err(error) = input_recorder.flush() {
+                            log::error!("failed to write recorded input: {}", error);
+                        }
+                    }
+                    // Stop playing audio right away rather than when application is dropped.
+                    #[cfg(feature = "audio")]
+                    drop(self.audio.take());
+                    log::info!("closing this application");
+                }
+                _ => (),
+            }
+        };
+        action();
 
-            // Assign `Platform` object back to `self`.
-            self.egui = Some(egui);
-        })
+        // Assign `Platform` object back to `self`.
+        self.egui = Some(egui);
+
+        let requests = self.handle.take_requests();
+        if requests.redraw {
+            self.redraw_requested = true;
+        }
+        if requests.shutdown {
+            self.shutdown_requested = true;
+        }
+        if let Some(camera) = requests.camera {
+            self.set_camera(camera);
+        }
+        // Event loop sleeps after the frame if redraws are on demand, so redraw requested
+        // while rendering (by the UI for animations or by the game) must wake it up.
+        // Redraws requested by other events are handled on `MainEventsCleared`.
+        if self.redraw_requested && (redraw_event || requests.redraw) {
+            self.window().request_redraw();
+        }
+
+        if self.shutdown_requested {
+            *control_flow = ControlFlow::Exit;
+        }
+    }
+}
+
+/// Checks if input event was consumed by the UI and should not be delivered to the game.
+fn consumed_by_ui(context: &CtxRef, event: &WindowEvent) -> bool {
+    match event {
+        WindowEvent::ReceivedCharacter(_)
+        | WindowEvent::KeyboardInput { .. }
+        | WindowEvent::ModifiersChanged(_) => context.wants_keyboard_input(),
+        WindowEvent::CursorMoved { .. }
+        | WindowEvent::MouseInput { .. }
+        | WindowEvent::MouseWheel { .. }
+        | WindowEvent::Touch(_) => context.wants_pointer_input(),
+        _ => false,
     }
 }
 
@@ -217,20 +1319,43 @@ pub fn run(mut self, mut callback: impl FnMut(MyEvent) + 'static) -> ! {
 ///
 pub fn init(config: Config) -> Result<Application> {
     static FLAG: AtomicBool = AtomicBool::new(false);
+
+    self::mark_initialized(&FLAG)?;
+    Application::new(config)
+}
+
+/// Marks application as initialized with given flag.
+///
+/// # Errors
+///
+/// An error is returned if the flag was already set by previous call.
+///
+fn mark_initialized(flag: &AtomicBool) -> Result<()> {
     const UNINITIALIZED: bool = false;
     const INITIALIZED: bool = true;
 
-    let initialized = FLAG
-        .compare_exchange(
-            UNINITIALIZED,
-            INITIALIZED,
-            Ordering::SeqCst,
-            Ordering::SeqCst,
-        )
-        .unwrap();
+    // On failure, exchange returns the current value, i.e. application is already initialized.
+    flag.compare_exchange(
+        UNINITIALIZED,
+        INITIALIZED,
+        Ordering::SeqCst,
+        Ordering::SeqCst,
+    )
+    .map(|_| ())
+    .map_err(|_| AppCreationError::Initialized)
+}
 
-    if initialized {
-        return Err(AppCreationError::Initialized);
-    }
-    Application::new(config)
+/// Builds camera UBO of the demo scene for the render target with given aspect ratio.
+///
+/// Demo scene assumes Z-up coordinate system.
+///
+fn demo_camera_ubo(elapsed: Duration, aspect: f32) -> CameraUBO {
+    use ultraviolet::projection::perspective_vk as perspective;
+
+    let elapsed = elapsed.as_millis() as f32;
+    let projection = perspective(45f32.to_radians(), aspect, 1.0, 10.0);
+    // Camera orbits around the cube, so the cube appears to be rotating.
+    let eye = Mat3::from_rotation_z(-elapsed * 0.1f32.to_radians()) * Vec3::new(2.0, 2.0, 2.0);
+    let view = Mat4::look_at(eye, Vec3::zero(), Vec3::unit_z());
+    CameraUBO::new(projection, view)
 }
@@ -0,0 +1,66 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_init_once() {
+    let flag = AtomicBool::new(false);
+
+    assert!(mark_initialized(&flag).is_ok());
+    assert!(matches!(
+        mark_initialized(&flag),
+        Err(AppCreationError::Initialized)
+    ));
+}
+
+#[test]
+fn test_input_round_trip() {
+    use crate::window::{ElementState, MouseButton, VirtualKeyCode};
+
+    // Path is unique per process, so concurrent test runs do not share the file.
+    let path = std::env::temp_dir().join(format!(
+        "titan_test_input_round_trip_{}.txt",
+        std::process::id()
+    ));
+    let mut recorder = InputRecorder::create(&path).unwrap();
+    let events = [
+        MyEvent::TextInput('ж'),
+        MyEvent::Keyboard {
+            key: VirtualKeyCode::NumpadEnter,
+            state: ElementState::Pressed,
+        },
+        MyEvent::MouseButton {
+            button: MouseButton::Other(4),
+            state: ElementState::Released,
+        },
+        MyEvent::CursorMoved(12.5, 0.1),
+        MyEvent::Focused(true),
+    ];
+    for (frame, event) in events.iter().enumerate() {
+        recorder
+            .record(frame as u64, Duration::from_millis(16), event)
+            .unwrap();
+    }
+    recorder.flush().unwrap();
+
+    let mut replay = InputReplay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(replay.next(0), Some(MyEvent::TextInput('ж'))));
+    assert!(matches!(
+        replay.next(1),
+        Some(MyEvent::Keyboard {
+            key: VirtualKeyCode::NumpadEnter,
+            state: ElementState::Pressed,
+        })
+    ));
+    assert!(matches!(
+        replay.next(2),
+        Some(MyEvent::MouseButton {
+            button: MouseButton::Other(4),
+            state: ElementState::Released,
+        })
+    ));
+    assert!(matches!(replay.next(3), Some(MyEvent::CursorMoved(x, y)) if x == 12.5 && y == 0.1));
+    // Events which are not input are not recorded.
+    assert!(replay.next(4).is_none());
+}
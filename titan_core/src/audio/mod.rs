@@ -0,0 +1,92 @@
+//! Audio playback for game engine.
+//!
+//! Available only with `audio` feature enabled.
+//!
+
+use std::io::Cursor;
+
+use rodio::decoder::DecoderError;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, PlayError, Sink, Source, StreamError};
+use thiserror::Error;
+
+/// Error of audio engine creation.
+#[derive(Debug, Error)]
+pub enum AudioCreationError {
+    #[error("failed to open audio output stream: {0}")]
+    Stream(#[from] StreamError),
+
+    #[error("failed to create music sink: {0}")]
+    Sink(#[from] PlayError),
+}
+
+/// Error of sound or music playback.
+#[derive(Debug, Error)]
+pub enum PlaybackError {
+    #[error("failed to decode audio: {0}")]
+    Decode(#[from] DecoderError),
+
+    #[error("failed to play audio: {0}")]
+    Play(#[from] PlayError),
+}
+
+/// Simple audio engine which plays sounds and background music
+/// through the default output device.
+pub struct AudioEngine {
+    music: Sink,
+    handle: OutputStreamHandle,
+    // Stream must outlive everything played through its handle.
+    _stream: OutputStream,
+}
+
+impl AudioEngine {
+    /// Opens default audio output device.
+    pub fn new() -> Result<Self, AudioCreationError> {
+        let (stream, handle) = OutputStream::try_default()?;
+        let music = Sink::try_new(&handle)?;
+        Ok(Self {
+            music,
+            handle,
+            _stream: stream,
+        })
+    }
+
+    /// Plays sound from encoded audio data (WAV, Vorbis, FLAC or MP3) once.
+    ///
+    /// Sounds are mixed together with each other and with music.
+    ///
+    pub fn play_sound(&self, bytes: &[u8]) -> Result<(), PlaybackError> {
+        let source = Decoder::new(Cursor::new(bytes.to_vec()))?;
+        self.handle.play_raw(source.convert_samples())?;
+        Ok(())
+    }
+
+    /// Plays music from encoded audio data (WAV, Vorbis, FLAC or MP3),
+    /// replacing currently playing music.
+    ///
+    /// If `looped` is `true`, music will be repeated until it is stopped or replaced.
+    ///
+    pub fn play_music(&mut self, bytes: Vec<u8>, looped: bool) -> Result<(), PlaybackError> {
+        let source = Decoder::new(Cursor::new(bytes))?;
+        // Stopped sink cannot be reused, so it is replaced with the new one.
+        let volume = self.music.volume();
+        self.music.stop();
+        self.music = Sink::try_new(&self.handle)?;
+        self.music.set_volume(volume);
+        if looped {
+            self.music.append(source.repeat_infinite());
+        } else {
+            self.music.append(source);
+        }
+        Ok(())
+    }
+
+    /// Stops currently playing music.
+    pub fn stop_music(&self) {
+        self.music.stop();
+    }
+
+    /// Sets volume of the music, where `1.0` is the original volume.
+    pub fn set_music_volume(&self, volume: f32) {
+        self.music.set_volume(volume);
+    }
+}
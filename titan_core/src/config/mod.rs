@@ -1,6 +1,10 @@
 //! Configuration utilities for game engine and your game.
 
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
 use semver::Version;
+use thiserror::Error;
 
 /// This struct represents general configuration of game engine.
 #[derive(Debug, Clone)]
@@ -8,6 +12,237 @@ pub struct Config {
     name: String,
     version: Version,
     enable_validation: bool,
+    demo_scene: bool,
+    frames_in_flight: usize,
+    low_latency: bool,
+    depth_format: Option<DepthFormat>,
+    depth_readback: bool,
+    window_size: Option<(u32, u32)>,
+    min_window_size: Option<(u32, u32)>,
+    max_window_size: Option<(u32, u32)>,
+    window_position: Option<(i32, i32)>,
+    transparent_window: bool,
+    resizable: bool,
+    fullscreen: Option<FullscreenMode>,
+    cull_mode: CullMode,
+    front_face: FrontFace,
+    wireframe: bool,
+    redraw_mode: RedrawMode,
+    coordinate_system: CoordinateSystem,
+    render_threads: Option<usize>,
+    depth_test: bool,
+    transparent_objects: bool,
+    ui_sampler_filter: SamplerFilter,
+    replay_input: Option<PathBuf>,
+    trace_allocations: bool,
+    vsync: bool,
+    msaa_samples: u8,
+    preferred_device: PreferredDevice,
+    queue_config: QueueConfig,
+    fixed_timestep: Option<Duration>,
+    hdr: bool,
+}
+
+/// Format of depth (and stencil) buffer which is used for rendering.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DepthFormat {
+    /// 16-bit depth without stencil.
+    D16Unorm,
+    /// 32-bit floating point depth without stencil.
+    D32Sfloat,
+    /// 16-bit depth with 8-bit stencil.
+    D16UnormS8Uint,
+    /// 24-bit depth with 8-bit stencil.
+    D24UnormS8Uint,
+    /// 32-bit floating point depth with 8-bit stencil.
+    D32SfloatS8Uint,
+}
+
+impl DepthFormat {
+    /// If this format has stencil aspect.
+    pub const fn has_stencil(self) -> bool {
+        !matches!(self, Self::D16Unorm | Self::D32Sfloat)
+    }
+}
+
+/// Which faces of game objects are discarded while rendering.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CullMode {
+    /// Back faces are discarded.
+    Back,
+    /// Front faces are discarded.
+    Front,
+    /// No faces are discarded.
+    None,
+}
+
+impl Default for CullMode {
+    fn default() -> Self {
+        Self::Back
+    }
+}
+
+/// Vertex winding order of faces which are considered as front-facing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FrontFace {
+    /// Faces with clockwise vertex winding are front-facing.
+    Clockwise,
+    /// Faces with counter-clockwise vertex winding are front-facing.
+    CounterClockwise,
+}
+
+impl Default for FrontFace {
+    fn default() -> Self {
+        Self::CounterClockwise
+    }
+}
+
+/// Fullscreen mode of the window.
+///
+/// Monitors and their video modes are referenced by their indices
+/// in [`Application::available_monitors`](crate::app::Application::available_monitors).
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FullscreenMode {
+    /// Window covers the whole monitor without changing its video mode.
+    Borderless { monitor: usize },
+    /// Window exclusively owns the monitor with given video mode.
+    Exclusive { monitor: usize, video_mode: usize },
+}
+
+/// Describes when game engine renders new frames.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RedrawMode {
+    /// New frame is rendered as fast as possible.
+    Continuous,
+    /// New frame is rendered only when something was changed
+    /// (window event, UI repaint or explicit request of the game).
+    ///
+    /// Event loop sleeps while there is nothing to render,
+    /// which is useful for editors and turn-based games to save power.
+    OnDemand,
+}
+
+impl Default for RedrawMode {
+    fn default() -> Self {
+        Self::Continuous
+    }
+}
+
+/// Convention of the world coordinate system: which axis points up.
+///
+/// Both conventions are right-handed. Camera looks along its local forward axis:
+/// `-Z` for [`YUp`](CoordinateSystem::YUp) and `+Y` for [`ZUp`](CoordinateSystem::ZUp).
+///
+/// Engine convention is [`ZUp`](CoordinateSystem::ZUp), which is the default.
+/// Only camera helpers ([`Camera`], [`CameraRig`] and [`FreeCamera`]) respect it,
+/// and they take the convention explicitly (for example, from [`Config::coordinate_system`]).
+/// Meshes, transforms and lights are not converted between conventions.
+///
+/// [`Camera`]: crate::camera::Camera
+/// [`CameraRig`]: crate::camera::CameraRig
+/// [`FreeCamera`]: crate::camera::FreeCamera
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CoordinateSystem {
+    /// Y axis points up, as in Vulkan view space (no basis change is needed).
+    YUp,
+    /// Z axis points up, as in most modeling tools.
+    ///
+    /// Built-in demo scene and primitive meshes assume this convention.
+    ZUp,
+}
+
+impl Default for CoordinateSystem {
+    fn default() -> Self {
+        Self::ZUp
+    }
+}
+
+/// Filter which is used to sample textures.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SamplerFilter {
+    /// Linear interpolation between texels (smooth result).
+    Linear,
+    /// Nearest texel is used (crisp result, useful for pixel-art).
+    Nearest,
+}
+
+impl Default for SamplerFilter {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
+/// Physical device (GPU) which is preferred for rendering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreferredDevice {
+    /// The most suitable device is chosen automatically (discrete GPU is preferred).
+    Auto,
+    /// Device with given index in the list of all devices of the system.
+    Index(usize),
+    /// Device with given name (see [`enumerate_device_names`]).
+    ///
+    /// [`enumerate_device_names`]: crate::app::Application::enumerate_device_names
+    Name(String),
+}
+
+impl Default for PreferredDevice {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
+/// Count and priority of device queues which are requested for some kind of work.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct QueueRequest {
+    /// Count of queues, at least one queue is always created.
+    pub count: u32,
+    /// Priority of the queues in range `0.0..=1.0`.
+    pub priority: f32,
+}
+
+impl QueueRequest {
+    /// Creates new request of queues with given count and priority.
+    pub const fn new(count: u32, priority: f32) -> Self {
+        Self { count, priority }
+    }
+}
+
+impl Default for QueueRequest {
+    fn default() -> Self {
+        Self::new(1, 1.0)
+    }
+}
+
+/// Queues of the device which are requested for each kind of work.
+///
+/// Kinds of work share the same queue family if there is no dedicated one for some of them.
+/// If queue family cannot provide requested count of queues, fewer queues are created.
+///
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct QueueConfig {
+    /// Queues used to render frames.
+    pub graphics: QueueRequest,
+    /// Queues used to upload data into GPU.
+    pub transfer: QueueRequest,
+    /// Queues used to dispatch compute work.
+    pub compute: QueueRequest,
+}
+
+impl QueueConfig {
+    /// Creates new configuration of queues.
+    pub const fn new(
+        graphics: QueueRequest,
+        transfer: QueueRequest,
+        compute: QueueRequest,
+    ) -> Self {
+        Self {
+            graphics,
+            transfer,
+            compute,
+        }
+    }
 }
 
 pub const ENGINE_NAME: &str = env!("CARGO_CRATE_NAME", "library must be compiled by Cargo");
@@ -24,9 +259,47 @@ pub const fn new(name: String, version: Version, enable_validation: bool) -> Sel
             name,
             version,
             enable_validation,
+            demo_scene: false,
+            frames_in_flight: 2,
+            low_latency: false,
+            depth_format: None,
+            depth_readback: false,
+            window_size: None,
+            min_window_size: Some((250, 100)),
+            max_window_size: None,
+            window_position: None,
+            transparent_window: false,
+            resizable: true,
+            fullscreen: None,
+            cull_mode: CullMode::Back,
+            front_face: FrontFace::CounterClockwise,
+            wireframe: false,
+            redraw_mode: RedrawMode::Continuous,
+            coordinate_system: CoordinateSystem::ZUp,
+            render_threads: None,
+            depth_test: true,
+            transparent_objects: false,
+            ui_sampler_filter: SamplerFilter::Linear,
+            replay_input: None,
+            trace_allocations: false,
+            vsync: false,
+            msaa_samples: 1,
+            preferred_device: PreferredDevice::Auto,
+            queue_config: QueueConfig::new(
+                QueueRequest::new(1, 1.0),
+                QueueRequest::new(1, 1.0),
+                QueueRequest::new(1, 1.0),
+            ),
+            fixed_timestep: None,
+            hdr: false,
         }
     }
 
+    /// Creates builder of configuration which starts from the [default](Config::default) one.
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::new()
+    }
+
     /// Name of your game.
     pub fn name(&self) -> &str {
         &self.name
@@ -41,6 +314,494 @@ pub fn version(&self) -> &Version {
     pub fn enable_validation(&self) -> bool {
         self.enable_validation
     }
+
+    /// If game engine will render built-in demo scene (spinning cube with fixed camera).
+    ///
+    /// Disabled by default, so only game objects submitted by the game are rendered.
+    pub fn demo_scene(&self) -> bool {
+        self.demo_scene
+    }
+
+    /// Enables or disables rendering of built-in demo scene.
+    pub fn set_demo_scene(&mut self, demo_scene: bool) {
+        self.demo_scene = demo_scene;
+    }
+
+    /// Count of frames which could be processed by GPU at the same time.
+    ///
+    /// Per-frame resources (such as uniform buffers) are created for each frame in flight.
+    pub fn frames_in_flight(&self) -> usize {
+        self.frames_in_flight
+    }
+
+    /// Sets count of frames in flight, which must be at least `1`.
+    pub fn set_frames_in_flight(&mut self, frames_in_flight: usize) {
+        self.frames_in_flight = frames_in_flight.max(1);
+    }
+
+    /// If rendering of the next frame waits for the previous frame to be completed by GPU.
+    ///
+    /// This reduces input latency at the cost of throughput,
+    /// effectively limiting count of frames in flight to one.
+    ///
+    pub fn low_latency(&self) -> bool {
+        self.low_latency
+    }
+
+    /// Sets if rendering of the next frame waits for the previous frame to be completed by GPU.
+    pub fn set_low_latency(&mut self, low_latency: bool) {
+        self.low_latency = low_latency;
+    }
+
+    /// Format of depth buffer which should be used instead of automatically chosen one.
+    ///
+    /// If the format is not supported by the device,
+    /// automatically chosen format will be used instead.
+    pub fn depth_format(&self) -> Option<DepthFormat> {
+        self.depth_format
+    }
+
+    /// Overrides format of depth buffer, `None` means automatic choice.
+    pub fn set_depth_format(&mut self, depth_format: Option<DepthFormat>) {
+        self.depth_format = depth_format;
+    }
+
+    /// If depth of rendered frames could be read back (for example, to pick objects by cursor).
+    ///
+    /// Depth buffer is stored into memory after each frame only if enabled,
+    /// so it is disabled by default. Depth formats with stencil could not be read back.
+    ///
+    pub fn depth_readback(&self) -> bool {
+        self.depth_readback
+    }
+
+    /// Sets if depth of rendered frames could be read back.
+    pub fn set_depth_readback(&mut self, depth_readback: bool) {
+        self.depth_readback = depth_readback;
+    }
+
+    /// Initial logical size of the window, `None` means size is chosen by the OS.
+    pub fn window_size(&self) -> Option<(u32, u32)> {
+        self.window_size
+    }
+
+    /// Sets initial logical size of the window, `None` means size is chosen by the OS.
+    pub fn set_window_size(&mut self, window_size: Option<(u32, u32)>) {
+        self.window_size = window_size;
+    }
+
+    /// Min logical size of the window, `None` means no constraint.
+    pub fn min_window_size(&self) -> Option<(u32, u32)> {
+        self.min_window_size
+    }
+
+    /// Sets min logical size of the window, `None` means no constraint.
+    pub fn set_min_window_size(&mut self, min_window_size: Option<(u32, u32)>) {
+        self.min_window_size = min_window_size;
+    }
+
+    /// Max logical size of the window, `None` means no constraint.
+    pub fn max_window_size(&self) -> Option<(u32, u32)> {
+        self.max_window_size
+    }
+
+    /// Sets max logical size of the window, `None` means no constraint.
+    pub fn set_max_window_size(&mut self, max_window_size: Option<(u32, u32)>) {
+        self.max_window_size = max_window_size;
+    }
+
+    /// Initial physical position of the window on the desktop,
+    /// `None` means position is chosen by the OS.
+    pub fn window_position(&self) -> Option<(i32, i32)> {
+        self.window_position
+    }
+
+    /// Sets initial physical position of the window on the desktop,
+    /// `None` means position is chosen by the OS.
+    pub fn set_window_position(&mut self, window_position: Option<(i32, i32)>) {
+        self.window_position = window_position;
+    }
+
+    /// If the window can be resized by the user.
+    pub fn resizable(&self) -> bool {
+        self.resizable
+    }
+
+    /// Sets if the window can be resized by the user.
+    pub fn set_resizable(&mut self, resizable: bool) {
+        self.resizable = resizable;
+    }
+
+    /// If background of the window is transparent, so the desktop is visible through it.
+    pub fn transparent_window(&self) -> bool {
+        self.transparent_window
+    }
+
+    /// Sets if background of the window is transparent, so the desktop is visible through it.
+    ///
+    /// Window is opaque anyway if transparency is not supported by the platform.
+    ///
+    pub fn set_transparent_window(&mut self, transparent_window: bool) {
+        self.transparent_window = transparent_window;
+    }
+
+    /// Fullscreen mode of the window on start, `None` means windowed mode.
+    pub fn fullscreen(&self) -> Option<FullscreenMode> {
+        self.fullscreen
+    }
+
+    /// Sets fullscreen mode of the window on start, `None` means windowed mode.
+    ///
+    /// If monitor or video mode does not exist, window starts in windowed mode.
+    ///
+    pub fn set_fullscreen(&mut self, fullscreen: Option<FullscreenMode>) {
+        self.fullscreen = fullscreen;
+    }
+
+    /// Which faces of game objects are discarded while rendering.
+    pub fn cull_mode(&self) -> CullMode {
+        self.cull_mode
+    }
+
+    /// Sets which faces of game objects are discarded while rendering.
+    pub fn set_cull_mode(&mut self, cull_mode: CullMode) {
+        self.cull_mode = cull_mode;
+    }
+
+    /// Vertex winding order of front-facing faces of game objects.
+    ///
+    /// Change it if imported models are rendered inside-out.
+    pub fn front_face(&self) -> FrontFace {
+        self.front_face
+    }
+
+    /// Sets vertex winding order of front-facing faces of game objects.
+    pub fn set_front_face(&mut self, front_face: FrontFace) {
+        self.front_face = front_face;
+    }
+
+    /// Draw only edges of game objects (useful for debugging geometry).
+    pub fn wireframe(&self) -> bool {
+        self.wireframe
+    }
+
+    /// Sets if only edges of game objects are drawn (useful for debugging geometry).
+    ///
+    /// UI is always drawn filled. Requires device which supports non-solid fill mode,
+    /// otherwise renderer creation fails.
+    ///
+    pub fn set_wireframe(&mut self, wireframe: bool) {
+        self.wireframe = wireframe;
+    }
+
+    /// Describes when game engine renders new frames.
+    pub fn redraw_mode(&self) -> RedrawMode {
+        self.redraw_mode
+    }
+
+    /// Sets when game engine renders new frames.
+    pub fn set_redraw_mode(&mut self, redraw_mode: RedrawMode) {
+        self.redraw_mode = redraw_mode;
+    }
+
+    /// Convention of the world coordinate system used to build camera view matrix,
+    /// Z up by default (see [`CoordinateSystem`] for which code respects it).
+    pub fn coordinate_system(&self) -> CoordinateSystem {
+        self.coordinate_system
+    }
+
+    /// Sets convention of the world coordinate system used to build camera view matrix.
+    pub fn set_coordinate_system(&mut self, coordinate_system: CoordinateSystem) {
+        self.coordinate_system = coordinate_system;
+    }
+
+    /// Count of threads used to record draw commands in parallel.
+    ///
+    /// `None` means that count of threads is equal to count of logical CPUs.
+    pub fn render_threads(&self) -> Option<usize> {
+        self.render_threads
+    }
+
+    /// Sets count of threads used to record draw commands in parallel,
+    /// which must be at least `1` if provided.
+    pub fn set_render_threads(&mut self, render_threads: Option<usize>) {
+        self.render_threads = render_threads.map(|threads| threads.max(1));
+    }
+
+    /// If depth test is used while rendering game objects.
+    ///
+    /// If disabled, game objects are drawn in order of their layers,
+    /// which is useful for 2D content.
+    pub fn depth_test(&self) -> bool {
+        self.depth_test
+    }
+
+    /// Enables or disables depth test while rendering game objects.
+    pub fn set_depth_test(&mut self, depth_test: bool) {
+        self.depth_test = depth_test;
+    }
+
+    /// Game objects are blended with what is already drawn using alpha of their color.
+    pub fn transparent_objects(&self) -> bool {
+        self.transparent_objects
+    }
+
+    /// Sets if game objects are blended with what is already drawn using alpha of their color.
+    ///
+    /// Game objects do not write depth then, and blending depends on draw order:
+    /// the game is responsible for sorting them back-to-front (see [`RenderInstance::layer`]).
+    ///
+    /// [`RenderInstance::layer`]: crate::RenderInstance::layer
+    ///
+    pub fn set_transparent_objects(&mut self, transparent_objects: bool) {
+        self.transparent_objects = transparent_objects;
+    }
+
+    /// Filter which is used to sample UI textures (including text).
+    pub fn ui_sampler_filter(&self) -> SamplerFilter {
+        self.ui_sampler_filter
+    }
+
+    /// Sets filter which is used to sample UI textures (including text).
+    ///
+    /// Use [`SamplerFilter::Nearest`] for crisp pixel-art UI.
+    pub fn set_ui_sampler_filter(&mut self, ui_sampler_filter: SamplerFilter) {
+        self.ui_sampler_filter = ui_sampler_filter;
+    }
+
+    /// File with recorded input events which are replayed instead of live input,
+    /// `None` means live input is used.
+    ///
+    /// Input could be recorded
+    /// with [`Application::record_input`](crate::app::Application::record_input).
+    ///
+    pub fn replay_input(&self) -> Option<&Path> {
+        self.replay_input.as_deref()
+    }
+
+    /// Sets file with recorded input events which are replayed instead of live input,
+    /// `None` means live input is used.
+    pub fn set_replay_input(&mut self, replay_input: Option<PathBuf>) {
+        self.replay_input = replay_input;
+    }
+
+    /// If warnings are logged when GPU resources (buffers, images, descriptor sets)
+    /// are created while rendering frames.
+    ///
+    /// Such resources should be created once and reused, so this helps to find
+    /// the cause of frame time spikes. Has no effect in release builds.
+    ///
+    pub fn trace_allocations(&self) -> bool {
+        self.trace_allocations
+    }
+
+    /// Sets if warnings are logged when GPU resources are created while rendering frames.
+    pub fn set_trace_allocations(&mut self, trace_allocations: bool) {
+        self.trace_allocations = trace_allocations;
+    }
+
+    /// If presentation is synchronized with vertical blank of the display.
+    ///
+    /// Vertical synchronization prevents tearing, but limits frame rate
+    /// by refresh rate of the display.
+    ///
+    pub fn vsync(&self) -> bool {
+        self.vsync
+    }
+
+    /// Sets if presentation is synchronized with vertical blank of the display.
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.vsync = vsync;
+    }
+
+    /// Sample count of multisample anti-aliasing (MSAA) of game objects, `1` means disabled.
+    ///
+    /// If the device does not support this sample count, the highest supported one is used.
+    ///
+    pub fn msaa_samples(&self) -> u8 {
+        self.msaa_samples
+    }
+
+    /// Sets sample count of multisample anti-aliasing (MSAA) of game objects.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if sample count is not one of `1`, `2`, `4` or `8`,
+    /// then current sample count is kept.
+    ///
+    pub fn set_msaa_samples(&mut self, msaa_samples: u8) -> Result<(), ConfigError> {
+        self.msaa_samples = self::validate_msaa_samples(msaa_samples)?;
+        Ok(())
+    }
+
+    /// Physical device (GPU) which is preferred for rendering.
+    ///
+    /// If preferred device does not exist or is not suitable for rendering,
+    /// warning is logged and the device is chosen automatically.
+    ///
+    pub fn preferred_device(&self) -> &PreferredDevice {
+        &self.preferred_device
+    }
+
+    /// Sets physical device (GPU) which is preferred for rendering.
+    pub fn set_preferred_device(&mut self, preferred_device: PreferredDevice) {
+        self.preferred_device = preferred_device;
+    }
+
+    /// Queues of the device which are requested for each kind of work.
+    pub fn queue_config(&self) -> QueueConfig {
+        self.queue_config
+    }
+
+    /// Sets queues of the device which are requested for each kind of work
+    /// (for example, multiple compute queues for compute-heavy workload).
+    pub fn set_queue_config(&mut self, queue_config: QueueConfig) {
+        self.queue_config = queue_config;
+    }
+
+    /// Constant time step of game updates, `None` means game is updated once per frame.
+    ///
+    /// With fixed time step, [`Update`](crate::window::Event::Update) is delivered
+    /// zero or more times per frame with this delta time, followed by
+    /// [`Render`](crate::window::Event::Render). To prevent the game from falling behind
+    /// forever when updates are slower than real time, at most
+    /// [`MAX_CATCH_UP_STEPS`](crate::app::MAX_CATCH_UP_STEPS) updates are delivered
+    /// per frame and the rest of elapsed time is dropped, so the game slows down instead.
+    ///
+    pub fn fixed_timestep(&self) -> Option<Duration> {
+        self.fixed_timestep
+    }
+
+    /// Sets constant time step of game updates, `None` means game is updated once per frame.
+    ///
+    /// Zero time step is treated as `None`.
+    ///
+    pub fn set_fixed_timestep(&mut self, fixed_timestep: Option<Duration>) {
+        self.fixed_timestep = fixed_timestep.filter(|step| !step.is_zero());
+    }
+
+    /// If HDR (10-bit HDR10) output is preferred.
+    ///
+    /// If HDR is not supported by the display, SDR output is used and warning is logged.
+    ///
+    pub fn hdr(&self) -> bool {
+        self.hdr
+    }
+
+    /// Sets if HDR (10-bit HDR10) output is preferred.
+    pub fn set_hdr(&mut self, hdr: bool) {
+        self.hdr = hdr;
+    }
+}
+
+/// Error of building the configuration with [`ConfigBuilder`].
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("MSAA sample count must be one of 1, 2, 4 or 8, but {0} was given")]
+    InvalidSampleCount(u8),
+}
+
+/// Builder of the [`Config`] which validates the options when configuration is built.
+#[derive(Debug, Clone)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    /// Creates builder which starts from the [default](Config::default) configuration.
+    pub fn new() -> Self {
+        Self {
+            config: Config::default(),
+        }
+    }
+
+    /// Sets name of your game.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.config.name = name.into();
+        self
+    }
+
+    /// Sets semver version of your game.
+    pub fn version(mut self, version: Version) -> Self {
+        self.config.version = version;
+        self
+    }
+
+    /// Sets if game will use validation.
+    pub fn enable_validation(mut self, enable_validation: bool) -> Self {
+        self.config.enable_validation = enable_validation;
+        self
+    }
+
+    /// Sets if presentation is synchronized with vertical blank of the display.
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.config.vsync = vsync;
+        self
+    }
+
+    /// Sets if HDR (10-bit HDR10) output is preferred.
+    pub fn hdr(mut self, hdr: bool) -> Self {
+        self.config.hdr = hdr;
+        self
+    }
+
+    /// Sets constant time step of game updates (see [`Config::fixed_timestep`]).
+    pub fn fixed_timestep(mut self, fixed_timestep: Duration) -> Self {
+        self.config.set_fixed_timestep(Some(fixed_timestep));
+        self
+    }
+
+    /// Sets sample count of multisample anti-aliasing (MSAA), `1` means disabled.
+    ///
+    /// Sample count is validated when configuration is built.
+    ///
+    pub fn msaa_samples(mut self, msaa_samples: u8) -> Self {
+        self.config.msaa_samples = msaa_samples;
+        self
+    }
+
+    /// Sets initial logical size of the window.
+    pub fn window_size(mut self, width: u32, height: u32) -> Self {
+        self.config.window_size = Some((width, height));
+        self
+    }
+
+    /// Sets min logical size of the window, `None` means no constraint.
+    pub fn min_window_size(mut self, min_window_size: Option<(u32, u32)>) -> Self {
+        self.config.min_window_size = min_window_size;
+        self
+    }
+
+    /// Sets if the window can be resized by the user.
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.config.resizable = resizable;
+        self
+    }
+
+    /// Builds the configuration.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if MSAA sample count is not one of `1`, `2`, `4` or `8`.
+    ///
+    pub fn build(self) -> Result<Config, ConfigError> {
+        self::validate_msaa_samples(self.config.msaa_samples)?;
+        Ok(self.config)
+    }
+}
+
+/// Checks that MSAA sample count is one of the counts which devices could support.
+fn validate_msaa_samples(msaa_samples: u8) -> Result<u8, ConfigError> {
+    match msaa_samples {
+        1 | 2 | 4 | 8 => Ok(msaa_samples),
+        _ => Err(ConfigError::InvalidSampleCount(msaa_samples)),
+    }
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Default for Config {
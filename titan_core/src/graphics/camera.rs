@@ -1,24 +1,488 @@
-//! Internal camera utilities for game engine.
+//! Camera utilities for game engine.
 
-use ultraviolet::Mat4;
+use palette::Srgb;
+use titan_ecs::Transform;
+use ultraviolet::projection::perspective_vk as perspective;
+use ultraviolet::{Lerp, Mat3, Mat4, Rotor3, Slerp, Vec3, Vec4};
+use vulkano::swapchain::SurfaceTransform;
+
+use crate::{
+    app::DeltaTime,
+    config::CoordinateSystem,
+    graphics::light::DirectionalLight,
+    window::{ElementState, Event, MouseButton, VirtualKeyCode},
+};
+
+/// Max angle (in radians) between view direction of [`FreeCamera`] and the horizon,
+/// so it never looks straight up or down.
+const MAX_PITCH: f32 = 89.0 * std::f32::consts::PI / 180.0;
+
+/// Perspective projection of the camera.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Projection {
+    /// Vertical field of view in radians.
+    pub fov: f32,
+    /// Distance to the near clipping plane.
+    pub near: f32,
+    /// Distance to the far clipping plane.
+    pub far: f32,
+}
+
+impl Projection {
+    /// Creates new perspective projection.
+    pub const fn new(fov: f32, near: f32, far: f32) -> Self {
+        Self { fov, near, far }
+    }
+
+    /// Computes projection matrix for given aspect ratio (width / height).
+    pub fn matrix(&self, aspect: f32) -> Mat4 {
+        perspective(self.fov, aspect, self.near, self.far)
+    }
+}
+
+/// Camera which is described by its transform, projection
+/// and coordinate system of the world.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Camera {
+    /// Transform of the camera in the world.
+    pub transform: Transform,
+    /// Projection of the camera.
+    pub projection: Projection,
+    /// Coordinate system of the world.
+    pub coordinate_system: CoordinateSystem,
+}
+
+impl Camera {
+    /// Creates new camera.
+    pub fn new(
+        transform: Transform,
+        projection: Projection,
+        coordinate_system: CoordinateSystem,
+    ) -> Self {
+        Self {
+            transform,
+            projection,
+            coordinate_system,
+        }
+    }
+
+    /// Creates new camera at given position which looks at the target.
+    ///
+    /// Up direction of the camera is defined by the coordinate system of the world.
+    /// If the camera should look straight up or down, camera is not rotated.
+    ///
+    pub fn look_at(
+        position: Vec3,
+        target: Vec3,
+        projection: Projection,
+        coordinate_system: CoordinateSystem,
+    ) -> Self {
+        let rotation = self::look_rotation(target - position, coordinate_system)
+            .unwrap_or_else(Rotor3::identity);
+        let transform = Transform::new(position, rotation, Vec3::one());
+        Self::new(transform, projection, coordinate_system)
+    }
+
+    /// Builds camera UBO for the render target with given aspect ratio (width / height).
+    ///
+    /// Aspect ratio should match the render target (window, its region or offscreen image)
+    /// rather than the window itself.
+    ///
+    pub fn ubo_for_aspect(&self, aspect: f32) -> CameraUBO {
+        self::camera_ubo_from(
+            &self.transform,
+            &self.projection,
+            aspect,
+            self.coordinate_system,
+        )
+    }
+}
+
+/// Helper which smoothly moves the camera towards its target,
+/// for example, to follow the player or to animate cutscenes.
+#[derive(Debug, Copy, Clone)]
+pub struct CameraRig {
+    camera: Camera,
+    target_translation: Vec3,
+    target_rotation: Rotor3,
+    smoothing: f32,
+}
+
+impl CameraRig {
+    /// Creates new camera rig which targets current position and orientation of the camera.
+    ///
+    /// Smoothing is a time constant (in seconds): camera moves through about 63%
+    /// of the remaining distance to the target during this time.
+    ///
+    pub fn new(camera: Camera, smoothing: f32) -> Self {
+        Self {
+            camera,
+            target_translation: camera.transform.translation(),
+            target_rotation: camera.transform.rotation(),
+            smoothing: smoothing.max(0.0),
+        }
+    }
+
+    /// Camera which is moved by this rig.
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    /// Mutable camera which is moved by this rig.
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    /// Smoothing time constant (in seconds), `0.0` means camera is moved instantly.
+    pub fn smoothing(&self) -> f32 {
+        self.smoothing
+    }
+
+    /// Sets smoothing time constant (in seconds), `0.0` means camera is moved instantly.
+    pub fn set_smoothing(&mut self, smoothing: f32) {
+        self.smoothing = smoothing.max(0.0);
+    }
+
+    /// Sets target position of the camera and the point which it should look at.
+    ///
+    /// If the camera should look straight up or down, target orientation is not changed.
+    ///
+    pub fn set_target(&mut self, position: Vec3, look_at: Vec3) {
+        self.target_translation = position;
+        let coordinate_system = self.camera.coordinate_system;
+        if let Some(rotation) = self::look_rotation(look_at - position, coordinate_system) {
+            self.target_rotation = rotation;
+        }
+    }
+
+    /// Moves the camera to its target instantly.
+    pub fn snap(&mut self) {
+        self.camera
+            .transform
+            .set_translation(self.target_translation);
+        self.camera.transform.set_rotation(self.target_rotation);
+    }
+
+    /// Moves the camera towards its target by the time passed since the previous frame.
+    pub fn update(&mut self, delta_time: DeltaTime) {
+        if self.smoothing == 0.0 {
+            self.snap();
+            return;
+        }
+        // Exponential smoothing does not depend on frame rate.
+        let t = 1.0 - (-delta_time.as_secs_f32() / self.smoothing).exp();
+
+        let transform = &mut self.camera.transform;
+        let translation = transform.translation().lerp(self.target_translation, t);
+        transform.set_translation(translation);
+        // Rotors `r` and `-r` represent the same rotation, so choose the shortest path.
+        let rotation = transform.rotation();
+        let target_rotation = if rotation.dot(self.target_rotation) < 0.0 {
+            self.target_rotation * -1.0
+        } else {
+            self.target_rotation
+        };
+        transform.set_rotation(rotation.slerp(target_rotation, t).normalized());
+    }
+}
+
+/// Debug camera which flies freely in the world, so every game does not need
+/// to implement camera movement for debugging again.
+///
+/// `W`, `A`, `S` and `D` keys move the camera forward, left, backward and right,
+/// `Q` and `E` move it down and up, and the mouse turns it while the right button is held.
+/// Pass all the events of the application into [`handle_event`](FreeCamera::handle_event):
+/// camera is moved on [`Event::Update`] by the time passed.
+///
+#[derive(Debug, Copy, Clone)]
+pub struct FreeCamera {
+    camera: Camera,
+    yaw: f32,
+    pitch: f32,
+    speed: f32,
+    sensitivity: f32,
+    movement: Movement,
+    looking: bool,
+    cursor: Option<(f64, f64)>,
+}
+
+/// Keys of [`FreeCamera`] movement which are currently held.
+#[derive(Debug, Default, Copy, Clone)]
+struct Movement {
+    forward: bool,
+    backward: bool,
+    left: bool,
+    right: bool,
+    up: bool,
+    down: bool,
+}
+
+impl FreeCamera {
+    /// Default speed of the camera in world units per second.
+    pub const DEFAULT_SPEED: f32 = 5.0;
+
+    /// Default mouse sensitivity of the camera in radians per physical pixel.
+    pub const DEFAULT_SENSITIVITY: f32 = 0.003;
+
+    /// Creates new free camera which starts from current position and orientation of the camera.
+    ///
+    /// Roll of the camera is discarded, because free camera always keeps the horizon level.
+    ///
+    pub fn new(camera: Camera) -> Self {
+        let forward = match camera.coordinate_system {
+            CoordinateSystem::YUp => -Vec3::unit_z(),
+            CoordinateSystem::ZUp => Vec3::unit_y(),
+        };
+        let forward = camera.transform.rotation() * forward;
+        let (yaw, pitch) = self::yaw_pitch(forward, camera.coordinate_system);
+
+        let mut free_camera = Self {
+            camera,
+            yaw,
+            pitch: pitch.clamp(-MAX_PITCH, MAX_PITCH),
+            speed: Self::DEFAULT_SPEED,
+            sensitivity: Self::DEFAULT_SENSITIVITY,
+            movement: Movement::default(),
+            looking: false,
+            cursor: None,
+        };
+        free_camera.update_rotation();
+        free_camera
+    }
+
+    /// Camera which is moved by this helper.
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    /// Speed of the camera in world units per second.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Sets speed of the camera in world units per second.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+    }
+
+    /// Mouse sensitivity of the camera in radians per physical pixel.
+    pub fn sensitivity(&self) -> f32 {
+        self.sensitivity
+    }
+
+    /// Sets mouse sensitivity of the camera in radians per physical pixel.
+    pub fn set_sensitivity(&mut self, sensitivity: f32) {
+        self.sensitivity = sensitivity;
+    }
+
+    /// Updates the camera with given event of the application.
+    pub fn handle_event(&mut self, event: &Event) {
+        match *event {
+            Event::Update(delta_time) => self.update(delta_time),
+            Event::Keyboard { key, state } => {
+                let pressed = state == ElementState::Pressed;
+                let movement = &mut self.movement;
+                match key {
+                    VirtualKeyCode::W => movement.forward = pressed,
+                    VirtualKeyCode::S => movement.backward = pressed,
+                    VirtualKeyCode::A => movement.left = pressed,
+                    VirtualKeyCode::D => movement.right = pressed,
+                    VirtualKeyCode::E => movement.up = pressed,
+                    VirtualKeyCode::Q => movement.down = pressed,
+                    _ => (),
+                }
+            }
+            Event::MouseButton {
+                button: MouseButton::Right,
+                state,
+            } => self.looking = state == ElementState::Pressed,
+            Event::CursorMoved(x, y) => {
+                if let (true, Some((last_x, last_y))) = (self.looking, self.cursor) {
+                    let (delta_x, delta_y) = ((x - last_x) as f32, (y - last_y) as f32);
+                    self.yaw -= delta_x * self.sensitivity;
+                    self.pitch =
+                        (self.pitch - delta_y * self.sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+                    self.update_rotation();
+                }
+                self.cursor = Some((x, y));
+            }
+            // Release events are not delivered if the window is not focused anymore.
+            Event::Focused(false) => {
+                self.movement = Movement::default();
+                self.looking = false;
+            }
+            _ => (),
+        }
+    }
+
+    /// Moves the camera by the time passed according to the keys which are held.
+    fn update(&mut self, delta_time: DeltaTime) {
+        let up = match self.camera.coordinate_system {
+            CoordinateSystem::YUp => Vec3::unit_y(),
+            CoordinateSystem::ZUp => Vec3::unit_z(),
+        };
+        let forward = self::direction(self.yaw, self.pitch, self.camera.coordinate_system);
+        let right = forward.cross(up).normalized();
+
+        let movement = self.movement;
+        let axis = |positive: bool, negative: bool| positive as i32 as f32 - negative as i32 as f32;
+        let direction = forward * axis(movement.forward, movement.backward)
+            + right * axis(movement.right, movement.left)
+            + up * axis(movement.up, movement.down);
+        if direction.mag_sq() < f32::EPSILON {
+            return;
+        }
+
+        let distance = self.speed * delta_time.as_secs_f32();
+        let transform = &mut self.camera.transform;
+        let translation = transform.translation() + direction.normalized() * distance;
+        transform.set_translation(translation);
+    }
+
+    /// Applies yaw and pitch to the orientation of the camera.
+    fn update_rotation(&mut self) {
+        let coordinate_system = self.camera.coordinate_system;
+        let direction = self::direction(self.yaw, self.pitch, coordinate_system);
+        if let Some(rotation) = self::look_rotation(direction, coordinate_system) {
+            self.camera.transform.set_rotation(rotation);
+        }
+    }
+}
+
+/// Computes view direction from yaw (counterclockwise around the up axis) and pitch,
+/// where zero yaw and pitch looks along `-Z` for Y up and along `+Y` for Z up.
+fn direction(yaw: f32, pitch: f32, coordinate_system: CoordinateSystem) -> Vec3 {
+    let (sin_yaw, cos_yaw) = yaw.sin_cos();
+    let (sin_pitch, cos_pitch) = pitch.sin_cos();
+    match coordinate_system {
+        CoordinateSystem::YUp => Vec3::new(-cos_pitch * sin_yaw, sin_pitch, -cos_pitch * cos_yaw),
+        CoordinateSystem::ZUp => Vec3::new(-cos_pitch * sin_yaw, cos_pitch * cos_yaw, sin_pitch),
+    }
+}
+
+/// Computes yaw and pitch of given view direction, inverse of [`direction`].
+fn yaw_pitch(direction: Vec3, coordinate_system: CoordinateSystem) -> (f32, f32) {
+    let direction = direction.normalized();
+    match coordinate_system {
+        CoordinateSystem::YUp => (
+            (-direction.x).atan2(-direction.z),
+            direction.y.clamp(-1.0, 1.0).asin(),
+        ),
+        CoordinateSystem::ZUp => (
+            (-direction.x).atan2(direction.y),
+            direction.z.clamp(-1.0, 1.0).asin(),
+        ),
+    }
+}
+
+/// Computes orientation of the camera which looks in given direction,
+/// or `None` if direction is zero or parallel to the up axis.
+fn look_rotation(direction: Vec3, coordinate_system: CoordinateSystem) -> Option<Rotor3> {
+    let up = match coordinate_system {
+        CoordinateSystem::YUp => Vec3::unit_y(),
+        CoordinateSystem::ZUp => Vec3::unit_z(),
+    };
+    if direction.mag_sq() < f32::EPSILON {
+        return None;
+    }
+    let forward = direction.normalized();
+    let right = forward.cross(up);
+    if right.mag_sq() < f32::EPSILON {
+        return None;
+    }
+    let right = right.normalized();
+    let up = right.cross(forward);
+    // Columns are local axes of the camera in the world.
+    let basis = match coordinate_system {
+        CoordinateSystem::YUp => Mat3::new(right, up, -forward),
+        CoordinateSystem::ZUp => Mat3::new(right, forward, up),
+    };
+    Some(basis.into_rotor3())
+}
+
+/// Builds camera UBO from the transform of the camera entity.
+///
+/// View matrix is derived as an inverse of the world transform of the camera
+/// followed by basis change from given coordinate system into view space
+/// (Y up, camera looks along `-Z`).
+///
+pub fn camera_ubo_from(
+    transform: &Transform,
+    projection: &Projection,
+    aspect: f32,
+    coordinate_system: CoordinateSystem,
+) -> CameraUBO {
+    let projection = projection.matrix(aspect);
+    let view = self::basis_change(coordinate_system) * transform.matrix().inversed();
+    CameraUBO::new(projection, view)
+}
+
+/// Matrix which converts camera local space of given convention into view space.
+fn basis_change(coordinate_system: CoordinateSystem) -> Mat4 {
+    match coordinate_system {
+        CoordinateSystem::YUp => Mat4::identity(),
+        // Maps +Y (forward) onto -Z and +Z (up) onto +Y.
+        CoordinateSystem::ZUp => Mat4::from_rotation_x(-90f32.to_radians()),
+    }
+}
 
 /// Camera uniform buffer object (UBO) that will be passed into uniform buffer.
 #[derive(Default, Copy, Clone)]
 pub struct CameraUBO {
     /// Projection 4x4 matrix.
     pub projection: Mat4,
-    /// Model 4x4 matrix.
-    pub model: Mat4,
     /// View 4x4 matrix.
     pub view: Mat4,
+    /// Direction in which directional light travels (`w` is unused).
+    light_direction: Vec4,
+    /// Linear color of directional light (`w` is unused).
+    light_color: Vec4,
+    /// Linear color of ambient light (`w` is unused).
+    ambient_color: Vec4,
 }
 
 impl CameraUBO {
-    pub fn new(projection: Mat4, model: Mat4, view: Mat4) -> Self {
+    /// Creates new camera UBO from given matrices.
+    ///
+    /// Model matrices are not part of the camera: they are supplied per object
+    /// by [`RenderInstance`](crate::graphics::RenderInstance).
+    ///
+    pub fn new(projection: Mat4, view: Mat4) -> Self {
         Self {
             projection,
-            model,
             view,
+            ..Self::default()
+        }
+    }
+
+    /// Sets directional light of the scene, which is stored by the renderer
+    /// separately from the camera.
+    pub(crate) fn with_light(self, light: &DirectionalLight) -> Self {
+        let linear = |color: Srgb| {
+            let (red, green, blue) = color.into_linear().into_components();
+            Vec4::new(red, green, blue, 1.0)
+        };
+        Self {
+            light_direction: light.direction.normalized().into_homogeneous_vector(),
+            light_color: linear(light.color),
+            ambient_color: linear(light.ambient),
+            ..self
+        }
+    }
+
+    /// Applies pre-rotation of the surface (on mobile devices) to the projection matrix,
+    /// so rendered image will not appear rotated after presentation.
+    pub(crate) fn pre_rotated(self, transform: SurfaceTransform) -> Self {
+        let angle: f32 = match transform {
+            SurfaceTransform::Rotate90 => 90.0,
+            SurfaceTransform::Rotate180 => 180.0,
+            SurfaceTransform::Rotate270 => 270.0,
+            _ => return self,
+        };
+        let rotation = Mat4::from_rotation_z(angle.to_radians());
+        Self {
+            projection: rotation * self.projection,
+            ..self
         }
     }
 }
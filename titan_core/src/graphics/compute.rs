@@ -0,0 +1,47 @@
+//! Compute pipeline utilities for game engine.
+
+use std::ffi::CStr;
+use std::sync::Arc;
+
+use vulkano::descriptor_set::layout::DescriptorSetDesc;
+use vulkano::device::Device;
+use vulkano::pipeline::layout::PipelineLayoutPcRange;
+use vulkano::pipeline::shader::ShaderModule;
+use vulkano::pipeline::ComputePipeline;
+
+use super::renderer::error::ComputePipelineLoadError;
+
+/// Layout of resources which are declared in compute shader.
+///
+/// SPIR-V is not reflected, so layout must describe all descriptor sets
+/// and push constants which are used by the shader.
+///
+#[derive(Debug, Clone, Default)]
+pub struct ComputeLayout {
+    /// Descriptions of descriptor sets in order of their indices.
+    pub descriptor_sets: Vec<DescriptorSetDesc>,
+    /// Range of push constants, if any.
+    pub push_constants: Option<PipelineLayoutPcRange>,
+}
+
+/// Creates compute pipeline from SPIR-V binary with `main` entry point.
+pub fn create_compute_pipeline(
+    device: Arc<Device>,
+    spirv: &[u8],
+    layout: ComputeLayout,
+) -> Result<Arc<ComputePipeline>, ComputePipelineLoadError> {
+    const SPIRV_MAGIC: [u8; 4] = 0x0723_0203u32.to_le_bytes();
+    if spirv.len() % 4 != 0 || !spirv.starts_with(&SPIRV_MAGIC) {
+        return Err(ComputePipelineLoadError::InvalidSpirv);
+    }
+
+    // Safety: SPIR-V binary is provided by the user, which must ensure that it is valid
+    // and its resources match given layout.
+    let module = unsafe { ShaderModule::new(device.clone(), spirv)? };
+    let name = CStr::from_bytes_with_nul(b"main\0").unwrap();
+    let entry_point = unsafe {
+        module.compute_entry_point(name, layout.descriptor_sets, layout.push_constants, &[])
+    };
+    let pipeline = ComputePipeline::new(device, &entry_point, &(), None, |_| {})?;
+    Ok(Arc::new(pipeline))
+}
@@ -1,11 +1,15 @@
 use thiserror::Error;
 use vulkano::command_buffer::{BuildError, DrawIndexedError};
+use vulkano::image::view::ImageViewCreationError;
+use vulkano::image::{ImageCreationError, SampleCount};
 use vulkano::memory::DeviceMemoryAllocError;
 use vulkano::pipeline::GraphicsPipelineCreationError;
 use vulkano::sync::FlushError;
 use vulkano::OomError;
 
-use crate::graphics::renderer::error::DescriptorSetCreationError;
+use crate::graphics::{
+    mesh::obj::ObjLoadError, renderer::error::DescriptorSetCreationError, vertex::VertexLayoutError,
+};
 
 #[derive(Debug, Error)]
 pub enum ObjectDrawSystemCreationError {
@@ -15,6 +19,18 @@ pub enum ObjectDrawSystemCreationError {
     #[error("queue family must support graphics operations")]
     QueueFamilyNotSupported,
 
+    #[error("shader SPIR-V binary is invalid")]
+    ShaderLoad,
+
+    #[error("pipeline is built for {expected:?} samples, but subpass has {actual:?} samples")]
+    SampleCountMismatch {
+        expected: SampleCount,
+        actual: SampleCount,
+    },
+
+    #[error("vertex layout validation failure: {0}")]
+    VertexLayout(#[from] VertexLayoutError),
+
     #[error("graphics pipeline creation failure: {0}")]
     GraphicsPipelineCreation(#[from] GraphicsPipelineCreationError),
 
@@ -23,6 +39,27 @@ pub enum ObjectDrawSystemCreationError {
 
     #[error("vertex/index buffer allocation failure: {0}")]
     BufferAllocation(#[from] DeviceMemoryAllocError),
+
+    #[error("default material creation failure: {0}")]
+    MaterialCreation(#[from] MaterialCreationError),
+}
+
+#[derive(Debug, Error)]
+pub enum MaterialCreationError {
+    #[error("uniform buffer allocation failure: {0}")]
+    BufferAllocation(#[from] DeviceMemoryAllocError),
+
+    #[error("uniform buffer creation failure: {0}")]
+    BufferCreation(#[from] FlushError),
+
+    #[error("material descriptor set creation failure: {0}")]
+    DescriptorSetCreation(#[from] DescriptorSetCreationError),
+
+    #[error("texture creation failure: {0}")]
+    TextureCreation(#[from] ImageCreationError),
+
+    #[error("texture view creation failure: {0}")]
+    TextureViewCreation(#[from] ImageViewCreationError),
 }
 
 #[derive(Debug, Error)]
@@ -38,4 +75,16 @@ pub enum ObjectDrawError {
 
     #[error("draw command buffer build failure: {0}")]
     CommandBufferBuild(#[from] BuildError),
+
+    #[error("OBJ mesh loading failure: {0}")]
+    ObjLoad(#[from] ObjLoadError),
+
+    #[error("vertex/index buffer creation failure: {0}")]
+    BufferCreation(#[from] FlushError),
+
+    #[error("vertex/index/instance buffer allocation failure: {0}")]
+    BufferAllocation(#[from] DeviceMemoryAllocError),
+
+    #[error("pipeline creation failure for topology of the mesh: {0}")]
+    PipelineCreation(#[from] ObjectDrawSystemCreationError),
 }
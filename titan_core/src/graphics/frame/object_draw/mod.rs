@@ -1,45 +1,157 @@
+use std::ffi::CStr;
+use std::iter;
+use std::ops::Range;
+use std::path::Path;
 use std::sync::Arc;
 
+use image::{Rgba, RgbaImage};
 use palette::Srgba;
-use ultraviolet::Vec3;
-use vulkano::buffer::{BufferUsage, ImmutableBuffer, TypedBufferAccess};
+use rayon::prelude::*;
+use rayon::ThreadPool;
+use slotmap::SlotMap;
+use ultraviolet::Mat4;
+use vulkano::buffer::{BufferUsage, CpuBufferPool, ImmutableBuffer, TypedBufferAccess};
 use vulkano::command_buffer::{
     AutoCommandBufferBuilder, CommandBufferUsage, SecondaryAutoCommandBuffer,
 };
-use vulkano::descriptor_set::SingleLayoutDescSetPool;
-use vulkano::device::Queue;
-use vulkano::pipeline::viewport::Viewport;
+use vulkano::descriptor_set::{
+    PersistentDescriptorSet, SingleLayoutDescSet, SingleLayoutDescSetPool,
+};
+use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
+use vulkano::image::view::{ImageView, ImageViewAbstract};
+use vulkano::image::{ImageDimensions, ImmutableImage, MipmapsCount, SampleCount};
+use vulkano::memory::DeviceMemoryAllocError;
+use vulkano::pipeline::depth_stencil::{Compare, DepthBounds, DepthStencil, Stencil, StencilOp};
+use vulkano::pipeline::shader::{
+    EntryPointAbstract, GraphicsEntryPoint, ShaderModule, SpecializationConstants,
+    SpecializationMapEntry,
+};
+use vulkano::pipeline::viewport::{Scissor, Viewport};
 use vulkano::pipeline::{GraphicsPipeline, PipelineBindPoint};
 use vulkano::render_pass::Subpass;
-use vulkano::sync::GpuFuture;
+use vulkano::sampler::Sampler;
+use vulkano::sync::{FlushError, GpuFuture};
 
 use crate::{
+    config::{CullMode, FrontFace},
     graphics::{
         camera::CameraUBO,
-        frame::object_draw::error::{ObjectDrawError, ObjectDrawSystemCreationError},
+        frame::object_draw::error::{
+            MaterialCreationError, ObjectDrawError, ObjectDrawSystemCreationError,
+        },
+        material::{Material, MaterialHandle, MaterialUBO},
+        mesh::{obj, primitives, Topology},
         renderer::error::DescriptorSetCreationError,
-        vertex::Vertex,
+        shader::default::fragment as object_fragment,
+        shader::outline::vertex as outline_vertex,
+        trace::{self, Resource},
+        utils::OutputEncoding,
+        vertex::{validate_vertex_input, Vertex},
     },
-    window::Size,
+    window::{Rect, Size},
 };
 
 pub mod error;
 
-const fn indices() -> [u32; 12] {
-    [0, 1, 2, 2, 3, 0, 4, 5, 6, 6, 7, 4]
+/// Options of the graphics pipeline used for rendering game objects.
+#[derive(Debug, Copy, Clone)]
+pub struct PipelineOptions {
+    /// Sample count which the pipeline is built for, must match sample count of the subpass.
+    pub samples: SampleCount,
+    /// Use alpha-to-coverage for cutout transparency (foliage, grass etc.).
+    ///
+    /// Only applies if the pipeline is multisampled.
+    pub alpha_to_coverage: bool,
+    /// Which faces of game objects are discarded.
+    pub cull_mode: CullMode,
+    /// Vertex winding order of front-facing faces.
+    pub front_face: FrontFace,
+    /// How vertices of the mesh are assembled into triangles, must match the drawn mesh.
+    pub topology: Topology,
+    /// Draw only edges of game objects.
+    ///
+    /// Requires `fill_mode_non_solid` feature of the device.
+    pub wireframe: bool,
+    /// Write game objects into stencil buffer, so they could be outlined.
+    ///
+    /// Depth format of the subpass must have stencil aspect.
+    pub stencil: bool,
+    /// Discard fragments of game objects which are behind already drawn ones.
+    ///
+    /// If disabled, game objects are drawn in order of their layers (painter's algorithm).
+    pub depth_test: bool,
+    /// Blend game objects with what is already drawn using alpha of their color.
+    ///
+    /// Depth is tested but not written, so overlapping transparent surfaces are blended.
+    /// Blending depends on draw order: caller is responsible for sorting instances
+    /// back-to-front (instances are drawn in order of their layers, then of submission).
+    pub transparent: bool,
+    /// How output color is encoded, must match format of the subpass.
+    pub output_encoding: OutputEncoding,
+}
+
+impl Default for PipelineOptions {
+    fn default() -> Self {
+        Self {
+            samples: SampleCount::Sample1,
+            alpha_to_coverage: false,
+            cull_mode: CullMode::default(),
+            front_face: FrontFace::default(),
+            topology: Topology::default(),
+            wireframe: false,
+            stencil: false,
+            depth_test: true,
+            transparent: false,
+            output_encoding: OutputEncoding::default(),
+        }
+    }
 }
 
-fn vertices() -> [Vertex; 8] {
-    [
-        Vertex::new(Vec3::new(-0.5, -0.5, 0.0), Srgba::new(1.0, 0.0, 0.0, 1.0)),
-        Vertex::new(Vec3::new(0.5, -0.5, 0.0), Srgba::new(0.0, 1.0, 0.0, 1.0)),
-        Vertex::new(Vec3::new(0.5, 0.5, 0.0), Srgba::new(0.0, 0.0, 1.0, 1.0)),
-        Vertex::new(Vec3::new(-0.5, 0.5, 0.0), Srgba::new(1.0, 1.0, 1.0, 1.0)),
-        Vertex::new(Vec3::new(-0.5, -0.5, -0.5), Srgba::new(1.0, 0.0, 0.0, 1.0)),
-        Vertex::new(Vec3::new(0.5, -0.5, -0.5), Srgba::new(0.0, 1.0, 0.0, 1.0)),
-        Vertex::new(Vec3::new(0.5, 0.5, -0.5), Srgba::new(0.0, 0.0, 1.0, 1.0)),
-        Vertex::new(Vec3::new(-0.5, 0.5, -0.5), Srgba::new(1.0, 1.0, 1.0, 1.0)),
-    ]
+/// Instance of the game object which is submitted for rendering.
+#[derive(Debug, Copy, Clone)]
+pub struct RenderInstance {
+    /// Model matrix of the instance which transforms it into the world.
+    pub model: Mat4,
+    /// Layer of the instance: instances with greater layer are drawn later.
+    ///
+    /// Only applies if depth test is disabled.
+    pub layer: i32,
+    /// Material of the instance, `None` means default material.
+    ///
+    /// If material was removed, default material is used too.
+    pub material: Option<MaterialHandle>,
+    /// Color which is multiplied with color of the instance, white means no tint
+    /// (useful to flash the object on damage without changing its material).
+    pub tint: Srgba,
+}
+
+impl RenderInstance {
+    /// Creates new instance with given model matrix on the default (zero) layer
+    /// with default material and without tint.
+    pub fn new(model: Mat4) -> Self {
+        Self {
+            model,
+            layer: 0,
+            material: None,
+            tint: Srgba::new(1.0, 1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Default for RenderInstance {
+    fn default() -> Self {
+        Self::new(Mat4::identity())
+    }
+}
+
+/// Outline of the game object which is drawn around it in solid color.
+#[derive(Debug, Copy, Clone)]
+pub struct Outline {
+    /// Color of the outline.
+    pub color: Srgba,
+    /// Thickness of the outline relative to the size of the game object.
+    pub thickness: f32,
 }
 
 /// System that contains the necessary facilities for rendering game objects.
@@ -53,123 +165,861 @@ pub struct ObjectDrawSystem {
     /// Buffer for all indices of vertices in game object.
     index_buffer: Arc<ImmutableBuffer<[u32]>>,
 
+    /// Buffer for model matrices of all instances drawn in the frame.
+    instance_buffer: CpuBufferPool<Mat4>,
+
     /// Graphics pipeline used for rendering of game objects.
     pipeline: Arc<GraphicsPipeline>,
 
-    /// Pool of descriptor sets of uniform buffers with data for vertex shader.
+    /// Graphics pipeline used for rendering of outlines (if stencil is enabled).
+    outline_pipeline: Option<Arc<GraphicsPipeline>>,
+
+    /// If instances need to be sorted by layers, because draw order matters
+    /// (depth test is disabled or game objects are transparent).
+    sort_by_layers: bool,
+
+    /// Pool of per-frame descriptor sets (set 0) of uniform buffers with camera data.
     descriptor_set_pool: SingleLayoutDescSetPool,
+
+    /// Descriptor set (set 1) of the default material.
+    default_material: Arc<PersistentDescriptorSet>,
+
+    /// Sampler for textures of game objects.
+    sampler: Arc<Sampler>,
+
+    /// Texture which is used when game objects have no texture,
+    /// so they are colored by their vertices only.
+    white_texture: Arc<dyn ImageViewAbstract + Send + Sync>,
+
+    /// Descriptor sets (set 1) of materials created by the user.
+    materials: SlotMap<MaterialHandle, Arc<PersistentDescriptorSet>>,
+
+    /// Options which the pipeline of game objects was built with.
+    options: PipelineOptions,
+
+    /// Shaders which replace built-in ones, if any.
+    shaders: Option<CustomShaders>,
+
+    /// Width of lines which edges of game objects are drawn with in wireframe mode.
+    line_width: f32,
 }
 
 impl ObjectDrawSystem {
-    /// Creates new object draw system.
+    /// Creates new object draw system with built-in shaders.
     pub fn new(
         graphics_queue: Arc<Queue>,
         subpass: Subpass,
+        sampler: Arc<Sampler>,
+        options: PipelineOptions,
+    ) -> Result<Self, ObjectDrawSystemCreationError> {
+        Self::create(graphics_queue, subpass, sampler, options, None)
+    }
+
+    /// Creates new object draw system which renders game objects with given shaders
+    /// (SPIR-V binaries with `main` entry points).
+    ///
+    /// Vertex input is [`Vertex`] as for built-in shaders.
+    /// Shaders must declare the same resources (uniform buffers, push constants etc.),
+    /// inputs and outputs as built-in `default.vert` and `default.frag` shaders do.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if any of binaries is not SPIR-V.
+    ///
+    /// # Safety
+    ///
+    /// Binaries must be valid SPIR-V modules which declare the same interface
+    /// as built-in shaders, because it is not checked and is trusted by the pipeline.
+    ///
+    pub unsafe fn with_shaders(
+        graphics_queue: Arc<Queue>,
+        subpass: Subpass,
+        sampler: Arc<Sampler>,
+        options: PipelineOptions,
+        vertex: &[u32],
+        fragment: &[u32],
+    ) -> Result<Self, ObjectDrawSystemCreationError> {
+        let shaders = CustomShaders::load(graphics_queue.device(), vertex, fragment)?;
+        Self::create(graphics_queue, subpass, sampler, options, Some(shaders))
+    }
+
+    fn create(
+        graphics_queue: Arc<Queue>,
+        subpass: Subpass,
+        sampler: Arc<Sampler>,
+        options: PipelineOptions,
+        shaders: Option<CustomShaders>,
     ) -> Result<Self, ObjectDrawSystemCreationError> {
         // Check queue for graphics support.
         if !graphics_queue.family().supports_graphics() {
             return Err(ObjectDrawSystemCreationError::QueueFamilyNotSupported);
         }
 
-        let pipeline = {
-            use crate::graphics::shader::default::{fragment, vertex};
-
-            let device = graphics_queue.device().clone();
-
-            let vert_shader_module = vertex::Shader::load(device.clone())?;
-            let frag_shader_module = fragment::Shader::load(device.clone())?;
-
-            Arc::new(
-                GraphicsPipeline::start()
-                    .vertex_input_single_buffer::<Vertex>()
-                    .vertex_shader(vert_shader_module.main_entry_point(), ())
-                    .fragment_shader(frag_shader_module.main_entry_point(), ())
-                    .triangle_list()
-                    .primitive_restart(false)
-                    .viewports_dynamic_scissors_irrelevant(1)
-                    .depth_stencil_simple_depth()
-                    .cull_mode_back()
-                    .render_pass(subpass)
-                    .build(device)?,
-            )
-        };
+        let samples = subpass.num_samples().unwrap_or(SampleCount::Sample1);
+        if samples != options.samples {
+            return Err(ObjectDrawSystemCreationError::SampleCountMismatch {
+                expected: options.samples,
+                actual: samples,
+            });
+        }
 
-        let vertex_buffer = {
-            let (vertex_buffer, future) = ImmutableBuffer::from_iter(
-                self::vertices(),
-                BufferUsage::vertex_buffer(),
-                graphics_queue.clone(),
-            )?;
-            future.flush()?;
-            vertex_buffer
-        };
+        let pipeline =
+            self::create_pipeline(&graphics_queue, subpass.clone(), options, shaders.as_ref())?;
+        let outline_pipeline = options
+            .stencil
+            .then(|| self::create_outline_pipeline(&graphics_queue, subpass, options))
+            .transpose()?;
 
-        let index_buffer = {
-            let (index_buffer, future) = ImmutableBuffer::from_iter(
-                self::indices(),
-                BufferUsage::index_buffer(),
-                graphics_queue.clone(),
-            )?;
-            future.flush()?;
-            index_buffer
-        };
+        let (mut vertices, indices) = primitives::cube();
+        // Color the demo cube by its normals, so its faces are distinguishable.
+        for vertex in &mut vertices {
+            let normal = *vertex.normal;
+            *vertex.color = Srgba::new(normal.x.abs(), normal.y.abs(), normal.z.abs(), 1.0);
+        }
+
+        let vertex_buffer =
+            self::create_buffer(&graphics_queue, vertices, BufferUsage::vertex_buffer())?;
+        let index_buffer =
+            self::create_buffer(&graphics_queue, indices, BufferUsage::index_buffer())?;
+
+        let instance_buffer = CpuBufferPool::new(
+            graphics_queue.device().clone(),
+            BufferUsage::storage_buffer(),
+        );
 
         let descriptor_set_pool = {
             let layout = &pipeline.layout().descriptor_set_layouts()[0];
             SingleLayoutDescSetPool::new(layout.clone())
         };
 
+        let white_texture = {
+            let white = RgbaImage::from_pixel(1, 1, Rgba([u8::MAX; 4]));
+            self::create_texture(&graphics_queue, &white)?
+        };
+        let default_material = self::create_material(
+            &graphics_queue,
+            &pipeline,
+            MaterialUBO::default(),
+            white_texture.clone(),
+            sampler.clone(),
+        )?;
+
         Ok(Self {
             graphics_queue,
             vertex_buffer,
             index_buffer,
+            instance_buffer,
             pipeline,
+            outline_pipeline,
+            sort_by_layers: !options.depth_test || options.transparent,
             descriptor_set_pool,
+            default_material,
+            sampler,
+            white_texture,
+            materials: SlotMap::with_key(),
+            options,
+            shaders,
+            line_width: 1.0,
         })
     }
 
-    /// Builds a secondary command buffer that draws game objects on the current subpass.
+    /// Replaces shaders which render game objects (see [`with_shaders`](Self::with_shaders)).
+    ///
+    /// Materials which were created earlier are kept.
+    /// Current shaders are kept if the pipeline could not be created.
+    ///
+    /// # Safety
+    ///
+    /// Binaries must be valid SPIR-V modules which declare the same interface
+    /// as built-in shaders, because it is not checked and is trusted by the pipeline.
+    ///
+    pub unsafe fn set_shaders(
+        &mut self,
+        vertex: &[u32],
+        fragment: &[u32],
+    ) -> Result<(), ObjectDrawSystemCreationError> {
+        let queue = &self.graphics_queue;
+        let shaders = CustomShaders::load(queue.device(), vertex, fragment)?;
+        let subpass = self.pipeline.subpass().clone();
+        self.pipeline = self::create_pipeline(queue, subpass, self.options, Some(&shaders))?;
+        self.shaders = Some(shaders);
+        Ok(())
+    }
+
+    /// Loads mesh from Wavefront OBJ file which replaces the current mesh of game objects.
+    ///
+    /// Current mesh is kept if loading fails.
+    ///
+    pub fn load_obj(&mut self, path: &Path) -> Result<(), ObjectDrawError> {
+        let (vertices, indices) = obj::load(path)?;
+        self.set_mesh(vertices, indices, Topology::TriangleList)
+    }
+
+    /// Replaces the current mesh of game objects with given vertices and indices,
+    /// which are assembled into triangles with given topology.
+    ///
+    /// Pipelines are created again if topology differs from the topology of the current mesh.
+    /// Current mesh is kept if buffers or pipelines could not be created.
+    ///
+    pub fn set_mesh(
+        &mut self,
+        vertices: Vec<Vertex>,
+        indices: Vec<u32>,
+        topology: Topology,
+    ) -> Result<(), ObjectDrawError> {
+        let queue = &self.graphics_queue;
+        let vertex_buffer = self::create_buffer(queue, vertices, BufferUsage::vertex_buffer())?;
+        let index_buffer = self::create_buffer(queue, indices, BufferUsage::index_buffer())?;
+        if topology != self.options.topology {
+            let options = PipelineOptions {
+                topology,
+                ..self.options
+            };
+            let subpass = self.pipeline.subpass().clone();
+            let pipeline =
+                self::create_pipeline(queue, subpass.clone(), options, self.shaders.as_ref())?;
+            let outline_pipeline = self
+                .outline_pipeline
+                .as_ref()
+                .map(|_| self::create_outline_pipeline(queue, subpass, options))
+                .transpose()?;
+            self.pipeline = pipeline;
+            self.outline_pipeline = outline_pipeline;
+            self.options = options;
+        }
+        self.vertex_buffer = vertex_buffer;
+        self.index_buffer = index_buffer;
+        Ok(())
+    }
+
+    /// Uploads given material, so it could be referenced by instances of game objects.
+    pub fn create_material(
+        &mut self,
+        material: Material,
+    ) -> Result<MaterialHandle, MaterialCreationError> {
+        let descriptor_set = self::create_material(
+            &self.graphics_queue,
+            &self.pipeline,
+            material.into(),
+            self.white_texture.clone(),
+            self.sampler.clone(),
+        )?;
+        Ok(self.materials.insert(descriptor_set))
+    }
+
+    /// Uploads given sRGB image as the texture of game objects with default material,
+    /// which is sampled by UV coordinates of their vertices.
+    ///
+    /// If `None`, texture is removed and game objects are colored by their vertices only.
+    ///
+    pub fn set_texture(&mut self, image: Option<&RgbaImage>) -> Result<(), MaterialCreationError> {
+        let texture = match image {
+            Some(image) => self::create_texture(&self.graphics_queue, image)?,
+            None => self.white_texture.clone(),
+        };
+        self.default_material = self::create_material(
+            &self.graphics_queue,
+            &self.pipeline,
+            MaterialUBO::default(),
+            texture,
+            self.sampler.clone(),
+        )?;
+        Ok(())
+    }
+
+    /// Removes material which was created earlier.
+    ///
+    /// Instances which still reference it are drawn with default material.
+    ///
+    pub fn remove_material(&mut self, handle: MaterialHandle) {
+        self.materials.remove(handle);
+    }
+
+    /// Width of lines which edges of game objects are drawn with in wireframe mode.
+    pub fn line_width(&self) -> f32 {
+        self.line_width
+    }
+
+    /// Sets width of lines which edges of game objects are drawn with in wireframe mode,
+    /// clamped to the line width range of the device.
+    ///
+    /// Lines wider than `1.0` require `wide_lines` feature of the device:
+    /// if it is not enabled, width stays `1.0` and warning is logged.
+    ///
+    pub fn set_line_width(&mut self, width: f32) {
+        let device = self.graphics_queue.device();
+        if width != 1.0 && !device.enabled_features().wide_lines {
+            log::warn!("wide lines are not supported by the device, using line width of 1.0");
+            self.line_width = 1.0;
+            return;
+        }
+        let [min, max] = device.physical_device().properties().line_width_range;
+        self.line_width = width.clamp(min, max);
+    }
+
+    /// If game objects could be outlined, i.e. stencil is enabled.
+    pub fn supports_outline(&self) -> bool {
+        self.outline_pipeline.is_some()
+    }
+
+    /// Builds secondary command buffers that draw given instances of game objects
+    /// into given region of the viewport on the current subpass.
+    ///
+    /// Depth of game objects is mapped into given depth range (clamped into `0.0..=1.0`).
+    ///
+    /// Model matrices of instances are uploaded into storage buffer, so consecutive instances
+    /// with the same material are drawn with a single instanced draw call.
+    /// If there are many instances, command buffers are recorded in parallel on the thread pool.
+    /// Given outline is drawn around game objects only if stencil is enabled.
+    ///
     pub fn draw<B>(
         &mut self,
         viewport_size: Size,
+        region: Rect,
+        depth_range: Range<f32>,
         uniform_buffer: Arc<B>,
-    ) -> Result<SecondaryAutoCommandBuffer, ObjectDrawError>
+        instances: &[RenderInstance],
+        outline: Option<Outline>,
+        thread_pool: &ThreadPool,
+    ) -> Result<Vec<SecondaryAutoCommandBuffer>, ObjectDrawError>
     where
         B: TypedBufferAccess<Content = CameraUBO> + Send + Sync + 'static,
     {
-        let mut builder = AutoCommandBufferBuilder::secondary_graphics(
-            self.graphics_queue.device().clone(),
-            self.graphics_queue.family(),
-            CommandBufferUsage::OneTimeSubmit,
-            self.pipeline.subpass().clone(),
-        )?;
+        if instances.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Sort is stable, so order of submission is preserved inside of the layer.
+        let sorted;
+        let instances = if self.sort_by_layers {
+            let mut instances = instances.to_vec();
+            instances.sort_by_key(|instance| instance.layer);
+            sorted = instances;
+            &sorted[..]
+        } else {
+            instances
+        };
 
-        let descriptor_sets = {
+        // Instance index of the shader is an index into this buffer.
+        let models = self
+            .instance_buffer
+            .chunk(instances.iter().map(|instance| instance.model))?;
+        let frame_descriptor_set = {
             let mut builder = self.descriptor_set_pool.next();
             builder
                 .add_buffer(uniform_buffer)
                 .map_err(DescriptorSetCreationError::from)?;
+            builder
+                .add_buffer(Arc::new(models))
+                .map_err(DescriptorSetCreationError::from)?;
             let descriptor_set = builder.build().map_err(DescriptorSetCreationError::from)?;
             Arc::new(descriptor_set)
         };
 
-        let viewport = Viewport {
-            origin: [0.0, 0.0],
-            dimensions: [viewport_size.width as f32, viewport_size.height as f32],
-            depth_range: 0.0..1.0,
+        let recorder = Recorder {
+            graphics_queue: self.graphics_queue.clone(),
+            vertex_buffer: self.vertex_buffer.clone(),
+            index_buffer: self.index_buffer.clone(),
+            frame_descriptor_set,
+            viewport: {
+                let (width, height) = (viewport_size.width as f32, viewport_size.height as f32);
+                Viewport {
+                    origin: [region.x * width, region.y * height],
+                    dimensions: [region.width * width, region.height * height],
+                    depth_range: depth_range.start.clamp(0.0, 1.0)..depth_range.end.clamp(0.0, 1.0),
+                }
+            },
+            // Fragments could be generated outside of the viewport (for example, by guard band),
+            // so they are discarded by the scissor of the same region.
+            scissor: {
+                let (width, height) = (viewport_size.width as f32, viewport_size.height as f32);
+                let min_x = (region.x * width).clamp(0.0, width).round();
+                let min_y = (region.y * height).clamp(0.0, height).round();
+                let max_x = ((region.x + region.width) * width)
+                    .clamp(min_x, width)
+                    .round();
+                let max_y = ((region.y + region.height) * height)
+                    .clamp(min_y, height)
+                    .round();
+                Scissor {
+                    origin: [min_x as u32, min_y as u32],
+                    dimensions: [(max_x - min_x) as u32, (max_y - min_y) as u32],
+                }
+            },
+            line_width: self.options.wireframe.then(|| self.line_width),
         };
+
+        let materials = Materials {
+            default: &self.default_material,
+            created: &self.materials,
+        };
+        let mut command_buffers = if instances.len() < PARALLEL_RECORDING_THRESHOLD {
+            vec![recorder.objects(&self.pipeline, materials, instances, 0)?]
+        } else {
+            let chunk_size = instances.len() / thread_pool.current_num_threads() + 1;
+            let pipeline = &self.pipeline;
+            thread_pool.install(|| {
+                instances
+                    .par_chunks(chunk_size)
+                    .enumerate()
+                    .map(|(index, chunk)| {
+                        let first_instance = (index * chunk_size) as u32;
+                        recorder.objects(pipeline, materials, chunk, first_instance)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })?
+        };
+
+        // Outline is drawn after all game objects, so stencil is fully written by them.
+        if let (Some(outline), Some(outline_pipeline)) = (outline, &self.outline_pipeline) {
+            command_buffers.push(recorder.outline(outline_pipeline, instances, outline)?);
+        }
+        Ok(command_buffers)
+    }
+}
+
+/// Count of instances starting from which command buffers are recorded in parallel.
+///
+/// Recording in parallel has overhead (thread synchronization, more command buffers),
+/// so it is not worth it for small count of instances.
+const PARALLEL_RECORDING_THRESHOLD: usize = 256;
+
+/// Descriptor sets of materials which are referenced by instances while recording.
+#[derive(Copy, Clone)]
+struct Materials<'a> {
+    default: &'a Arc<PersistentDescriptorSet>,
+    created: &'a SlotMap<MaterialHandle, Arc<PersistentDescriptorSet>>,
+}
+
+impl<'a> Materials<'a> {
+    /// Descriptor set of the material of given instance.
+    fn get(&self, instance: &RenderInstance) -> &'a Arc<PersistentDescriptorSet> {
+        instance
+            .material
+            .and_then(|handle| self.created.get(handle))
+            .unwrap_or(self.default)
+    }
+}
+
+/// Resources of the frame which are shared between command buffer recordings.
+struct Recorder {
+    graphics_queue: Arc<Queue>,
+    vertex_buffer: Arc<ImmutableBuffer<[Vertex]>>,
+    index_buffer: Arc<ImmutableBuffer<[u32]>>,
+    frame_descriptor_set: Arc<SingleLayoutDescSet>,
+    viewport: Viewport,
+    scissor: Scissor,
+    line_width: Option<f32>,
+}
+
+impl Recorder {
+    /// Starts recording of secondary command buffer for given pipeline.
+    fn start(
+        &self,
+        pipeline: &Arc<GraphicsPipeline>,
+    ) -> Result<AutoCommandBufferBuilder<SecondaryAutoCommandBuffer>, ObjectDrawError> {
+        let mut builder = AutoCommandBufferBuilder::secondary_graphics(
+            self.graphics_queue.device().clone(),
+            self.graphics_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+            pipeline.subpass().clone(),
+        )?;
+        // Per-frame descriptor set is bound once for all game objects.
         builder
-            .set_viewport(0, std::iter::once(viewport))
-            .bind_pipeline_graphics(self.pipeline.clone())
-            .bind_vertex_buffers(0, self.vertex_buffer.clone())
-            .bind_index_buffer(self.index_buffer.clone())
+            .set_viewport(0, iter::once(self.viewport.clone()))
+            .set_scissor(0, iter::once(self.scissor.clone()))
+            .bind_pipeline_graphics(pipeline.clone())
             .bind_descriptor_sets(
                 PipelineBindPoint::Graphics,
-                self.pipeline.layout().clone(),
+                pipeline.layout().clone(),
                 0,
-                descriptor_sets,
+                self.frame_descriptor_set.clone(),
             )
-            .draw_indexed(self.index_buffer.len() as u32, 1, 0, 0, 0)?;
+            .bind_vertex_buffers(0, self.vertex_buffer.clone())
+            .bind_index_buffer(self.index_buffer.clone());
+        Ok(builder)
+    }
+
+    /// Records secondary command buffer which draws given instances of game objects.
+    ///
+    /// Instances are located in the instance buffer starting from `first_instance`.
+    ///
+    fn objects(
+        &self,
+        pipeline: &Arc<GraphicsPipeline>,
+        materials: Materials,
+        instances: &[RenderInstance],
+        first_instance: u32,
+    ) -> Result<SecondaryAutoCommandBuffer, ObjectDrawError> {
+        let mut builder = self.start(pipeline)?;
+        // Line width is dynamic only for the pipeline which draws edges.
+        if let Some(line_width) = self.line_width {
+            builder.set_line_width(line_width);
+        }
+        let mut start = 0;
+        while start < instances.len() {
+            // Consecutive instances with the same material and tint are drawn at once.
+            let material = materials.get(&instances[start]);
+            let tint = instances[start].tint;
+            let count = instances[start..]
+                .iter()
+                .take_while(|instance| {
+                    Arc::ptr_eq(materials.get(instance), material) && instance.tint == tint
+                })
+                .count();
+            // Shading is done in linear space, so tint is converted from sRGB.
+            let (red, green, blue, alpha) = tint.into_linear().into_components();
+            let push_constants = object_fragment::ty::PushConstants {
+                tint: [red, green, blue, alpha],
+            };
+            builder
+                .bind_descriptor_sets(
+                    PipelineBindPoint::Graphics,
+                    pipeline.layout().clone(),
+                    1,
+                    material.clone(),
+                )
+                .push_constants(pipeline.layout().clone(), 0, push_constants)
+                .draw_indexed(
+                    self.index_buffer.len() as u32,
+                    count as u32,
+                    0,
+                    0,
+                    first_instance + start as u32,
+                )?;
+            start += count;
+        }
         Ok(builder.build()?)
     }
+
+    /// Records secondary command buffer which draws outlines around given instances.
+    ///
+    /// Scaled up game objects are drawn only where stencil was not written by them.
+    ///
+    fn outline(
+        &self,
+        outline_pipeline: &Arc<GraphicsPipeline>,
+        instances: &[RenderInstance],
+        outline: Outline,
+    ) -> Result<SecondaryAutoCommandBuffer, ObjectDrawError> {
+        let mut builder = self.start(outline_pipeline)?;
+        // Outline shader encodes linear color for the render target.
+        let (red, green, blue, alpha) = outline.color.into_linear().into_components();
+        let push_constants = outline_vertex::ty::PushConstants {
+            color: [red, green, blue, alpha],
+            thickness: outline.thickness,
+        };
+        builder
+            .push_constants(outline_pipeline.layout().clone(), 0, push_constants)
+            .draw_indexed(
+                self.index_buffer.len() as u32,
+                instances.len() as u32,
+                0,
+                0,
+                0,
+            )?;
+        Ok(builder.build()?)
+    }
+}
+
+/// Shader modules which replace built-in ones.
+///
+/// Modules are trusted to be valid and to have the interface of built-in shaders,
+/// as required by the unsafe functions which create them.
+///
+struct CustomShaders {
+    vertex: Arc<ShaderModule>,
+    fragment: Arc<ShaderModule>,
+}
+
+impl CustomShaders {
+    /// Loads shader modules from SPIR-V binaries provided by the user.
+    ///
+    /// # Safety
+    ///
+    /// Binaries must be valid SPIR-V modules which declare the same interface
+    /// as built-in shaders.
+    ///
+    unsafe fn load(
+        device: &Arc<Device>,
+        vertex: &[u32],
+        fragment: &[u32],
+    ) -> Result<Self, ObjectDrawSystemCreationError> {
+        Ok(Self {
+            vertex: self::load_shader_module(device, vertex)?,
+            fragment: self::load_shader_module(device, fragment)?,
+        })
+    }
+}
+
+/// Loads shader module from SPIR-V binary provided by the user.
+///
+/// # Safety
+///
+/// Binary must be valid SPIR-V module: only its magic number is checked.
+///
+unsafe fn load_shader_module(
+    device: &Arc<Device>,
+    spirv: &[u32],
+) -> Result<Arc<ShaderModule>, ObjectDrawSystemCreationError> {
+    const SPIRV_MAGIC: u32 = 0x0723_0203;
+    if spirv.first() != Some(&SPIRV_MAGIC) {
+        return Err(ObjectDrawSystemCreationError::ShaderLoad);
+    }
+    let module = ShaderModule::from_words(device.clone(), spirv)?;
+    Ok(module)
+}
+
+/// Creates entry point of `main` function of the module
+/// with the same interface as entry point of built-in shader.
+///
+/// # Safety
+///
+/// Module must declare the same resources, inputs and outputs as built-in shader.
+///
+unsafe fn entry_point_like<'a>(
+    module: &'a ShaderModule,
+    built_in: &GraphicsEntryPoint,
+    spec_constants: &'static [SpecializationMapEntry],
+) -> GraphicsEntryPoint<'a> {
+    let name = CStr::from_bytes_with_nul(b"main\0").unwrap();
+    module.graphics_entry_point(
+        name,
+        built_in.descriptor_set_layout_descs().iter().cloned(),
+        *built_in.push_constant_range(),
+        spec_constants,
+        built_in.input_interface().clone(),
+        built_in.output_interface().clone(),
+        built_in.ty(),
+    )
+}
+
+/// Creates graphics pipeline for rendering of game objects with given options.
+///
+/// If custom shaders are not provided, built-in ones are used.
+///
+fn create_pipeline(
+    graphics_queue: &Arc<Queue>,
+    subpass: Subpass,
+    options: PipelineOptions,
+    shaders: Option<&CustomShaders>,
+) -> Result<Arc<GraphicsPipeline>, ObjectDrawSystemCreationError> {
+    use crate::graphics::shader::default::{fragment, vertex};
+
+    let device = graphics_queue.device().clone();
+
+    let vert_shader_module = vertex::Shader::load(device.clone())?;
+    let frag_shader_module = fragment::Shader::load(device.clone())?;
+
+    let multisampled = options.samples != SampleCount::Sample1;
+
+    let mut depth_stencil = if options.depth_test {
+        DepthStencil::simple_depth_test()
+    } else {
+        DepthStencil::disabled()
+    };
+    // Transparent surfaces must not hide surfaces behind them which are drawn later.
+    if options.transparent {
+        depth_stencil.depth_write = false;
+    }
+    // Game objects always pass stencil test and write reference value into stencil.
+    if options.stencil {
+        let stencil = Stencil {
+            compare: Compare::Always,
+            pass_op: StencilOp::Replace,
+            fail_op: StencilOp::Keep,
+            depth_fail_op: StencilOp::Keep,
+            compare_mask: Some(0xFF),
+            write_mask: Some(0xFF),
+            reference: Some(STENCIL_REFERENCE),
+        };
+        depth_stencil.stencil_front = stencil.clone();
+        depth_stencil.stencil_back = stencil;
+    }
+
+    // Custom shaders are described by the interface of built-in ones.
+    // Safety: custom shaders are trusted to have it (see `CustomShaders`).
+    let (vert_entry_point, frag_entry_point) = match shaders {
+        Some(CustomShaders {
+            vertex: vert_module,
+            fragment: frag_module,
+        }) => unsafe {
+            let vert_built_in = vert_shader_module.main_entry_point();
+            let frag_built_in = frag_shader_module.main_entry_point();
+            let frag_spec_constants = fragment::SpecializationConstants::descriptors();
+            (
+                self::entry_point_like(vert_module, &vert_built_in, &[]),
+                self::entry_point_like(frag_module, &frag_built_in, frag_spec_constants),
+            )
+        },
+        None => (
+            vert_shader_module.main_entry_point(),
+            frag_shader_module.main_entry_point(),
+        ),
+    };
+    validate_vertex_input::<Vertex>(&vert_entry_point)?;
+    // Custom fragment shader could ignore output encoding if it is not declared.
+    let frag_constants = fragment::SpecializationConstants {
+        output_encoding: options.output_encoding as i32,
+    };
+
+    let mut builder = GraphicsPipeline::start()
+        .vertex_input_single_buffer::<Vertex>()
+        .vertex_shader(vert_entry_point, ())
+        .fragment_shader(frag_entry_point, frag_constants)
+        .viewports_scissors_dynamic(1)
+        .depth_stencil(depth_stencil);
+    // Primitive restart for lists requires additional feature, so it is used only for strips.
+    builder = match options.topology {
+        Topology::TriangleList => builder.triangle_list().primitive_restart(false),
+        Topology::TriangleStrip { primitive_restart } => builder
+            .triangle_strip()
+            .primitive_restart(primitive_restart),
+    };
+    builder = match options.cull_mode {
+        CullMode::Back => builder.cull_mode_back(),
+        CullMode::Front => builder.cull_mode_front(),
+        CullMode::None => builder.cull_mode_disabled(),
+    };
+    builder = match options.front_face {
+        FrontFace::Clockwise => builder.front_face_clockwise(),
+        FrontFace::CounterClockwise => builder.front_face_counter_clockwise(),
+    };
+    if options.wireframe {
+        builder = builder.polygon_mode_line().line_width_dynamic();
+    }
+    if options.transparent {
+        builder = builder.blend_alpha_blending();
+    }
+    if options.alpha_to_coverage && multisampled {
+        builder = builder.alpha_to_coverage_enabled();
+    }
+    let pipeline = builder.render_pass(subpass).build(device)?;
+    Ok(Arc::new(pipeline))
+}
+
+/// Creates immutable buffer with given data and waits until it is uploaded.
+fn create_buffer<T, E>(
+    graphics_queue: &Arc<Queue>,
+    data: Vec<T>,
+    usage: BufferUsage,
+) -> Result<Arc<ImmutableBuffer<[T]>>, E>
+where
+    T: Send + Sync + 'static,
+    E: From<DeviceMemoryAllocError> + From<FlushError>,
+{
+    let (buffer, future) = ImmutableBuffer::from_iter(data, usage, graphics_queue.clone())?;
+    future.flush()?;
+    trace::created(Resource::Buffer);
+    Ok(buffer)
+}
+
+/// Creates descriptor set (set 1 of given pipeline) of uniform buffer with given material data
+/// and of given texture.
+fn create_material(
+    graphics_queue: &Arc<Queue>,
+    pipeline: &Arc<GraphicsPipeline>,
+    material: MaterialUBO,
+    texture: Arc<dyn ImageViewAbstract + Send + Sync>,
+    sampler: Arc<Sampler>,
+) -> Result<Arc<PersistentDescriptorSet>, MaterialCreationError> {
+    let (material_buffer, future) = ImmutableBuffer::from_data(
+        material,
+        BufferUsage::uniform_buffer(),
+        graphics_queue.clone(),
+    )?;
+    future.flush()?;
+    trace::created(Resource::Buffer);
+
+    let layout = pipeline.layout().descriptor_set_layouts()[1].clone();
+    let mut builder = PersistentDescriptorSet::start(layout);
+    builder
+        .add_buffer(material_buffer)
+        .map_err(DescriptorSetCreationError::from)?;
+    builder
+        .add_sampled_image(texture, sampler)
+        .map_err(DescriptorSetCreationError::from)?;
+    let descriptor_set = builder.build().map_err(DescriptorSetCreationError::from)?;
+    trace::created(Resource::DescriptorSet);
+    Ok(Arc::new(descriptor_set))
+}
+
+/// Creates texture of game objects from given sRGB image.
+fn create_texture(
+    graphics_queue: &Arc<Queue>,
+    image: &RgbaImage,
+) -> Result<Arc<dyn ImageViewAbstract + Send + Sync>, MaterialCreationError> {
+    let dimensions = ImageDimensions::Dim2d {
+        width: image.width(),
+        height: image.height(),
+        array_layers: 1,
+    };
+    let (image, future) = ImmutableImage::from_iter(
+        image.as_raw().clone(),
+        dimensions,
+        MipmapsCount::One,
+        Format::R8G8B8A8_SRGB,
+        graphics_queue.clone(),
+    )?;
+    future.flush()?;
+    trace::created(Resource::Image);
+    Ok(ImageView::new(image)?)
+}
+
+/// Value which is written into stencil buffer by game objects.
+const STENCIL_REFERENCE: u32 = 1;
+
+/// Creates graphics pipeline for rendering of outlines around game objects.
+fn create_outline_pipeline(
+    graphics_queue: &Arc<Queue>,
+    subpass: Subpass,
+    options: PipelineOptions,
+) -> Result<Arc<GraphicsPipeline>, ObjectDrawSystemCreationError> {
+    use crate::graphics::shader::outline::{fragment, vertex};
+
+    let device = graphics_queue.device().clone();
+
+    let vert_shader_module = vertex::Shader::load(device.clone())?;
+    let frag_shader_module = fragment::Shader::load(device.clone())?;
+
+    // Outline is drawn only where game objects did not write into stencil buffer.
+    let stencil = Stencil {
+        compare: Compare::NotEqual,
+        pass_op: StencilOp::Keep,
+        fail_op: StencilOp::Keep,
+        depth_fail_op: StencilOp::Keep,
+        compare_mask: Some(0xFF),
+        write_mask: Some(0x00),
+        reference: Some(STENCIL_REFERENCE),
+    };
+    let depth_stencil = DepthStencil {
+        depth_compare: Compare::Less,
+        depth_write: false,
+        depth_bounds_test: DepthBounds::Disabled,
+        stencil_front: stencil.clone(),
+        stencil_back: stencil,
+    };
+
+    let vert_entry_point = vert_shader_module.main_entry_point();
+    validate_vertex_input::<Vertex>(&vert_entry_point)?;
+    let frag_constants = fragment::SpecializationConstants {
+        output_encoding: options.output_encoding as i32,
+    };
+
+    let builder = GraphicsPipeline::start()
+        .vertex_input_single_buffer::<Vertex>()
+        .vertex_shader(vert_entry_point, ())
+        .fragment_shader(frag_shader_module.main_entry_point(), frag_constants)
+        .viewports_scissors_dynamic(1)
+        .depth_stencil(depth_stencil)
+        .cull_mode_disabled();
+    // Outlines are drawn with the same mesh as game objects, so they have the same topology.
+    let builder = match options.topology {
+        Topology::TriangleList => builder.triangle_list().primitive_restart(false),
+        Topology::TriangleStrip { primitive_restart } => builder
+            .triangle_strip()
+            .primitive_restart(primitive_restart),
+    };
+    let pipeline = builder.render_pass(subpass).build(device)?;
+    Ok(Arc::new(pipeline))
 }
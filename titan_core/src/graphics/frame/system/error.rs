@@ -1,7 +1,7 @@
 use thiserror::Error;
 use vulkano::command_buffer::{
     AutoCommandBufferBuilderContextError, BeginRenderPassError, BuildError, CommandBufferExecError,
-    ExecuteCommandsError,
+    ExecuteCommandsError, ResetQueryPoolError, WriteTimestampError,
 };
 use vulkano::image::view::ImageViewCreationError;
 use vulkano::image::ImageCreationError;
@@ -33,6 +33,12 @@ pub enum FrameCreationError {
 
     #[error("failed to create framebuffer for the frame: {0}")]
     FramebufferCreation(#[from] FramebufferCreationError),
+
+    #[error("failed to reset timestamp queries for the frame: {0}")]
+    ResetQueryPool(#[from] ResetQueryPoolError),
+
+    #[error("failed to write timestamp for the frame: {0}")]
+    WriteTimestamp(#[from] WriteTimestampError),
 }
 
 #[derive(Debug, Error)]
@@ -45,6 +51,9 @@ pub enum NextPassError {
 
     #[error("next pass command buffer execution failure: {0}")]
     Execution(#[from] CommandBufferExecError),
+
+    #[error("failed to write timestamp for the frame: {0}")]
+    WriteTimestamp(#[from] WriteTimestampError),
 }
 
 #[derive(Debug, Error)]
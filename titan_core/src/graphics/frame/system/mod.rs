@@ -7,13 +7,18 @@
 use vulkano::device::Queue;
 use vulkano::format::{ClearValue, Format};
 use vulkano::image::view::ImageView;
-use vulkano::image::{AttachmentImage, ImageAccess, ImageUsage};
+use vulkano::image::{AttachmentImage, ImageAccess, ImageUsage, SampleCount};
+use vulkano::query::QueryPool;
 use vulkano::render_pass::{Framebuffer, FramebufferAbstract, RenderPass, Subpass};
-use vulkano::sync::GpuFuture;
+use vulkano::sync::{GpuFuture, PipelineStage};
 
 use error::{DrawPassExecuteError, FrameCreationError, FrameSystemCreationError, NextPassError};
 
-use crate::{graphics::utils, window::Size};
+use crate::{
+    config::DepthFormat,
+    graphics::trace::{self, Resource},
+    window::Size,
+};
 
 pub mod error;
 
@@ -28,56 +33,200 @@ pub struct FrameSystem {
     /// Intermediate render target that will contain the depth of each pixel of the scene.
     /// This is a traditional depth buffer. `0.0` means "near", and `1.0` means "far".
     depth_buffer: Option<Arc<AttachmentImage>>,
+
+    /// Multisampled render target for game objects which is resolved into the final image
+    /// (if multisample anti-aliasing is enabled).
+    msaa_buffer: Option<Arc<AttachmentImage>>,
+
+    /// Format of the depth buffer.
+    depth_format: Format,
+
+    /// Format of the final image.
+    final_output_format: Format,
+
+    /// Sample count of game objects rendering.
+    samples: SampleCount,
+
+    /// If depth buffer is stored after the frame, so it could be read back.
+    depth_readback: bool,
 }
 
 impl FrameSystem {
     /// Creates the frame system.
+    ///
+    /// If `depth_readback` is enabled, depth buffer is stored after the frame
+    /// and could be sampled (see [`depth_buffer`](Self::depth_buffer)).
+    ///
     pub fn new(
         graphics_queue: Arc<Queue>,
         final_output_format: Format,
+        depth_format: Format,
+        samples: SampleCount,
+        depth_readback: bool,
     ) -> Result<Self, FrameSystemCreationError> {
         // Check queue for graphics support.
         if !graphics_queue.family().supports_graphics() {
             return Err(FrameSystemCreationError::QueueFamilyNotSupported);
         }
 
-        let device = graphics_queue.device().clone();
-        let depth_format = utils::suitable_depth_stencil_format(device.physical_device());
-
         // TODO: vulkano error: https://github.com/vulkano-rs/vulkano/issues/1665
-        let render_pass = Arc::new(vulkano::ordered_passes_renderpass! {
-            graphics_queue.device().clone(),
-            attachments: {
-                color: {
-                    load: Clear,
-                    store: Store,
-                    format: final_output_format,
-                    samples: 1,
-                },
-                depth: {
-                    load: Clear,
-                    store: DontCare,
-                    format: depth_format,
-                    samples: 1,
-                    initial_layout: ImageLayout::Undefined,
-                    final_layout: ImageLayout::DepthStencilAttachmentOptimal,
-                }
-            },
-            passes: [
-                // Subpass for complex rendering.
-                { color: [color], depth_stencil: {depth}, input: [] },
-                // Subpass for UI rendering.
-                { color: [color], depth_stencil: {}, input: [] }
-            ]
-        }?);
+        let render_pass = if samples == SampleCount::Sample1 {
+            Self::render_pass(
+                &graphics_queue,
+                final_output_format,
+                depth_format,
+                depth_readback,
+            )?
+        } else {
+            Self::msaa_render_pass(
+                &graphics_queue,
+                final_output_format,
+                depth_format,
+                samples,
+                depth_readback,
+            )?
+        };
 
         Ok(Self {
             graphics_queue,
             render_pass,
             depth_buffer: None,
+            msaa_buffer: None,
+            depth_format,
+            final_output_format,
+            samples,
+            depth_readback,
         })
     }
 
+    /// Creates render pass which draws game objects directly into the final image.
+    fn render_pass(
+        graphics_queue: &Arc<Queue>,
+        final_output_format: Format,
+        depth_format: Format,
+        depth_readback: bool,
+    ) -> Result<Arc<RenderPass>, FrameSystemCreationError> {
+        macro_rules! render_pass {
+            ($depth_store:ident) => {
+                vulkano::ordered_passes_renderpass! {
+                    graphics_queue.device().clone(),
+                    attachments: {
+                        color: {
+                            load: Clear,
+                            store: Store,
+                            format: final_output_format,
+                            samples: 1,
+                        },
+                        depth: {
+                            load: Clear,
+                            store: $depth_store,
+                            format: depth_format,
+                            samples: 1,
+                            initial_layout: ImageLayout::Undefined,
+                            final_layout: ImageLayout::DepthStencilAttachmentOptimal,
+                        }
+                    },
+                    passes: [
+                        // Subpass for complex rendering.
+                        { color: [color], depth_stencil: {depth}, input: [] },
+                        // Subpass for UI rendering.
+                        { color: [color], depth_stencil: {}, input: [] }
+                    ]
+                }
+            };
+        }
+
+        // Depth is kept after the frame only if it could be read back for picking.
+        let render_pass = if depth_readback {
+            render_pass!(Store)
+        } else {
+            render_pass!(DontCare)
+        }?;
+        Ok(Arc::new(render_pass))
+    }
+
+    /// Creates render pass which draws game objects into multisampled image
+    /// which is resolved into the final image before UI is drawn.
+    fn msaa_render_pass(
+        graphics_queue: &Arc<Queue>,
+        final_output_format: Format,
+        depth_format: Format,
+        samples: SampleCount,
+        depth_readback: bool,
+    ) -> Result<Arc<RenderPass>, FrameSystemCreationError> {
+        macro_rules! render_pass {
+            ($depth_store:ident) => {
+                vulkano::ordered_passes_renderpass! {
+                    graphics_queue.device().clone(),
+                    attachments: {
+                        msaa: {
+                            load: Clear,
+                            store: DontCare,
+                            format: final_output_format,
+                            samples: samples as u32,
+                        },
+                        color: {
+                            load: DontCare,
+                            store: Store,
+                            format: final_output_format,
+                            samples: 1,
+                        },
+                        depth: {
+                            load: Clear,
+                            store: $depth_store,
+                            format: depth_format,
+                            samples: samples as u32,
+                            initial_layout: ImageLayout::Undefined,
+                            final_layout: ImageLayout::DepthStencilAttachmentOptimal,
+                        }
+                    },
+                    passes: [
+                        // Subpass for complex rendering, resolved into the final image.
+                        { color: [msaa], depth_stencil: {depth}, input: [], resolve: [color] },
+                        // Subpass for UI rendering.
+                        { color: [color], depth_stencil: {}, input: [] }
+                    ]
+                }
+            };
+        }
+
+        // Depth is kept after the frame only if it could be read back for picking.
+        let render_pass = if depth_readback {
+            render_pass!(Store)
+        } else {
+            render_pass!(DontCare)
+        }?;
+        Ok(Arc::new(render_pass))
+    }
+
+    /// Depth buffer of the last frame, if any frame was drawn.
+    ///
+    /// Contents of depth buffer are kept after the frame only if depth readback is enabled.
+    ///
+    pub fn depth_buffer(&self) -> Option<Arc<AttachmentImage>> {
+        self.depth_buffer.clone()
+    }
+
+    /// If depth buffer is stored after the frame, so it could be read back.
+    pub fn depth_readback(&self) -> bool {
+        self.depth_readback
+    }
+
+    /// Sample count of game objects rendering.
+    pub fn samples(&self) -> SampleCount {
+        self.samples
+    }
+
+    /// Format of the depth buffer used for rendering.
+    pub fn depth_format(&self) -> Format {
+        self.depth_format
+    }
+
+    /// Format of the final image which frames are rendered into.
+    pub fn final_output_format(&self) -> Format {
+        self.final_output_format
+    }
+
     /// Retrieve subpass for object rendering.
     pub fn object_subpass(&self) -> Subpass {
         Subpass::from(self.render_pass.clone(), 0).unwrap()
@@ -89,10 +238,18 @@ pub fn ui_subpass(&self) -> Subpass {
     }
 
     /// Starts drawing a new frame.
+    ///
+    /// Final image is cleared with given color (as it is stored in the image).
+    ///
+    /// If `timestamp_queries` are provided (query pool and index of the first query),
+    /// timestamps of start and end of the frame will be written into two queries.
+    ///
     pub fn frame<F, I>(
         &mut self,
         before_future: F,
         final_image: Arc<I>,
+        clear_color: [f32; 4],
+        timestamp_queries: Option<(Arc<QueryPool>, u32)>,
     ) -> Result<Frame, FrameCreationError>
     where
         F: GpuFuture + Send + Sync + 'static,
@@ -110,37 +267,72 @@ pub fn frame<F, I>(
         // or dimensions are incompatible, (re)create buffers.
         if old_dimensions.is_none() || old_dimensions.unwrap() != dimensions {
             // (Re)create depth buffer.
-            let depth_buffer = {
-                let depth_format = utils::suitable_depth_stencil_format(device.physical_device());
-                AttachmentImage::with_usage(
+            let depth_buffer = AttachmentImage::multisampled_with_usage(
+                device.clone(),
+                dimensions,
+                self.samples,
+                self.depth_format,
+                ImageUsage {
+                    sampled: self.depth_readback,
+                    ..ImageUsage::depth_stencil_attachment()
+                },
+            )?;
+            trace::created(Resource::Image);
+            self.depth_buffer = Some(depth_buffer);
+
+            // (Re)create multisampled color buffer.
+            if self.samples != SampleCount::Sample1 {
+                let msaa_buffer = AttachmentImage::transient_multisampled(
                     device.clone(),
                     dimensions,
-                    depth_format,
-                    ImageUsage::depth_stencil_attachment(),
-                )?
-            };
-            self.depth_buffer = Some(depth_buffer.clone());
+                    self.samples,
+                    self.final_output_format,
+                )?;
+                trace::created(Resource::Image);
+                self.msaa_buffer = Some(msaa_buffer);
+            }
         }
 
         // Create framebuffer.
-        let framebuffer = {
+        let framebuffer: Arc<dyn FramebufferAbstract + Send + Sync> = {
             let image_view = ImageView::new(final_image.clone())?;
             let depth_buffer_view = {
                 let depth_buffer = self.depth_buffer.as_ref().unwrap().clone();
                 ImageView::new(depth_buffer)?
             };
-            Arc::new(
-                Framebuffer::start(self.render_pass.clone())
-                    .add(image_view)?
-                    .add(depth_buffer_view)?
-                    .build()?,
-            )
+            match self.msaa_buffer.as_ref() {
+                Some(msaa_buffer) => Arc::new(
+                    Framebuffer::start(self.render_pass.clone())
+                        .add(ImageView::new(msaa_buffer.clone())?)?
+                        .add(image_view)?
+                        .add(depth_buffer_view)?
+                        .build()?,
+                ),
+                None => Arc::new(
+                    Framebuffer::start(self.render_pass.clone())
+                        .add(image_view)?
+                        .add(depth_buffer_view)?
+                        .build()?,
+                ),
+            }
         };
 
-        let clear_values = [
-            ClearValue::Float([0.0, 0.0, 0.0, 1.0]),
-            ClearValue::Depth(1.0),
-        ];
+        let has_stencil =
+            DepthFormat::try_from(self.depth_format).map_or(false, DepthFormat::has_stencil);
+        let depth_clear_value = if has_stencil {
+            ClearValue::DepthStencil((1.0, 0))
+        } else {
+            ClearValue::Depth(1.0)
+        };
+        let clear_values = match self.msaa_buffer {
+            // Final image is fully overwritten by resolve, so it is not cleared.
+            Some(_) => vec![
+                ClearValue::Float(clear_color),
+                ClearValue::None,
+                depth_clear_value,
+            ],
+            None => vec![ClearValue::Float(clear_color), depth_clear_value],
+        };
 
         // Build primary command buffer that will execute secondary command buffers
         // in rendering process.
@@ -149,6 +341,15 @@ pub fn frame<F, I>(
             self.graphics_queue.family(),
             CommandBufferUsage::OneTimeSubmit,
         )?;
+        if let Some((query_pool, first)) = timestamp_queries.clone() {
+            // Safety: queries are reset before writing and are not in use by previous frames
+            // because corresponding frame in flight was already waited.
+            unsafe {
+                builder
+                    .reset_query_pool(query_pool.clone(), first..first + 2)?
+                    .write_timestamp(query_pool, first, PipelineStage::TopOfPipe)?;
+            }
+        }
         builder.begin_render_pass(
             framebuffer.clone(),
             SubpassContents::SecondaryCommandBuffers,
@@ -161,6 +362,7 @@ pub fn frame<F, I>(
             before_future: Some(Box::new(before_future)),
             framebuffer,
             command_buffer_builder: Some(builder),
+            timestamp_queries,
         })
     }
 }
@@ -181,6 +383,9 @@ pub struct Frame<'a> {
 
     /// The command buffer builder that will be built during the lifetime of this object.
     command_buffer_builder: Option<AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>>,
+
+    /// Query pool and index of the first query to write timestamps into.
+    timestamp_queries: Option<(Arc<QueryPool>, u32)>,
 }
 
 impl<'a> Frame<'a> {
@@ -208,10 +413,18 @@ pub fn next_pass<'f>(&'f mut self) -> Result<Option<Pass<'f, 'a>>, NextPassError
 
             // If we are in pass 2 then we have finished rendering UI.
             2 => {
-                self.command_buffer_builder
-                    .as_mut()
-                    .unwrap()
-                    .end_render_pass()?;
+                let builder = self.command_buffer_builder.as_mut().unwrap();
+                builder.end_render_pass()?;
+                if let Some((query_pool, first)) = self.timestamp_queries.take() {
+                    // Safety: query was reset at the start of the frame.
+                    unsafe {
+                        builder.write_timestamp(
+                            query_pool,
+                            first + 1,
+                            PipelineStage::BottomOfPipe,
+                        )?;
+                    }
+                }
                 let command_buffer = self.command_buffer_builder.take().unwrap().build()?;
 
                 // Extract `before_future` and append the command buffer execution to it.
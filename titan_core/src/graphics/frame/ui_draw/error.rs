@@ -2,13 +2,13 @@
 use vulkano::command_buffer::{BuildError, DrawIndexedError};
 use vulkano::image::view::ImageViewCreationError;
 use vulkano::image::ImageCreationError;
+use vulkano::image::SampleCount;
 use vulkano::memory::DeviceMemoryAllocError;
 use vulkano::pipeline::GraphicsPipelineCreationError;
-use vulkano::sampler::SamplerCreationError;
 use vulkano::sync::FlushError;
 use vulkano::OomError;
 
-use crate::graphics::renderer::error::DescriptorSetCreationError;
+use crate::graphics::{renderer::error::DescriptorSetCreationError, vertex::VertexLayoutError};
 
 #[derive(Debug, Error)]
 pub enum UiDrawSystemCreationError {
@@ -18,11 +18,17 @@ pub enum UiDrawSystemCreationError {
     #[error("queue family must support graphics operations")]
     QueueFamilyNotSupported,
 
+    #[error("pipeline is built for {expected:?} samples, but subpass has {actual:?} samples")]
+    SampleCountMismatch {
+        expected: SampleCount,
+        actual: SampleCount,
+    },
+
+    #[error("vertex layout validation failure: {0}")]
+    VertexLayout(#[from] VertexLayoutError),
+
     #[error("graphics pipeline creation failure: {0}")]
     GraphicsPipelineCreation(#[from] GraphicsPipelineCreationError),
-
-    #[error("texture sampler creation failure: {0}")]
-    SamplerCreation(#[from] SamplerCreationError),
 }
 
 #[derive(Debug, Error)]
@@ -10,19 +10,23 @@
 use vulkano::device::Queue;
 use vulkano::format::Format;
 use vulkano::image::view::ImageView;
-use vulkano::image::{ImageDimensions, ImageViewAbstract, ImmutableImage, MipmapsCount};
+use vulkano::image::{
+    ImageDimensions, ImageViewAbstract, ImmutableImage, MipmapsCount, SampleCount,
+};
 use vulkano::pipeline::blend::{AttachmentBlend, BlendFactor};
 use vulkano::pipeline::viewport::{Scissor, Viewport};
 use vulkano::pipeline::{GraphicsPipeline, PipelineBindPoint};
 use vulkano::render_pass::Subpass;
-use vulkano::sampler::{Filter, MipmapMode, Sampler, SamplerAddressMode};
+use vulkano::sampler::Sampler;
 use vulkano::sync::GpuFuture;
 
 use crate::{
     graphics::{
         frame::ui_draw::error::{UiDrawError, UiDrawSystemCreationError},
         renderer::error::DescriptorSetCreationError,
-        vertex::UiVertex,
+        trace::{self, Resource},
+        utils::OutputEncoding,
+        vertex::{validate_vertex_input, UiVertex},
     },
     window::Size,
 };
@@ -51,7 +55,7 @@ pub struct UiDrawSystem {
     /// Collection of descriptor sets for user textures to be drawn in UI.
     user_texture_descriptor_sets: SlotMap<DefaultKey, Arc<dyn DescriptorSet + Send + Sync>>,
 
-    /// A sampler for textures used in UI rendering.
+    /// A sampler for `egui` base texture.
     sampler: Arc<Sampler>,
 }
 
@@ -60,12 +64,23 @@ impl UiDrawSystem {
     pub fn new(
         graphics_queue: Arc<Queue>,
         subpass: Subpass,
+        sampler: Arc<Sampler>,
+        output_encoding: OutputEncoding,
     ) -> Result<Self, UiDrawSystemCreationError> {
         // Check queue for graphics support.
         if !graphics_queue.family().supports_graphics() {
             return Err(UiDrawSystemCreationError::QueueFamilyNotSupported);
         }
 
+        // UI pipeline is not multisampled, so it must be used with single-sampled subpass.
+        let samples = subpass.num_samples().unwrap_or(SampleCount::Sample1);
+        if samples != SampleCount::Sample1 {
+            return Err(UiDrawSystemCreationError::SampleCountMismatch {
+                expected: SampleCount::Sample1,
+                actual: samples,
+            });
+        }
+
         let device = graphics_queue.device().clone();
         let pipeline = {
             use crate::graphics::shader::ui::{fragment, vertex};
@@ -78,11 +93,17 @@ pub fn new(
                 ..AttachmentBlend::alpha_blending()
             };
 
+            let vert_entry_point = vert_shader_module.main_entry_point();
+            validate_vertex_input::<UiVertex>(&vert_entry_point)?;
+            let frag_constants = fragment::SpecializationConstants {
+                output_encoding: output_encoding as i32,
+            };
+
             Arc::new(
                 GraphicsPipeline::start()
                     .vertex_input_single_buffer::<UiVertex>()
-                    .vertex_shader(vert_shader_module.main_entry_point(), ())
-                    .fragment_shader(frag_shader_module.main_entry_point(), ())
+                    .vertex_shader(vert_entry_point, ())
+                    .fragment_shader(frag_shader_module.main_entry_point(), frag_constants)
                     .triangle_list()
                     .viewports_scissors_dynamic(1)
                     .cull_mode_disabled()
@@ -93,24 +114,7 @@ pub fn new(
         };
 
         let vertex_buffer = Arc::new(CpuBufferPool::vertex_buffer(device.clone()));
-        let index_buffer = Arc::new(CpuBufferPool::new(
-            device.clone(),
-            BufferUsage::index_buffer(),
-        ));
-
-        let sampler = Sampler::new(
-            device.clone(),
-            Filter::Linear,
-            Filter::Linear,
-            MipmapMode::Linear,
-            SamplerAddressMode::ClampToEdge,
-            SamplerAddressMode::ClampToEdge,
-            SamplerAddressMode::ClampToEdge,
-            0.0,
-            1.0,
-            0.0,
-            0.0,
-        )?;
+        let index_buffer = Arc::new(CpuBufferPool::new(device, BufferUsage::index_buffer()));
 
         Ok(Self {
             graphics_queue,
@@ -127,36 +131,79 @@ pub fn new(
     fn image_descriptor_set(
         &self,
         image_view: Arc<dyn ImageViewAbstract + Send + Sync>,
+        sampler: Arc<Sampler>,
     ) -> Result<Arc<PersistentDescriptorSet>, DescriptorSetCreationError> {
         let layout = self.pipeline.layout().descriptor_set_layouts()[0].clone();
         let mut builder = PersistentDescriptorSet::start(layout);
         builder
-            .add_sampled_image(image_view, self.sampler.clone())
+            .add_sampled_image(image_view, sampler)
             .map_err(DescriptorSetCreationError::from)?;
         let set = builder.build().map_err(DescriptorSetCreationError::from)?;
+        trace::created(Resource::DescriptorSet);
         Ok(Arc::new(set))
     }
 
-    /// Registers new user texture to be drawn in UI.
+    /// Registers new user texture to be drawn in UI, sampled with given sampler.
+    ///
+    /// Returned identifier stays valid until the texture is unregistered
+    /// and is never reused for other textures.
+    ///
     pub fn register_texture(
         &mut self,
         image_view: Arc<dyn ImageViewAbstract + Send + Sync>,
+        sampler: Arc<Sampler>,
     ) -> Result<TextureId, DescriptorSetCreationError> {
-        let descriptor_set = self.image_descriptor_set(image_view)?;
+        let descriptor_set = self.image_descriptor_set(image_view, sampler)?;
         let key = self.user_texture_descriptor_sets.insert(descriptor_set);
         let id = key.data().as_ffi();
         Ok(TextureId::User(id))
     }
 
+    /// Replaces image and sampler of previously registered user texture,
+    /// keeping its identifier.
+    ///
+    /// Does nothing if the texture is not registered.
+    ///
+    pub fn replace_texture(
+        &mut self,
+        texture_id: TextureId,
+        image_view: Arc<dyn ImageViewAbstract + Send + Sync>,
+        sampler: Arc<Sampler>,
+    ) -> Result<(), DescriptorSetCreationError> {
+        if let TextureId::User(id) = texture_id {
+            let key = DefaultKey::from(KeyData::from_ffi(id));
+            if self.user_texture_descriptor_sets.contains_key(key) {
+                let descriptor_set = self.image_descriptor_set(image_view, sampler)?;
+                self.user_texture_descriptor_sets[key] = descriptor_set;
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes all user textures from other UI draw system, so their identifiers
+    /// stay valid in this system.
+    ///
+    /// Textures should be replaced with [`replace_texture`](Self::replace_texture)
+    /// if they were created by another device.
+    ///
+    pub fn take_textures(&mut self, other: &mut Self) {
+        self.user_texture_descriptor_sets = std::mem::take(&mut other.user_texture_descriptor_sets);
+    }
+
     /// Unregisters previously registered user texture to be drawn in UI.
     pub fn unregister_texture(&mut self, texture_id: TextureId) {
         if let TextureId::User(id) = texture_id {
-            let key_data = KeyData::from_ffi(id);
-            let key = DefaultKey::from(key_data);
+            let key = DefaultKey::from(KeyData::from_ffi(id));
             self.user_texture_descriptor_sets.remove(key);
         }
     }
 
+    /// Descriptor set of user texture with given identifier, if it is registered.
+    fn user_texture(&self, id: u64) -> Option<&Arc<dyn DescriptorSet + Send + Sync>> {
+        let key = DefaultKey::from(KeyData::from_ffi(id));
+        self.user_texture_descriptor_sets.get(key)
+    }
+
     /// Builds a secondary command buffer that draws UI on the current subpass.
     pub fn draw(
         &mut self,
@@ -192,11 +239,12 @@ pub fn draw(
                     self.graphics_queue.clone(),
                 )?;
                 image_future.flush()?;
+                trace::created(Resource::Image);
                 image
             };
 
             let image = ImageView::new(image)?;
-            let set = self.image_descriptor_set(image)?;
+            let set = self.image_descriptor_set(image, self.sampler.clone())?;
             self.texture_descriptor_set = Some(set);
         }
 
@@ -211,6 +259,17 @@ pub fn draw(
             if mesh.vertices.is_empty() || mesh.indices.is_empty() {
                 continue;
             }
+            let descriptor_sets = match mesh.texture_id {
+                TextureId::Egui => self.texture_descriptor_set.as_ref().unwrap().clone(),
+                TextureId::User(id) => match self.user_texture(id) {
+                    Some(descriptor_set) => descriptor_set.clone(),
+                    // UI could still reference texture in the frame it was unregistered.
+                    None => {
+                        log::warn!("user texture {} is not registered, skipping its mesh", id);
+                        continue;
+                    }
+                },
+            };
             let scissor = {
                 let min = rect.min;
                 let min = Pos2 {
@@ -250,17 +309,6 @@ pub fn draw(
                 dimensions: [viewport_size.width as f32, viewport_size.height as f32],
                 depth_range: 0.0..1.0,
             };
-            let descriptor_sets = match mesh.texture_id {
-                TextureId::Egui => self.texture_descriptor_set.as_ref().unwrap().clone(),
-                TextureId::User(id) => {
-                    let key_data = KeyData::from_ffi(id);
-                    let key = DefaultKey::from(key_data);
-                    self.user_texture_descriptor_sets
-                        .get(key)
-                        .expect("User texture was unregistered, but still in use!")
-                        .clone()
-                }
-            };
             builder
                 .set_viewport(0, std::iter::once(viewport))
                 .set_scissor(0, std::iter::once(scissor))
@@ -0,0 +1,36 @@
+//! Light utilities for game engine.
+
+use palette::Srgb;
+use ultraviolet::Vec3;
+
+/// Light which comes from infinitely far away in the same direction (like the sun).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DirectionalLight {
+    /// Direction in which the light travels, in world space.
+    pub direction: Vec3,
+    /// Color of the light.
+    pub color: Srgb,
+    /// Color of ambient light which lits all the surfaces regardless of their orientation.
+    pub ambient: Srgb,
+}
+
+impl DirectionalLight {
+    /// Creates new directional light.
+    pub fn new(direction: Vec3, color: Srgb, ambient: Srgb) -> Self {
+        Self {
+            direction,
+            color,
+            ambient,
+        }
+    }
+}
+
+impl Default for DirectionalLight {
+    fn default() -> Self {
+        Self::new(
+            Vec3::new(-1.0, -1.0, -1.0).normalized(),
+            Srgb::new(1.0, 1.0, 1.0),
+            Srgb::new(0.1, 0.1, 0.1),
+        )
+    }
+}
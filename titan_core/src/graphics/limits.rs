@@ -0,0 +1,41 @@
+//! Utilities for limits of the device used by game engine.
+
+use vulkano::device::physical::PhysicalDevice;
+
+/// Limits of the device which should be respected when creating resources.
+#[derive(Debug, Copy, Clone)]
+pub struct DeviceLimits {
+    /// Max width or height of 2D image.
+    pub max_image_dimension_2d: u32,
+    /// Max count of layers of an image array.
+    pub max_image_array_layers: u32,
+    /// Max size (in bytes) of uniform buffer range.
+    pub max_uniform_buffer_range: u32,
+    /// Max size (in bytes) of storage buffer range.
+    pub max_storage_buffer_range: u32,
+    /// Max size (in bytes) of push constants.
+    pub max_push_constants_size: u32,
+    /// Max anisotropy level of the sampler.
+    pub max_sampler_anisotropy: f32,
+}
+
+impl From<PhysicalDevice<'_>> for DeviceLimits {
+    fn from(physical_device: PhysicalDevice) -> Self {
+        let properties = physical_device.properties();
+        Self {
+            max_image_dimension_2d: properties.max_image_dimension2_d,
+            max_image_array_layers: properties.max_image_array_layers,
+            max_uniform_buffer_range: properties.max_uniform_buffer_range,
+            max_storage_buffer_range: properties.max_storage_buffer_range,
+            max_push_constants_size: properties.max_push_constants_size,
+            max_sampler_anisotropy: properties.max_sampler_anisotropy,
+        }
+    }
+}
+
+impl DeviceLimits {
+    /// Max count of mipmap levels of 2D image, including the base level.
+    pub const fn max_mip_levels(&self) -> u32 {
+        u32::BITS - self.max_image_dimension_2d.leading_zeros()
+    }
+}
@@ -0,0 +1,89 @@
+//! Material utilities for game engine.
+
+use palette::{Srgb, Srgba};
+use slotmap::new_key_type;
+use ultraviolet::Vec4;
+
+new_key_type! {
+    /// Unique identifier of the material created by the renderer.
+    pub struct MaterialHandle;
+}
+
+/// Parameters of the surface of game objects used for lighting.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Material {
+    /// Color which is multiplied with the color of the object.
+    pub base_color: Srgba,
+    /// How much the surface is metallic, in range `0.0..=1.0`.
+    pub metallic: f32,
+    /// How much the surface is rough, in range `0.0..=1.0`.
+    pub roughness: f32,
+    /// Color emitted by the surface regardless of lighting.
+    pub emissive: Srgb,
+}
+
+impl Material {
+    /// Creates new material with given parameters.
+    pub fn new(base_color: Srgba, metallic: f32, roughness: f32, emissive: Srgb) -> Self {
+        Self {
+            base_color,
+            metallic,
+            roughness,
+            emissive,
+        }
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::new(
+            Srgba::new(1.0, 1.0, 1.0, 1.0),
+            0.0,
+            0.5,
+            Srgb::new(0.0, 0.0, 0.0),
+        )
+    }
+}
+
+/// Material uniform buffer object (UBO) that will be passed into uniform buffer.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct MaterialUBO {
+    /// Color (in linear space) which is multiplied with the color of the object.
+    pub color: Vec4,
+    /// Emitted color (in linear space), alpha is unused.
+    pub emissive: Vec4,
+    /// Metallic factor of the surface.
+    pub metallic: f32,
+    /// Roughness factor of the surface.
+    pub roughness: f32,
+}
+
+impl MaterialUBO {
+    pub fn new(color: Vec4, emissive: Vec4, metallic: f32, roughness: f32) -> Self {
+        Self {
+            color,
+            emissive,
+            metallic,
+            roughness,
+        }
+    }
+}
+
+impl Default for MaterialUBO {
+    fn default() -> Self {
+        Material::default().into()
+    }
+}
+
+impl From<Material> for MaterialUBO {
+    fn from(material: Material) -> Self {
+        let (red, green, blue, alpha) = material.base_color.into_linear().into_components();
+        let color = Vec4::new(red, green, blue, alpha);
+        let (red, green, blue) = material.emissive.into_linear().into_components();
+        let emissive = Vec4::new(red, green, blue, 0.0);
+        let metallic = material.metallic.clamp(0.0, 1.0);
+        let roughness = material.roughness.clamp(0.0, 1.0);
+        Self::new(color, emissive, metallic, roughness)
+    }
+}
@@ -0,0 +1,30 @@
+//! Mesh utilities for game engine.
+
+pub use super::vertex::Vertex;
+
+pub mod obj;
+pub mod primitives;
+mod tests;
+
+/// Special index which starts new triangle strip if primitive restart is enabled
+/// (see [`Topology::TriangleStrip`]).
+pub const PRIMITIVE_RESTART_INDEX: u32 = u32::MAX;
+
+/// How vertices of the mesh are assembled into triangles.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Topology {
+    /// Each three indices form separate triangle.
+    TriangleList,
+    /// Each index forms triangle with two previous indices.
+    TriangleStrip {
+        /// If [`PRIMITIVE_RESTART_INDEX`] starts new strip,
+        /// so disjoint strips could be stored in one index buffer.
+        primitive_restart: bool,
+    },
+}
+
+impl Default for Topology {
+    fn default() -> Self {
+        Self::TriangleList
+    }
+}
@@ -0,0 +1,187 @@
+//! Loading of meshes from Wavefront OBJ files.
+//!
+//! Supported statements are vertex positions (`v`, with optional RGB color),
+//! texture coordinates (`vt`), normals (`vn`) and faces (`f`).
+//! Other statements (groups, materials etc.) are ignored.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use palette::Srgba;
+use thiserror::Error;
+use ultraviolet::{Vec2, Vec3};
+
+use super::Vertex;
+
+/// Error of loading mesh from Wavefront OBJ file.
+#[derive(Debug, Error)]
+pub enum ObjLoadError {
+    #[error("failed to read OBJ file: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("OBJ file is malformed at line {line}")]
+    Malformed { line: usize },
+
+    #[error("OBJ file has no faces")]
+    NoFaces,
+}
+
+/// Loads mesh from Wavefront OBJ file.
+pub fn load(path: &Path) -> Result<(Vec<Vertex>, Vec<u32>), ObjLoadError> {
+    let source = fs::read_to_string(path)?;
+    self::parse(&source)
+}
+
+/// Parses mesh from the contents of Wavefront OBJ file.
+///
+/// Vertices without color are white. Faces with more than three vertices
+/// are triangulated as a fan, so they should be convex.
+/// If face has no normals, its flat normal is computed from its positions.
+///
+pub fn parse(source: &str) -> Result<(Vec<Vertex>, Vec<u32>), ObjLoadError> {
+    let mut positions = Vec::new();
+    let mut colors = Vec::new();
+    let mut uvs = Vec::new();
+    let mut normals = Vec::new();
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    // Corners which reference the same attributes share the same vertex.
+    let mut unique_vertices = HashMap::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let malformed = || ObjLoadError::Malformed { line: index + 1 };
+        let line = line.split('#').next().unwrap_or_default();
+        let mut parts = line.split_whitespace();
+        let statement = match parts.next() {
+            Some(statement) => statement,
+            None => continue,
+        };
+        let parse_numbers = || parts.clone().map(str::parse::<f32>);
+        match statement {
+            "v" => {
+                let numbers = parse_numbers()
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| malformed())?;
+                let (position, color) = match numbers[..] {
+                    [x, y, z] | [x, y, z, _] => (Vec3::new(x, y, z), self::white()),
+                    [x, y, z, red, green, blue] => {
+                        (Vec3::new(x, y, z), Srgba::new(red, green, blue, 1.0))
+                    }
+                    _ => return Err(malformed()),
+                };
+                positions.push(position);
+                colors.push(color);
+            }
+            "vt" => {
+                let numbers = parse_numbers()
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| malformed())?;
+                let uv = match numbers[..] {
+                    [u] => Vec2::new(u, 0.0),
+                    [u, v] | [u, v, _] => Vec2::new(u, v),
+                    _ => return Err(malformed()),
+                };
+                uvs.push(uv);
+            }
+            "vn" => {
+                let numbers = parse_numbers()
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| malformed())?;
+                let normal = match numbers[..] {
+                    [x, y, z] => Vec3::new(x, y, z).normalized(),
+                    _ => return Err(malformed()),
+                };
+                normals.push(normal);
+            }
+            "f" => {
+                let corners = parts
+                    .map(|corner| {
+                        self::face_corner(corner, positions.len(), uvs.len(), normals.len())
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(malformed)?;
+                if corners.len() < 3 {
+                    return Err(malformed());
+                }
+                let flat_normal = {
+                    let [a, b, c] = [0, 1, 2].map(|i| positions[corners[i].0]);
+                    let normal = (b - a).cross(c - a);
+                    if normal.mag_sq() > f32::EPSILON {
+                        normal.normalized()
+                    } else {
+                        Vec3::unit_z()
+                    }
+                };
+                let face_indices: Vec<u32> = corners
+                    .into_iter()
+                    .map(|corner @ (position, uv, normal)| {
+                        let mut push_vertex = || {
+                            let vertex = Vertex::new(
+                                positions[position],
+                                normal.map_or(flat_normal, |normal| normals[normal]),
+                                uv.map_or_else(Vec2::zero, |uv| uvs[uv]),
+                                colors[position],
+                            );
+                            vertices.push(vertex);
+                            vertices.len() as u32 - 1
+                        };
+                        // Flat normal differs between faces, so such vertices are not shared.
+                        if normal.is_none() {
+                            return push_vertex();
+                        }
+                        *unique_vertices.entry(corner).or_insert_with(push_vertex)
+                    })
+                    .collect();
+                // Triangulate the polygon as a fan around its first vertex.
+                for i in 1..face_indices.len() - 1 {
+                    indices.extend([face_indices[0], face_indices[i], face_indices[i + 1]]);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    if indices.is_empty() {
+        return Err(ObjLoadError::NoFaces);
+    }
+    Ok((vertices, indices))
+}
+
+/// Parses corner of the face in form of `v`, `v/vt`, `v//vn` or `v/vt/vn`
+/// into zero-based indices of its position, texture coordinates and normal.
+fn face_corner(
+    corner: &str,
+    positions: usize,
+    uvs: usize,
+    normals: usize,
+) -> Option<(usize, Option<usize>, Option<usize>)> {
+    let mut parts = corner.split('/');
+    let position = self::resolve_index(parts.next()?, positions)?;
+    let uv = match parts.next() {
+        None | Some("") => None,
+        Some(uv) => Some(self::resolve_index(uv, uvs)?),
+    };
+    let normal = match parts.next() {
+        None | Some("") => None,
+        Some(normal) => Some(self::resolve_index(normal, normals)?),
+    };
+    Some((position, uv, normal))
+}
+
+/// Converts one-based (or negative, relative to the end) index of OBJ into zero-based one.
+fn resolve_index(index: &str, len: usize) -> Option<usize> {
+    let index: isize = index.parse().ok()?;
+    let index = match index {
+        0 => return None,
+        index if index > 0 => index as usize - 1,
+        index => len.checked_sub(index.unsigned_abs())?,
+    };
+    (index < len).then(|| index)
+}
+
+fn white() -> Srgba {
+    Srgba::new(1.0, 1.0, 1.0, 1.0)
+}
@@ -0,0 +1,132 @@
+//! Generators of primitive meshes which are useful for prototyping.
+//!
+//! All meshes are centered at the origin, have unit size and white color.
+//! Z axis is considered as up, front faces have counter-clockwise winding.
+
+use std::f32::consts::PI;
+
+use palette::Srgba;
+use ultraviolet::{Vec2, Vec3};
+
+use super::Vertex;
+
+/// Creates a quad which lies in XY plane and faces +Z.
+pub fn quad() -> (Vec<Vertex>, Vec<u32>) {
+    self::plane(0)
+}
+
+/// Creates a plane which lies in XY plane and faces +Z.
+///
+/// Each side of the plane is split into `subdivisions + 1` segments.
+///
+pub fn plane(subdivisions: u32) -> (Vec<Vertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    self::grid(
+        Vec3::zero(),
+        Vec3::unit_x(),
+        Vec3::unit_y(),
+        subdivisions + 1,
+        &mut vertices,
+        &mut indices,
+    );
+    (vertices, indices)
+}
+
+/// Creates a cube with separate vertices for each face (so normals are not smoothed).
+pub fn cube() -> (Vec<Vertex>, Vec<u32>) {
+    // Normal of the face and two axes of the face such that `u × v = normal`.
+    let faces = [
+        (Vec3::unit_x(), Vec3::unit_y(), Vec3::unit_z()),
+        (-Vec3::unit_x(), -Vec3::unit_y(), Vec3::unit_z()),
+        (Vec3::unit_y(), -Vec3::unit_x(), Vec3::unit_z()),
+        (-Vec3::unit_y(), Vec3::unit_x(), Vec3::unit_z()),
+        (Vec3::unit_z(), Vec3::unit_x(), Vec3::unit_y()),
+        (-Vec3::unit_z(), -Vec3::unit_x(), Vec3::unit_y()),
+    ];
+
+    let mut vertices = Vec::with_capacity(faces.len() * 4);
+    let mut indices = Vec::with_capacity(faces.len() * 6);
+    for (normal, u, v) in faces {
+        self::grid(normal * 0.5, u, v, 1, &mut vertices, &mut indices);
+    }
+    (vertices, indices)
+}
+
+/// Creates a UV sphere with given count of rings (latitudes) and sectors (longitudes).
+///
+/// At least `2` rings and `3` sectors are used.
+///
+pub fn sphere(rings: u32, sectors: u32) -> (Vec<Vertex>, Vec<u32>) {
+    let rings = rings.max(2);
+    let sectors = sectors.max(3);
+
+    let mut vertices = Vec::with_capacity(((rings + 1) * (sectors + 1)) as usize);
+    for ring in 0..=rings {
+        let phi = PI * ring as f32 / rings as f32;
+        for sector in 0..=sectors {
+            let theta = 2.0 * PI * sector as f32 / sectors as f32;
+            let normal = Vec3::new(phi.sin() * theta.cos(), phi.sin() * theta.sin(), phi.cos());
+            let uv = Vec2::new(sector as f32 / sectors as f32, ring as f32 / rings as f32);
+            vertices.push(Vertex::new(normal * 0.5, normal, uv, self::white()));
+        }
+    }
+
+    let mut indices = Vec::with_capacity((rings * sectors * 6) as usize);
+    for ring in 0..rings {
+        for sector in 0..sectors {
+            let top_left = ring * (sectors + 1) + sector;
+            let top_right = top_left + 1;
+            let bottom_left = top_left + sectors + 1;
+            let bottom_right = bottom_left + 1;
+
+            // Triangles which touch the poles are degenerate, so skip them.
+            if ring != rings - 1 {
+                indices.extend([top_left, bottom_left, bottom_right]);
+            }
+            if ring != 0 {
+                indices.extend([top_left, bottom_right, top_right]);
+            }
+        }
+    }
+    (vertices, indices)
+}
+
+/// Appends a square grid of unit size with given center and axes.
+fn grid(
+    center: Vec3,
+    u: Vec3,
+    v: Vec3,
+    segments: u32,
+    vertices: &mut Vec<Vertex>,
+    indices: &mut Vec<u32>,
+) {
+    let normal = u.cross(v).normalized();
+    let offset = vertices.len() as u32;
+
+    for j in 0..=segments {
+        let t = j as f32 / segments as f32;
+        for i in 0..=segments {
+            let s = i as f32 / segments as f32;
+            let position = center + u * (s - 0.5) + v * (t - 0.5);
+            let uv = Vec2::new(s, 1.0 - t);
+            vertices.push(Vertex::new(position, normal, uv, self::white()));
+        }
+    }
+
+    let row = segments + 1;
+    for j in 0..segments {
+        for i in 0..segments {
+            let bottom_left = offset + j * row + i;
+            let bottom_right = bottom_left + 1;
+            let top_right = bottom_right + row;
+            let top_left = bottom_left + row;
+            indices.extend([bottom_left, bottom_right, top_right]);
+            indices.extend([top_right, top_left, bottom_left]);
+        }
+    }
+}
+
+fn white() -> Srgba {
+    Srgba::new(1.0, 1.0, 1.0, 1.0)
+}
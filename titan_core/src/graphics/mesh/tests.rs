@@ -0,0 +1,90 @@
+#![cfg(test)]
+
+use palette::Srgba;
+use ultraviolet::Vec3;
+
+use super::obj::{self, ObjLoadError};
+
+#[test]
+fn test_obj_quad_fan() {
+    let source = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+f 1 2 3 4
+";
+    let (vertices, indices) = obj::parse(source).unwrap();
+    assert_eq!(vertices.len(), 4);
+    assert_eq!(indices, [0, 1, 2, 0, 2, 3]);
+    // Face has no normals, so its flat normal is used.
+    for vertex in &vertices {
+        assert_eq!(*vertex.normal, Vec3::unit_z());
+    }
+}
+
+#[test]
+fn test_obj_ngon_fan() {
+    let source = "\
+v 0 0 0
+v 2 0 0
+v 3 1 0
+v 1 2 0
+v -1 1 0
+f 1 2 3 4 5
+";
+    let (_, indices) = obj::parse(source).unwrap();
+    assert_eq!(indices, [0, 1, 2, 0, 2, 3, 0, 3, 4]);
+}
+
+#[test]
+fn test_obj_negative_indices() {
+    let source = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vn 0 0 2
+f -3//-1 -2//-1 -1//-1
+";
+    let (vertices, indices) = obj::parse(source).unwrap();
+    assert_eq!(indices, [0, 1, 2]);
+    assert_eq!(*vertices[0].position, Vec3::new(0.0, 0.0, 0.0));
+    assert_eq!(*vertices[2].position, Vec3::new(0.0, 1.0, 0.0));
+    // Normals are normalized while parsing.
+    assert_eq!(*vertices[1].normal, Vec3::unit_z());
+}
+
+#[test]
+fn test_obj_vertex_colors() {
+    let source = "\
+v 0 0 0 1 0 0
+v 1 0 0
+v 0 1 0
+f 1 2 3
+";
+    let (vertices, _) = obj::parse(source).unwrap();
+    assert_eq!(*vertices[0].color, Srgba::new(1.0, 0.0, 0.0, 1.0));
+    // Vertices without color are white.
+    assert_eq!(*vertices[1].color, Srgba::new(1.0, 1.0, 1.0, 1.0));
+    assert_eq!(*vertices[2].color, Srgba::new(1.0, 1.0, 1.0, 1.0));
+}
+
+#[test]
+fn test_obj_malformed() {
+    let malformed = |source: &str| match obj::parse(source) {
+        Err(ObjLoadError::Malformed { line }) => Some(line),
+        _ => None,
+    };
+
+    assert_eq!(malformed("v 0 0 0\nv 1 zero 0\n"), Some(2));
+    assert_eq!(malformed("v 0 0\n"), Some(1));
+    assert_eq!(malformed("v 0 0 0\nv 1 0 0\nf 1 2\n"), Some(3));
+    // Index 0 and indices out of range do not reference any vertex.
+    assert_eq!(malformed("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 0 1 2\n"), Some(4));
+    assert_eq!(malformed("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n"), Some(4));
+    assert_eq!(malformed("v 0 0 0\nv 1 0 0\nv 0 1 0\nf -4 1 2\n"), Some(4));
+    assert!(matches!(
+        obj::parse("v 0 0 0\n"),
+        Err(ObjLoadError::NoFaces)
+    ));
+}
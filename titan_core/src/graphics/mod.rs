@@ -1,12 +1,31 @@
 //! Graphics utilities and backend based on Vulkan API for game engine.
 
+pub use self::frame::object_draw::{
+    error::{MaterialCreationError, ObjectDrawError, ObjectDrawSystemCreationError},
+    RenderInstance,
+};
+pub use self::frame::system::{DrawPass, Frame, Pass};
+pub use self::limits::DeviceLimits;
+pub use self::material::{Material, MaterialHandle};
 pub use self::renderer::*;
+pub use self::sampler::{Filter, MipmapMode, SamplerAddressMode, SamplerDesc};
+pub use self::timing::FrameTiming;
+pub use self::upload::UploadTicket;
 
-pub(crate) mod camera;
+pub mod camera;
+pub mod compute;
+pub mod light;
+pub(crate) mod material;
+pub mod mesh;
 
 mod debug_callback;
 mod frame;
+mod limits;
 mod renderer;
+mod sampler;
 mod shader;
+mod timing;
+mod trace;
+mod upload;
 mod utils;
 mod vertex;
@@ -1,14 +1,22 @@
 //! Error types and utilities for graphics backend for game engine.
 
 use thiserror::Error;
-use vulkano::command_buffer::{BuildError, CommandBufferExecError, UpdateBufferError};
+use vulkano::buffer::cpu_access::ReadLockError;
+use vulkano::command_buffer::{
+    BuildError, CommandBufferExecError, CopyBufferImageError,
+    DispatchError as DispatchCommandError, UpdateBufferError,
+};
 use vulkano::descriptor_set::DescriptorSetError;
 use vulkano::device::DeviceCreationError;
+use vulkano::format::Format;
 use vulkano::image::view::ImageViewCreationError;
 use vulkano::image::ImageCreationError;
 use vulkano::instance::debug::DebugCallbackCreationError;
 use vulkano::instance::InstanceCreationError;
 use vulkano::memory::DeviceMemoryAllocError;
+use vulkano::pipeline::ComputePipelineCreationError;
+use vulkano::query::QueryPoolCreationError;
+use vulkano::sampler::SamplerCreationError;
 use vulkano::swapchain::{AcquireError, CapabilitiesError, SwapchainCreationError};
 use vulkano::sync::FlushError;
 use vulkano::OomError;
@@ -27,6 +35,12 @@ pub enum RendererCreationError {
     #[error("instance creation failure: {0}")]
     InstanceCreation(#[from] InstanceCreationError),
 
+    #[error(
+        "Vulkan is not available on this system ({0}), \
+        make sure that GPU drivers with Vulkan support are installed"
+    )]
+    VulkanUnavailable(InstanceCreationError),
+
     #[error("debug callback creation failure: {0}")]
     DebugCallbackCreation(#[from] DebugCallbackCreationError),
 
@@ -36,6 +50,9 @@ pub enum RendererCreationError {
     #[error("no suitable physical device were found")]
     NoSuitablePhysicalDevice,
 
+    #[error("wireframe rendering is requested, but no device supports non-solid fill mode")]
+    WireframeUnsupported,
+
     #[error("device creation failure: {0}")]
     DeviceCreation(#[from] DeviceCreationError),
 
@@ -48,6 +65,18 @@ pub enum RendererCreationError {
     #[error("failed to allocate device memory: {0}")]
     MemoryAllocation(#[from] DeviceMemoryAllocError),
 
+    #[error("headless image creation failure: {0}")]
+    ImageCreation(#[from] ImageCreationError),
+
+    #[error("sampler creation failure: {0}")]
+    SamplerCreation(#[from] SamplerCreationError),
+
+    #[error("timestamp query pool creation failure: {0}")]
+    QueryPoolCreation(#[from] QueryPoolCreationError),
+
+    #[error("render thread pool creation failure: {0}")]
+    ThreadPoolCreation(#[from] rayon::ThreadPoolBuildError),
+
     #[error("frame system creation failure: {0}")]
     FrameSystemCreation(#[from] FrameSystemCreationError),
 
@@ -68,6 +97,9 @@ pub enum DescriptorSetCreationError {
 /// Error that can happen on resizing of [`Renderer`](super::Renderer) system.
 #[derive(Debug, Error)]
 pub enum ResizeError {
+    #[error("failed to get surface capabilities: {0}")]
+    SurfaceCapabilitiesRetrieve(#[from] CapabilitiesError),
+
     #[error("swapchain recreation failure: {0}")]
     SwapchainRecreation(#[from] SwapchainCreationError),
 }
@@ -87,6 +119,26 @@ pub enum TransferCommandBufferCreationError {
     Build(#[from] BuildError),
 }
 
+/// Error that can happen when waiting for GPU to finish all submitted work.
+#[derive(Debug, Error)]
+pub enum WaitIdleError {
+    #[error("failed to submit pending commands before waiting: {0}")]
+    Flush(#[from] FlushError),
+
+    #[error("failed to wait for device to become idle: {0}")]
+    OutOfMemory(#[from] OomError),
+}
+
+impl WaitIdleError {
+    /// Kind of the error, which tells if it could be recovered from.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Flush(error) => ErrorKind::of_flush(error),
+            Self::OutOfMemory(_) => ErrorKind::OutOfMemory,
+        }
+    }
+}
+
 /// Error that can happen on rendering operation of [`Renderer`](super::Renderer) system.
 #[derive(Debug, Error)]
 pub enum RenderError {
@@ -102,12 +154,21 @@ pub enum RenderError {
     #[error("failed to submit commands while rendering: {0}")]
     SubmitQueue(#[from] FlushError),
 
+    #[error("failed to wait for frame in flight while rendering: {0}")]
+    FenceWait(FlushError),
+
     #[error("frame creation failure: {0}")]
     FrameCreation(#[from] FrameCreationError),
 
+    #[error("failed to allocate uniform buffer for viewport: {0}")]
+    UniformBufferAllocation(#[from] DeviceMemoryAllocError),
+
     #[error("subpass switching failure: {0}")]
     NextPass(#[from] NextPassError),
 
+    #[error("frame was finished by drawing closure, so it cannot be submitted")]
+    FrameFinishedByDraw,
+
     #[error("failed to draw game objects: {0}")]
     ObjectDraw(#[from] ObjectDrawError),
 
@@ -119,14 +180,148 @@ pub enum RenderError {
 
     #[error("failed to resize while rendering: {0}")]
     Resize(#[from] ResizeError),
+
+    #[error("failed to capture the frame: {0}")]
+    Capture(#[from] CaptureError),
+
+    #[error("failed to recreate lost device: {0}")]
+    DeviceRecreation(#[from] RendererCreationError),
+
+    #[error("swapchain was retired because lost device could not be recreated")]
+    SwapchainRetired,
+}
+
+impl RenderError {
+    /// Kind of the error, which tells if it could be recovered from.
+    ///
+    /// Rendering could continue after [`ErrorKind::OutOfMemory`] once some resources are freed,
+    /// but [`ErrorKind::DeviceLost`] means that the renderer must be recreated.
+    ///
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::TransferCommandBufferCreation(
+                TransferCommandBufferCreationError::OutOfMemory(_),
+            ) => ErrorKind::OutOfMemory,
+            Self::AcquireNextImage(error) => ErrorKind::of_acquire(error),
+            Self::SubmitQueue(error) | Self::FenceWait(error) => ErrorKind::of_flush(error),
+            Self::UniformBufferAllocation(error) => ErrorKind::of_allocation(error),
+            Self::FrameCreation(error) => match error {
+                FrameCreationError::OutOfMemory(_) => ErrorKind::OutOfMemory,
+                FrameCreationError::ImageCreation(error) => ErrorKind::of_image(error),
+                _ => ErrorKind::Other,
+            },
+            Self::ObjectDraw(error) => match error {
+                ObjectDrawError::OutOfMemory(_) => ErrorKind::OutOfMemory,
+                ObjectDrawError::BufferCreation(error) => ErrorKind::of_flush(error),
+                ObjectDrawError::BufferAllocation(error) => ErrorKind::of_allocation(error),
+                _ => ErrorKind::Other,
+            },
+            Self::UiDraw(error) => match error {
+                UiDrawError::OutOfMemory(_) => ErrorKind::OutOfMemory,
+                UiDrawError::ImageCreation(error) => ErrorKind::of_image(error),
+                UiDrawError::WaitOnImageCreation(error) => ErrorKind::of_flush(error),
+                UiDrawError::BufferAllocation(error) => ErrorKind::of_allocation(error),
+                _ => ErrorKind::Other,
+            },
+            Self::Resize(error) => match error {
+                ResizeError::SurfaceCapabilitiesRetrieve(CapabilitiesError::OomError(_)) => {
+                    ErrorKind::OutOfMemory
+                }
+                ResizeError::SwapchainRecreation(error) => ErrorKind::of_swapchain(error),
+                _ => ErrorKind::Other,
+            },
+            Self::Capture(error) => match error {
+                CaptureError::OutOfMemory(_) => ErrorKind::OutOfMemory,
+                CaptureError::BufferAllocation(error) => ErrorKind::of_allocation(error),
+                _ => ErrorKind::Other,
+            },
+            Self::DeviceRecreation(error) => match error {
+                RendererCreationError::MemoryAllocation(error) => ErrorKind::of_allocation(error),
+                RendererCreationError::SwapchainCreation(error) => ErrorKind::of_swapchain(error),
+                RendererCreationError::ImageCreation(error) => ErrorKind::of_image(error),
+                _ => ErrorKind::Other,
+            },
+            // Device is still lost, so recreation of it could be tried again.
+            Self::SwapchainRetired => ErrorKind::DeviceLost,
+            // Execution errors only report misuse of resources by command buffers.
+            Self::CommandBufferExecution(_) | Self::NextPass(_) | Self::DrawPassExecution(_) => {
+                ErrorKind::Other
+            }
+            Self::TransferCommandBufferCreation(_) | Self::FrameFinishedByDraw => ErrorKind::Other,
+        }
+    }
+}
+
+/// Kind of the error of the renderer, which allows to distinguish
+/// recoverable conditions from fatal ones.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// Host or device memory is exhausted.
+    OutOfMemory,
+    /// Logical device was lost (for example, because of driver crash or GPU reset).
+    DeviceLost,
+    /// Any other error.
+    Other,
+}
+
+impl ErrorKind {
+    fn of_flush(error: &FlushError) -> Self {
+        match error {
+            FlushError::OomError(_) => Self::OutOfMemory,
+            FlushError::DeviceLost => Self::DeviceLost,
+            _ => Self::Other,
+        }
+    }
+
+    fn of_acquire(error: &AcquireError) -> Self {
+        match error {
+            AcquireError::OomError(_) => Self::OutOfMemory,
+            AcquireError::DeviceLost => Self::DeviceLost,
+            _ => Self::Other,
+        }
+    }
+
+    fn of_allocation(error: &DeviceMemoryAllocError) -> Self {
+        match error {
+            DeviceMemoryAllocError::OomError(_) => Self::OutOfMemory,
+            _ => Self::Other,
+        }
+    }
+
+    fn of_image(error: &ImageCreationError) -> Self {
+        match error {
+            ImageCreationError::AllocError(error) => Self::of_allocation(error),
+            _ => Self::Other,
+        }
+    }
+
+    fn of_swapchain(error: &SwapchainCreationError) -> Self {
+        match error {
+            SwapchainCreationError::OomError(_) => Self::OutOfMemory,
+            SwapchainCreationError::DeviceLost => Self::DeviceLost,
+            _ => Self::Other,
+        }
+    }
 }
 
 /// Error of registering an image for UI.
 #[derive(Debug, Error)]
 pub enum ImageRegisterError {
+    #[error("renderer was shut down, so images cannot be registered anymore")]
+    RendererNotReady,
+
+    #[error("image decoding failure: {0}")]
+    Decode(#[from] image::ImageError),
+
+    #[error("image dimension {requested} exceeds device limit of {limit}")]
+    ExceedsDeviceLimit { limit: u32, requested: u32 },
+
     #[error("descriptor set for image creation failure: {0}")]
     DescriptorSetCreation(#[from] DescriptorSetCreationError),
 
+    #[error("sampler for image creation failure: {0}")]
+    SamplerCreation(#[from] SamplerCreationError),
+
     #[error("image creation failure: {0}")]
     ImageCreation(#[from] ImageCreationError),
 
@@ -136,3 +331,154 @@ pub enum ImageRegisterError {
     #[error("flush error: {0}")]
     Flush(#[from] FlushError),
 }
+
+/// Error of loading compute pipeline from SPIR-V binary.
+#[derive(Debug, Error)]
+pub enum ComputePipelineLoadError {
+    #[error("SPIR-V binary is invalid")]
+    InvalidSpirv,
+
+    #[error("shader module allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("compute pipeline creation failure: {0}")]
+    PipelineCreation(#[from] ComputePipelineCreationError),
+}
+
+/// Error of reading depth of the rendered frame.
+#[derive(Debug, Error)]
+pub enum DepthReadError {
+    #[error("depth readback is disabled by config")]
+    Disabled,
+
+    #[error("depth of format {0:?} could not be read back")]
+    UnsupportedFormat(Format),
+
+    #[error("nothing was rendered yet")]
+    NotRendered,
+
+    #[error("position ({x}, {y}) is out of bounds of depth buffer with size {width}x{height}")]
+    OutOfBounds {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+
+    #[error("shader module or command buffer allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("depth read pipeline creation failure: {0}")]
+    PipelineCreation(#[from] ComputePipelineCreationError),
+
+    #[error("result buffer allocation failure: {0}")]
+    BufferAllocation(#[from] DeviceMemoryAllocError),
+
+    #[error("sampler creation failure: {0}")]
+    SamplerCreation(#[from] SamplerCreationError),
+
+    #[error("depth buffer view creation failure: {0}")]
+    ImageViewCreation(#[from] ImageViewCreationError),
+
+    #[error("descriptor set creation failure: {0}")]
+    DescriptorSetCreation(#[from] DescriptorSetCreationError),
+
+    #[error("dispatch command failure: {0}")]
+    Dispatch(#[from] DispatchCommandError),
+
+    #[error("depth read command buffer build failure: {0}")]
+    CommandBufferBuild(#[from] BuildError),
+
+    #[error("depth read command buffer execution failure: {0}")]
+    CommandBufferExecution(#[from] CommandBufferExecError),
+
+    #[error("failed to submit or wait for depth read commands: {0}")]
+    SubmitQueue(#[from] FlushError),
+
+    #[error("failed to read result buffer: {0}")]
+    ReadLock(#[from] ReadLockError),
+}
+
+/// Error of capturing the frame into the image file.
+#[derive(Debug, Error)]
+pub enum CaptureError {
+    #[error("swapchain images cannot be used as copy source on this device")]
+    Unsupported,
+
+    #[error("format {0:?} cannot be read back as 8-bit RGBA image")]
+    UnsupportedFormat(Format),
+
+    #[error("read back buffer allocation failure: {0}")]
+    BufferAllocation(#[from] DeviceMemoryAllocError),
+
+    #[error("copy command buffer allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("copy command failure: {0}")]
+    Copy(#[from] CopyBufferImageError),
+
+    #[error("copy command buffer build failure: {0}")]
+    CommandBufferBuild(#[from] BuildError),
+
+    #[error("failed to read back buffer: {0}")]
+    ReadLock(#[from] ReadLockError),
+
+    #[error("failed to save captured image: {0}")]
+    Save(#[from] image::ImageError),
+}
+
+/// Error of rendering the frame into the image.
+#[derive(Debug, Error)]
+pub enum RenderToImageError {
+    #[error("image size {width}x{height} is zero or exceeds device limit of {limit}")]
+    InvalidSize { width: u32, height: u32, limit: u32 },
+
+    #[error("format {0:?} cannot be read back as 8-bit RGBA image")]
+    UnsupportedFormat(Format),
+
+    #[error("image creation failure: {0}")]
+    ImageCreation(#[from] ImageCreationError),
+
+    #[error("read back buffer allocation failure: {0}")]
+    BufferAllocation(#[from] DeviceMemoryAllocError),
+
+    #[error("copy command buffer allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("copy command failure: {0}")]
+    Copy(#[from] CopyBufferImageError),
+
+    #[error("copy command buffer build failure: {0}")]
+    CommandBufferBuild(#[from] BuildError),
+
+    #[error("copy command buffer execution failure: {0}")]
+    CommandBufferExecution(#[from] CommandBufferExecError),
+
+    #[error("failed to submit or wait for rendering: {0}")]
+    SubmitQueue(#[from] FlushError),
+
+    #[error("failed to render the frame: {0}")]
+    Render(#[from] RenderError),
+
+    #[error("failed to read back buffer: {0}")]
+    ReadLock(#[from] ReadLockError),
+}
+
+/// Error of dispatching compute work.
+#[derive(Debug, Error)]
+pub enum DispatchError {
+    #[error("command buffer allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("dispatch command failure: {0}")]
+    Dispatch(#[from] DispatchCommandError),
+
+    #[error("compute command buffer build failure: {0}")]
+    CommandBufferBuild(#[from] BuildError),
+
+    #[error("compute command buffer execution failure: {0}")]
+    CommandBufferExecution(#[from] CommandBufferExecError),
+
+    #[error("failed to submit compute commands: {0}")]
+    SubmitQueue(#[from] FlushError),
+}
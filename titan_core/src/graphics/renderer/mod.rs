@@ -1,68 +1,136 @@
 //! Render utilities for graphics backend for game engine.
 
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::iter;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use egui::{ClippedMesh, Texture, TextureId};
 use image::RgbaImage;
-use vulkano::buffer::{BufferUsage, DeviceLocalBuffer};
+use palette::Srgba;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use ultraviolet::Mat4;
+use vulkano::buffer::cpu_pool::CpuBufferPoolSubbuffer;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer, CpuBufferPool, DeviceLocalBuffer};
 use vulkano::command_buffer::{
     AutoCommandBufferBuilder, CommandBufferUsage, PrimaryAutoCommandBuffer,
 };
-use vulkano::device::physical::PhysicalDevice;
+use vulkano::descriptor_set::{DescriptorSetsCollection, PersistentDescriptorSet};
+use vulkano::device::physical::{PhysicalDevice, QueueFamily};
 use vulkano::device::{Device, DeviceExtensions, Features, Queue};
 use vulkano::format::Format;
 use vulkano::image::view::ImageView;
-use vulkano::image::{ImageDimensions, ImageUsage, ImmutableImage, MipmapsCount, SwapchainImage};
+use vulkano::image::{
+    AttachmentImage, ImageAccess, ImageDimensions, ImageUsage, ImmutableImage, MipmapsCount,
+    SampleCount, SwapchainImage,
+};
 use vulkano::instance::debug::{DebugCallback, MessageSeverity, MessageType};
-use vulkano::instance::Instance;
-use vulkano::swapchain::{AcquireError, PresentMode, Surface, Swapchain};
-use vulkano::sync::{FlushError, GpuFuture, SharingMode};
+use vulkano::instance::{Instance, InstanceCreationError};
+use vulkano::memory::pool::StdMemoryPool;
+use vulkano::pipeline::{ComputePipeline, PipelineBindPoint};
+use vulkano::sampler::{Sampler, SamplerAddressMode, SamplerCreationError};
+use vulkano::swapchain::{
+    AcquireError, ColorSpace, CompositeAlpha, PresentMode, Surface, SurfaceTransform, Swapchain,
+};
+use vulkano::sync::{FenceSignalFuture, FlushError, GpuFuture, SharingMode};
 use vulkano::{swapchain, sync};
 use vulkano_win::VkSurfaceBuild;
-use winit::dpi::LogicalSize;
+use winit::dpi::{LogicalSize, PhysicalPosition};
 use winit::event_loop::EventLoop;
 use winit::window::{Window, WindowBuilder};
 
 pub use error::RendererCreationError;
-use error::{ImageRegisterError, RenderError, ResizeError, TransferCommandBufferCreationError};
+use error::{
+    CaptureError, ComputePipelineLoadError, DepthReadError, DescriptorSetCreationError,
+    DispatchError, ErrorKind, ImageRegisterError, RenderError, RenderToImageError, ResizeError,
+    TransferCommandBufferCreationError, WaitIdleError,
+};
 
-use crate::config::Config;
+use crate::config::{
+    Config, DepthFormat, QueueRequest, SamplerFilter, ENGINE_NAME, ENGINE_VERSION,
+};
+use crate::window::{self, Rect, Size};
 
 use super::{
     camera::CameraUBO,
+    compute::{self, ComputeLayout},
     frame::{
-        object_draw::ObjectDrawSystem,
-        system::{FrameSystem, Pass},
+        object_draw::{
+            error::{MaterialCreationError, ObjectDrawError, ObjectDrawSystemCreationError},
+            ObjectDrawSystem, Outline, PipelineOptions, RenderInstance,
+        },
+        system::{Frame, FrameSystem, Pass},
         ui_draw::UiDrawSystem,
     },
+    light::DirectionalLight,
+    limits::DeviceLimits,
+    material::{Material, MaterialHandle},
+    mesh::{Topology, Vertex},
+    sampler::{SamplerCache, SamplerDesc},
+    timing::{FrameTiming, GpuTimer},
+    trace::{self, Resource},
+    upload::UploadTicket,
     utils,
 };
 
 pub mod error;
+mod tests;
 
 /// System that renders all game objects and UI.
 #[allow(dead_code)]
 pub struct Renderer {
     previous_frame_end: Option<Box<dyn GpuFuture + Send + Sync>>,
     recreate_swapchain: bool,
+    swapchain_recreated: Option<Size>,
+    device_recreated: bool,
+    frame_index: usize,
+    shut_down: bool,
+    device_lost: bool,
+    low_latency: bool,
+    fences: Vec<Option<Arc<FenceSignalFuture<Box<dyn GpuFuture + Send + Sync>>>>>,
+    timing: FrameTiming,
+    gpu_timer: Option<GpuTimer>,
     camera_ubo: CameraUBO,
+    light: DirectionalLight,
+    clear_color: Srgba,
+    ui_scale_override: Option<f32>,
+    output_encoding: utils::OutputEncoding,
+    demo_scene: bool,
+    draw_objects: bool,
+    draw_ui: bool,
+    outline: Option<Outline>,
+    instances: Vec<RenderInstance>,
+    viewport_passes: Vec<ViewportPass>,
+    viewport_uniform_pool: CpuBufferPool<CameraUBO>,
 
     ui_draw_system: UiDrawSystem,
     object_draw_system: ObjectDrawSystem,
     frame_system: FrameSystem,
     uniform_buffers: Vec<Arc<DeviceLocalBuffer<CameraUBO>>>,
+    sampler_cache: SamplerCache,
+    ui_sampler_desc: SamplerDesc,
+    ui_images: HashMap<TextureId, UiImage>,
+    depth_read_pipeline: Option<Arc<ComputePipeline>>,
+    capture: Option<PathBuf>,
+    capture_supported: bool,
+    thread_pool: ThreadPool,
 
     swapchain_images: Vec<Arc<SwapchainImage<Window>>>,
-    swapchain: Arc<Swapchain<Window>>,
+    swapchain: Option<Arc<Swapchain<Window>>>,
+    headless_image: Option<Arc<AttachmentImage>>,
+    surface_transform: SurfaceTransform,
     graphics_queue: Arc<Queue>,
     present_queue: Arc<Queue>,
     transfer_queue: Arc<Queue>,
+    compute_queue: Arc<Queue>,
+    compute_queues: Vec<Arc<Queue>>,
     device: Arc<Device>,
-    surface: Arc<Surface<Window>>,
+    output: Output,
     debug_callback: Option<DebugCallback>,
     instance: Arc<Instance>,
+    config: Config,
 }
 
 impl Renderer {
@@ -71,12 +139,84 @@ pub fn new<T>(config: &Config, event_loop: &EventLoop<T>) -> Result<Self, Render
     where
         T: 'static,
     {
-        let instance = utils::create_instance(config)?;
+        let (instance, debug_callback) = Self::create_instance(config)?;
+
+        let surface = {
+            let mut builder = WindowBuilder::new()
+                .with_title(config.name())
+                .with_transparent(config.transparent_window())
+                .with_resizable(config.resizable())
+                .with_visible(false);
+            if let Some((width, height)) = config.window_size() {
+                builder = builder.with_inner_size(LogicalSize::new(width, height));
+            }
+            if let Some((width, height)) = config.min_window_size() {
+                builder = builder.with_min_inner_size(LogicalSize::new(width, height));
+            }
+            if let Some((width, height)) = config.max_window_size() {
+                builder = builder.with_max_inner_size(LogicalSize::new(width, height));
+            }
+            let surface = builder.build_vk_surface(event_loop, instance.clone())?;
+            // Window is not visible yet, so it will be shown at the right position.
+            if let Some((x, y)) = config.window_position() {
+                surface
+                    .window()
+                    .set_outer_position(PhysicalPosition::new(x, y));
+            }
+            if let Some(mode) = config.fullscreen() {
+                let window = surface.window();
+                match window::fullscreen_of(window, mode) {
+                    Some(fullscreen) => window.set_fullscreen(Some(fullscreen)),
+                    None => log::warn!("fullscreen mode {:?} is not available", mode),
+                }
+            }
+            surface
+        };
+        log::info!("window & surface initialized successfully");
+
+        Self::create(config, instance, debug_callback, Output::Window(surface))
+    }
+
+    /// Creates render system without window (headless), which renders frames
+    /// into the image of given size instead of presenting them.
+    ///
+    /// Display is not required, so it could be used for tests and for rendering on servers.
+    /// Rendered frames could be saved with [`capture_frame`](Self::capture_frame),
+    /// or rendered into the separate image with [`render_to_image`](Self::render_to_image).
+    ///
+    pub fn new_headless(
+        config: &Config,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, RendererCreationError> {
+        let (instance, debug_callback) = Self::create_instance(config)?;
+        Self::create(
+            config,
+            instance,
+            debug_callback,
+            Output::Image { width, height },
+        )
+    }
+
+    /// Creates Vulkan instance with debug callback attached to it (if validation is enabled).
+    fn create_instance(
+        config: &Config,
+    ) -> Result<(Arc<Instance>, Option<DebugCallback>), RendererCreationError> {
+        // Missing Vulkan loader or drivers is the first thing users without GPU encounter,
+        // so report it separately from other instance creation errors.
+        let instance = utils::create_instance(config).map_err(|error| match error {
+            InstanceCreationError::LoadingError(_) | InstanceCreationError::IncompatibleDriver => {
+                RendererCreationError::VulkanUnavailable(error)
+            }
+            error => error.into(),
+        })?;
         log::info!(
             "max version of Vulkan instance is {}",
             instance.max_api_version(),
         );
 
+        trace::set_enabled(config.trace_allocations());
+
         let debug_callback = config
             .enable_validation()
             .then(|| {
@@ -86,34 +226,67 @@ pub fn new<T>(config: &Config, event_loop: &EventLoop<T>) -> Result<Self, Render
                 Result::<_, RendererCreationError>::Ok(debug_callback)
             })
             .transpose()?;
+        Ok((instance, debug_callback))
+    }
 
-        let surface = WindowBuilder::new()
-            .with_title(config.name())
-            .with_min_inner_size(LogicalSize::new(250, 100))
-            .with_visible(false)
-            .build_vk_surface(event_loop, instance.clone())?;
-        log::info!("window & surface initialized successfully");
-
+    /// Creates render system for given output.
+    ///
+    /// Surface of the window must not have a swapchain already.
+    ///
+    fn create(
+        config: &Config,
+        instance: Arc<Instance>,
+        debug_callback: Option<DebugCallback>,
+        output: Output,
+    ) -> Result<Self, RendererCreationError> {
         let physical_devices = PhysicalDevice::enumerate(&instance);
         log::info!("enumerated {} physical devices", physical_devices.len());
 
+        let surface = output.surface();
         let required_extensions = DeviceExtensions {
-            khr_swapchain: true,
+            khr_swapchain: surface.is_some(),
             ..DeviceExtensions::none()
         };
-        let required_features = Features::none();
+        // Wireframe rendering is for debugging only, so the feature is not required otherwise.
+        let required_features = Features {
+            fill_mode_non_solid: config.wireframe(),
+            ..Features::none()
+        };
+        let optional_features = Features {
+            sampler_anisotropy: true,
+            wide_lines: true,
+            ..Features::none()
+        };
         let utils::SuitablePhysicalDevice {
             physical_device,
             graphics_family,
             present_family,
             transfer_family,
+            compute_family,
         } = utils::suitable_physical_device(
             physical_devices,
-            &surface,
+            surface,
             &required_extensions,
             &required_features,
+            config.preferred_device(),
         )
-        .ok_or_else(|| RendererCreationError::NoSuitablePhysicalDevice)?;
+        .ok_or_else(|| {
+            // Check if some device would be suitable if wireframe was not requested.
+            let suitable_without_wireframe = config.wireframe()
+                && utils::suitable_physical_device(
+                    PhysicalDevice::enumerate(&instance),
+                    surface,
+                    &required_extensions,
+                    &Features::none(),
+                    config.preferred_device(),
+                )
+                .is_some();
+            if suitable_without_wireframe {
+                RendererCreationError::WireframeUnsupported
+            } else {
+                RendererCreationError::NoSuitablePhysicalDevice
+            }
+        })?;
         log::info!(
             r#"using device "{}" of type "{:?}" with Vulkan version {}"#,
             physical_device.properties().device_name,
@@ -121,171 +294,1168 @@ pub fn new<T>(config: &Config, event_loop: &EventLoop<T>) -> Result<Self, Render
             physical_device.api_version(),
         );
 
-        let (device, mut queues) = {
-            let priorities = 1.0;
-            let unique_queue_families = {
-                let unique_queue_families: HashSet<_> = [
-                    graphics_family.id(),
-                    present_family.unwrap_or(graphics_family).id(),
-                    transfer_family.unwrap_or(graphics_family).id(),
-                ]
+        // Kinds of work could share queue family, so their queues are requested one after another.
+        let queue_config = config.queue_config();
+        let roles = [
+            (graphics_family, queue_config.graphics),
+            (
+                transfer_family.unwrap_or(graphics_family),
+                queue_config.transfer,
+            ),
+            (
+                compute_family.unwrap_or(graphics_family),
+                queue_config.compute,
+            ),
+        ];
+        let mut family_priorities: Vec<(QueueFamily, Vec<f32>)> = Vec::new();
+        let mut role_offsets = [0; 3];
+        for ((family, request), offset) in roles.iter().zip(&mut role_offsets) {
+            let index = match family_priorities
                 .iter()
-                .cloned()
-                .collect();
-                unique_queue_families.into_iter().map(|family| {
-                    (
-                        physical_device.queue_family_by_id(family).unwrap(),
-                        priorities,
-                    )
-                })
+                .position(|(other, _)| other.id() == family.id())
+            {
+                Some(index) => index,
+                None => {
+                    family_priorities.push((*family, Vec::new()));
+                    family_priorities.len() - 1
+                }
             };
+            let priorities = &mut family_priorities[index].1;
+            *offset = priorities.len();
+            let priority = request.priority.clamp(0.0, 1.0);
+            let count = request.count.max(1) as usize;
+            priorities.extend(iter::repeat(priority).take(count));
+        }
+        let present_queue_family = present_family.unwrap_or(graphics_family);
+        if !family_priorities
+            .iter()
+            .any(|(family, _)| family.id() == present_queue_family.id())
+        {
+            family_priorities.push((present_queue_family, vec![1.0]));
+        }
+        for (family, priorities) in &mut family_priorities {
+            let max_count = family.queues_count();
+            if priorities.len() > max_count {
+                log::warn!(
+                    "{} queues of family {} were requested, but only {} are supported",
+                    priorities.len(),
+                    family.id(),
+                    max_count,
+                );
+                priorities.truncate(max_count);
+            }
+            log::info!(
+                "creating {} queues of family {} with priorities {:?}",
+                priorities.len(),
+                family.id(),
+                priorities,
+            );
+        }
+
+        let (device, queues) = {
+            let queue_families = family_priorities.iter().flat_map(|(family, priorities)| {
+                priorities.iter().map(move |&priority| (*family, priority))
+            });
             let required_extensions = physical_device
                 .required_extensions()
                 .union(&required_extensions);
+            let features = physical_device
+                .supported_features()
+                .intersection(&optional_features)
+                .union(&required_features);
             Device::new(
                 physical_device,
-                &required_features,
+                &features,
                 &required_extensions,
-                unique_queue_families,
+                queue_families,
             )?
         };
-        let graphics_queue = queues.next().unwrap();
-        let present_queue = queues.next().unwrap_or_else(|| graphics_queue.clone());
-        let transfer_queue = queues.next().unwrap_or_else(|| graphics_queue.clone());
-
-        let (swapchain, swapchain_images) = {
-            let capabilities = surface.capabilities(physical_device)?;
-            let (format, color_space) = utils::suitable_image_format(&capabilities);
-            let present_mode = capabilities
-                .present_modes
+        // Queues of the role start from its offset in the family,
+        // wrapping around if fewer queues were created than requested.
+        let queues: Vec<_> = queues.collect();
+        let queues_of = |(family, request): (QueueFamily, QueueRequest), offset: usize| {
+            let created = family_priorities
                 .iter()
-                .find(|&mode| mode == PresentMode::Mailbox)
-                .unwrap_or(PresentMode::Fifo);
-            let dimensions = if let Some(current_extent) = capabilities.current_extent {
-                current_extent
-            } else {
-                let window_size = surface.window().inner_size();
-                let min_width = capabilities.min_image_extent[0];
-                let max_width = capabilities.max_image_extent[0];
-                let min_height = capabilities.min_image_extent[1];
-                let max_height = capabilities.max_image_extent[1];
-                [
-                    window_size.width.clamp(min_width, max_width),
-                    window_size.height.clamp(min_height, max_height),
-                ]
-            };
-            let image_count = {
-                let image_count = capabilities.min_image_count + 1;
-                if let Some(max_image_count) = capabilities.max_image_count {
-                    image_count.max(max_image_count)
+                .find(|(other, _)| other.id() == family.id())
+                .map_or(0, |(_, priorities)| priorities.len());
+            let count = (request.count.max(1) as usize).min(created);
+            (offset..offset + count)
+                .map(|index| {
+                    let index = index % created;
+                    let queue = queues.iter().find(|queue| {
+                        queue.family().id() == family.id()
+                            && queue.id_within_family() == index as u32
+                    });
+                    queue.unwrap().clone()
+                })
+                .collect::<Vec<_>>()
+        };
+        let graphics_queue = queues_of(roles[0], role_offsets[0]).remove(0);
+        let transfer_queue = queues_of(roles[1], role_offsets[1]).remove(0);
+        let compute_queues = queues_of(roles[2], role_offsets[2]);
+        let compute_queue = compute_queues[0].clone();
+        let present_queue = queues_of((present_queue_family, QueueRequest::default()), 0).remove(0);
+
+        let capabilities = surface
+            .map(|surface| surface.capabilities(physical_device))
+            .transpose()?;
+        // Headless image has no surface transform, and its format is always supported
+        // and could be read back.
+        let surface_transform = capabilities
+            .as_ref()
+            .map_or(SurfaceTransform::Identity, |capabilities| {
+                capabilities.current_transform
+            });
+        let (format, color_space) = match &capabilities {
+            Some(capabilities) => utils::suitable_image_format(capabilities, config.hdr()),
+            None => (Format::R8G8B8A8_SRGB, ColorSpace::SrgbNonLinear),
+        };
+        let output_encoding = utils::OutputEncoding::of(format, color_space);
+        // Swapchain images are copied into the buffer to capture frames, if supported.
+        let capture_supported = capabilities.as_ref().map_or(true, |capabilities| {
+            capabilities.supported_usage_flags.transfer_source
+        });
+        let (swapchain, swapchain_images) = match (surface, &capabilities) {
+            (Some(surface), Some(capabilities)) => {
+                // FIFO mode is always supported and is synchronized with vertical blank.
+                let present_mode = if config.vsync() {
+                    PresentMode::Fifo
                 } else {
-                    image_count
-                }
-            };
-            let sharing_mode = present_family
-                .as_ref()
-                .map(|present_family| {
-                    (present_family.id() != graphics_family.id()).then(|| {
-                        let queues = [&graphics_queue, &present_queue];
-                        SharingMode::from(&queues[..])
+                    capabilities
+                        .present_modes
+                        .iter()
+                        .find(|&mode| mode == PresentMode::Mailbox)
+                        .unwrap_or(PresentMode::Fifo)
+                };
+                let dimensions = if let Some(current_extent) = capabilities.current_extent {
+                    current_extent
+                } else {
+                    let window_size = surface.window().inner_size();
+                    let min_width = capabilities.min_image_extent[0];
+                    let max_width = capabilities.max_image_extent[0];
+                    let min_height = capabilities.min_image_extent[1];
+                    let max_height = capabilities.max_image_extent[1];
+                    [
+                        window_size.width.clamp(min_width, max_width),
+                        window_size.height.clamp(min_height, max_height),
+                    ]
+                };
+                let image_count = {
+                    let image_count = capabilities.min_image_count + 1;
+                    if let Some(max_image_count) = capabilities.max_image_count {
+                        image_count.max(max_image_count)
+                    } else {
+                        image_count
+                    }
+                };
+                // Transparent window needs alpha of the image to be used by compositor.
+                let composite_alpha = {
+                    let supported = capabilities.supported_composite_alpha;
+                    if !config.transparent_window() {
+                        CompositeAlpha::Opaque
+                    } else if supported.pre_multiplied {
+                        CompositeAlpha::PreMultiplied
+                    } else if supported.post_multiplied {
+                        CompositeAlpha::PostMultiplied
+                    } else {
+                        log::warn!("composite alpha is not supported, window will be opaque");
+                        CompositeAlpha::Opaque
+                    }
+                };
+                let sharing_mode = present_family
+                    .as_ref()
+                    .map(|present_family| {
+                        (present_family.id() != graphics_family.id()).then(|| {
+                            let queues = [&graphics_queue, &present_queue];
+                            SharingMode::from(&queues[..])
+                        })
                     })
+                    .flatten()
+                    .unwrap_or_else(|| SharingMode::from(&graphics_queue));
+                let (swapchain, swapchain_images) =
+                    Swapchain::start(device.clone(), surface.clone())
+                        .format(format)
+                        .color_space(color_space)
+                        .present_mode(present_mode)
+                        .dimensions(dimensions)
+                        .num_images(image_count)
+                        .transform(surface_transform)
+                        .sharing_mode(sharing_mode)
+                        .composite_alpha(composite_alpha)
+                        .usage(ImageUsage {
+                            color_attachment: true,
+                            transfer_source: capture_supported,
+                            ..ImageUsage::none()
+                        })
+                        .build()?;
+                (Some(swapchain), swapchain_images)
+            }
+            _ => (None, Vec::new()),
+        };
+        let headless_image = match &output {
+            Output::Image { width, height } => {
+                let image = AttachmentImage::with_usage(
+                    device.clone(),
+                    [*width, *height],
+                    format,
+                    ImageUsage {
+                        color_attachment: true,
+                        transfer_source: true,
+                        ..ImageUsage::none()
+                    },
+                )?;
+                trace::created(Resource::Image);
+                Some(image)
+            }
+            Output::Window(_) => None,
+        };
+
+        // Uniform buffers are written by transfer queue and read by graphics queue,
+        // so they must be shared between these queue families (if they are different).
+        let uniform_buffers = {
+            let transfer_family = transfer_queue.family();
+            let graphics_family = graphics_queue.family();
+            let queue_families: Vec<_> = iter::once(transfer_family)
+                .chain((graphics_family.id() != transfer_family.id()).then(|| graphics_family))
+                .collect();
+            (0..config.frames_in_flight())
+                .map(|_| {
+                    let buffer = DeviceLocalBuffer::new(
+                        device.clone(),
+                        BufferUsage::uniform_buffer_transfer_destination(),
+                        queue_families.iter().cloned(),
+                    );
+                    trace::created(Resource::Buffer);
+                    buffer
                 })
-                .flatten()
-                .unwrap_or_else(|| SharingMode::from(&graphics_queue));
-            Swapchain::start(device.clone(), surface.clone())
-                .format(format)
-                .color_space(color_space)
-                .present_mode(present_mode)
-                .dimensions(dimensions)
-                .num_images(image_count)
-                .transform(capabilities.current_transform)
-                .sharing_mode(sharing_mode)
-                .usage(ImageUsage::color_attachment())
-                .build()?
+                .collect::<Result<Vec<_>, _>>()?
         };
 
-        let uniform_buffers = swapchain_images
-            .iter()
-            .map(|_| {
-                DeviceLocalBuffer::new(
-                    device.clone(),
-                    BufferUsage::uniform_buffer_transfer_destination(),
-                    iter::once(transfer_queue.family()),
-                )
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        let depth_format = utils::depth_stencil_format(physical_device, config);
+        // Transparent window is cleared with transparent color, so the desktop is visible.
+        let clear_color = if config.transparent_window() {
+            Srgba::new(0.0, 0.0, 0.0, 0.0)
+        } else {
+            Srgba::new(0.0, 0.0, 0.0, 1.0)
+        };
+        let samples = utils::msaa_sample_count(physical_device, config);
+        let frame_system = FrameSystem::new(
+            graphics_queue.clone(),
+            swapchain.format(),
+            depth_format,
+            samples,
+            config.depth_readback(),
+        )?;
+
+        let mut sampler_cache = SamplerCache::new(device.clone());
+        let object_sampler_desc = SamplerDesc {
+            address_mode: SamplerAddressMode::Repeat,
+            ..SamplerDesc::linear()
+        };
+        let object_draw_system = ObjectDrawSystem::new(
+            graphics_queue.clone(),
+            frame_system.object_subpass(),
+            sampler_cache.get(object_sampler_desc)?,
+            PipelineOptions {
+                samples,
+                cull_mode: config.cull_mode(),
+                front_face: config.front_face(),
+                wireframe: config.wireframe(),
+                stencil: DepthFormat::try_from(depth_format)
+                    .map_or(false, DepthFormat::has_stencil),
+                depth_test: config.depth_test(),
+                transparent: config.transparent_objects(),
+                output_encoding,
+                ..PipelineOptions::default()
+            },
+        )?;
+
+        // UI images may have mipmaps, so sampler must not clamp their levels.
+        // Images without mipmaps have the only level, which is used regardless of max LOD.
+        let ui_sampler_desc = SamplerDesc {
+            max_lod: DeviceLimits::from(physical_device).max_mip_levels() as f32,
+            ..match config.ui_sampler_filter() {
+                SamplerFilter::Linear => SamplerDesc::linear(),
+                SamplerFilter::Nearest => SamplerDesc::nearest(),
+            }
+        };
+        let ui_draw_system = UiDrawSystem::new(
+            graphics_queue.clone(),
+            frame_system.ui_subpass(),
+            sampler_cache.get(ui_sampler_desc)?,
+            output_encoding,
+        )?;
 
-        let frame_system = FrameSystem::new(graphics_queue.clone(), swapchain.format())?;
+        let thread_pool = {
+            let mut builder =
+                ThreadPoolBuilder::new().thread_name(|index| format!("titan-render-{}", index));
+            if let Some(threads) = config.render_threads() {
+                builder = builder.num_threads(threads);
+            }
+            builder.build()?
+        };
 
-        let object_draw_system =
-            ObjectDrawSystem::new(graphics_queue.clone(), frame_system.object_subpass())?;
+        let gpu_timer = GpuTimer::new(&device, config.frames_in_flight())?;
+        if gpu_timer.is_none() {
+            log::warn!("timestamps are not supported, GPU busy time will not be measured");
+        }
+        let fences = iter::repeat_with(|| None)
+            .take(config.frames_in_flight())
+            .collect();
 
-        let ui_draw_system = UiDrawSystem::new(graphics_queue.clone(), frame_system.ui_subpass())?;
+        let viewport_uniform_pool = CpuBufferPool::uniform_buffer(device.clone());
 
         let previous_frame_end = Some(Box::new(sync::now(device.clone())) as Box<_>);
-        Ok(Self {
+        let renderer = Self {
+            config: config.clone(),
             instance,
             debug_callback,
-            surface,
+            output,
             device,
             graphics_queue,
             present_queue,
             transfer_queue,
-            swapchain,
+            compute_queue,
+            compute_queues,
+            swapchain: Some(swapchain),
             swapchain_images,
+            headless_image,
+            surface_transform,
             uniform_buffers,
+            sampler_cache,
+            ui_sampler_desc,
+            ui_images: HashMap::new(),
+            depth_read_pipeline: None,
+            capture: None,
+            capture_supported,
+            thread_pool,
             frame_system,
             object_draw_system,
             ui_draw_system,
             camera_ubo: CameraUBO::default(),
+            light: DirectionalLight::default(),
+            clear_color,
+            ui_scale_override: None,
+            output_encoding,
+            demo_scene: config.demo_scene(),
+            draw_objects: true,
+            draw_ui: true,
+            outline: None,
+            instances: Vec::new(),
+            viewport_passes: Vec::new(),
+            viewport_uniform_pool,
             previous_frame_end,
             recreate_swapchain: false,
-        })
+            swapchain_recreated: None,
+            device_recreated: false,
+            frame_index: 0,
+            shut_down: false,
+            device_lost: false,
+            low_latency: config.low_latency(),
+            fences,
+            timing: FrameTiming::default(),
+            gpu_timer,
+        };
+        renderer.log_configuration();
+        Ok(renderer)
     }
 
-    /// Underlying window of render system.
-    pub fn window(&self) -> &Window {
-        self.surface.window()
+    /// Names of all physical devices (GPUs) of the system, in order of their indices.
+    ///
+    /// Could be used to choose preferred device (see [`Config::set_preferred_device`])
+    /// before the renderer is created. If Vulkan is not available, error is logged
+    /// and empty list is returned.
+    ///
+    pub fn enumerate_device_names() -> Vec<String> {
+        let config = Config::new(ENGINE_NAME.to_string(), ENGINE_VERSION.clone(), false);
+        let instance = match utils::create_instance(&config) {
+            Ok(instance) => instance,
+            Err(error) => {
+                log::error!("failed to enumerate devices: {}", error);
+                return Vec::new();
+            }
+        };
+        PhysicalDevice::enumerate(&instance)
+            .map(|physical_device| physical_device.properties().device_name.clone())
+            .collect()
+    }
+
+    /// Logs full configuration of the device, queues and swapchain at info level.
+    ///
+    /// Each line has the form `renderer config: <key> = <value>`,
+    /// so the whole configuration could be found in logs of bug reports.
+    ///
+    pub fn log_configuration(&self) {
+        let physical_device = self.device.physical_device();
+        let properties = physical_device.properties();
+        let samples = self.frame_system.samples();
+        let mut entries: Vec<(&str, String)> = vec![
+            ("device", properties.device_name.clone()),
+            ("device type", format!("{:?}", properties.device_type)),
+            ("api version", self.device.api_version().to_string()),
+            ("features", format!("{:?}", self.device.enabled_features())),
+            (
+                "extensions",
+                format!("{:?}", self.device.enabled_extensions()),
+            ),
+            (
+                "graphics queue family",
+                self.graphics_queue.family().id().to_string(),
+            ),
+            (
+                "present queue family",
+                self.present_queue.family().id().to_string(),
+            ),
+            (
+                "transfer queue family",
+                self.transfer_queue.family().id().to_string(),
+            ),
+            (
+                "compute queue family",
+                self.compute_queue.family().id().to_string(),
+            ),
+        ];
+        if let Output::Image { width, height } = self.output {
+            entries.push(("headless image extent", format!("{:?}", [width, height])));
+        }
+        if let Some(swapchain) = &self.swapchain {
+            entries.extend([
+                ("swapchain format", format!("{:?}", swapchain.format())),
+                (
+                    "swapchain present mode",
+                    format!("{:?}", swapchain.present_mode()),
+                ),
+                ("swapchain image count", swapchain.num_images().to_string()),
+                ("swapchain extent", format!("{:?}", swapchain.dimensions())),
+            ]);
+        }
+        entries.push(("sample count", format!("{:?}", samples)));
+        for (key, value) in entries {
+            log::info!("renderer config: {} = {}", key, value);
+        }
+    }
+
+    /// Underlying window of render system, or `None` if the renderer is headless.
+    pub fn window(&self) -> Option<&Window> {
+        self.output.surface().map(|surface| surface.window())
+    }
+
+    /// Logical device which all the resources of the renderer are created on.
+    ///
+    /// Could be used to create custom resources which are compatible with the renderer.
+    ///
+    pub fn device(&self) -> Arc<Device> {
+        self.device.clone()
+    }
+
+    /// Queue which is used to render frames.
+    pub fn graphics_queue(&self) -> Arc<Queue> {
+        self.graphics_queue.clone()
+    }
+
+    /// Queue which is used to upload data (such as uniform buffers and images).
+    pub fn transfer_queue(&self) -> Arc<Queue> {
+        self.transfer_queue.clone()
+    }
+
+    /// Queue which is used to dispatch compute work.
+    ///
+    /// It could be the same as [graphics queue](Self::graphics_queue)
+    /// if there is no dedicated compute queue family.
+    ///
+    pub fn compute_queue(&self) -> Arc<Queue> {
+        self.compute_queue.clone()
+    }
+
+    /// All the queues which were created to dispatch compute work
+    /// (see [`Config::set_queue_config`]), starting from the [compute queue](Self::compute_queue).
+    pub fn compute_queues(&self) -> &[Arc<Queue>] {
+        &self.compute_queues
+    }
+
+    /// Creates compute pipeline from SPIR-V binary with `main` entry point
+    /// and given layout of its resources.
+    pub fn create_compute_pipeline(
+        &self,
+        spirv: &[u8],
+        layout: ComputeLayout,
+    ) -> Result<Arc<ComputePipeline>, ComputePipelineLoadError> {
+        compute::create_compute_pipeline(self.device.clone(), spirv, layout)
+    }
+
+    /// Requests to capture the next presented frame into PNG image at given path.
+    ///
+    /// Frame is captured as it is presented, including UI.
+    /// Captured frame is read back and saved after GPU finishes rendering of it,
+    /// so capture stalls rendering once and should not be requested every frame
+    /// (for example, only when user presses a screenshot hotkey).
+    ///
+    /// Errors of reading back and saving the image are returned
+    /// by [`render`](Self::render) of the next frame.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if rendered images cannot be copied
+    /// or if their format cannot be converted into 8-bit RGBA image.
+    ///
+    pub fn capture_frame(&mut self, path: impl AsRef<Path>) -> Result<(), CaptureError> {
+        if !self.capture_supported {
+            return Err(CaptureError::Unsupported);
+        }
+        let format = self.frame_system.final_output_format();
+        if readback_swaps_blue_red(format).is_none() {
+            return Err(CaptureError::UnsupportedFormat(format));
+        }
+        self.capture = Some(path.as_ref().to_owned());
+        Ok(())
+    }
+
+    /// Reads depth of the last rendered frame at given position
+    /// (in physical pixels relative to the top left corner of the window).
+    ///
+    /// Returned depth is normalized: `0.0` means "near", and `1.0` means "far".
+    /// If multisample anti-aliasing is enabled, depth of the first sample is returned.
+    ///
+    /// This function blocks until GPU finishes all submitted work and the read itself,
+    /// so it stalls rendering and should not be called every frame
+    /// (for example, only when user clicks to pick an object).
+    ///
+    /// Depth readback must be enabled by [`Config::set_depth_readback`].
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if depth readback is disabled, if depth format has stencil aspect,
+    /// if nothing was rendered yet or if position is out of bounds of the depth buffer.
+    ///
+    pub fn read_depth_at(&mut self, x: u32, y: u32) -> Result<f32, DepthReadError> {
+        use crate::graphics::shader::depth_read;
+
+        if !self.frame_system.depth_readback() {
+            return Err(DepthReadError::Disabled);
+        }
+        // Sampled view of depth stencil image would include both aspects, which is not allowed.
+        let depth_format = self.frame_system.depth_format();
+        if DepthFormat::try_from(depth_format).map_or(true, DepthFormat::has_stencil) {
+            return Err(DepthReadError::UnsupportedFormat(depth_format));
+        }
+        let depth_buffer = self
+            .frame_system
+            .depth_buffer()
+            .ok_or(DepthReadError::NotRendered)?;
+        let [width, height] = depth_buffer.dimensions().width_height();
+        if x >= width || y >= height {
+            return Err(DepthReadError::OutOfBounds {
+                x,
+                y,
+                width,
+                height,
+            });
+        }
+
+        // Pipeline is created on first use, because most games never read depth.
+        let pipeline = match self.depth_read_pipeline.as_ref() {
+            Some(pipeline) => pipeline.clone(),
+            None => {
+                let device = self.device.clone();
+                let pipeline = if self.frame_system.samples() == SampleCount::Sample1 {
+                    let shader = depth_read::single::Shader::load(device.clone())?;
+                    ComputePipeline::new(device, &shader.main_entry_point(), &(), None, |_| {})?
+                } else {
+                    let shader = depth_read::multisampled::Shader::load(device.clone())?;
+                    ComputePipeline::new(device, &shader.main_entry_point(), &(), None, |_| {})?
+                };
+                let pipeline = Arc::new(pipeline);
+                self.depth_read_pipeline = Some(pipeline.clone());
+                pipeline
+            }
+        };
+
+        let result = CpuAccessibleBuffer::from_data(
+            self.device.clone(),
+            BufferUsage::storage_buffer(),
+            true,
+            0.0f32,
+        )?;
+        trace::created(Resource::Buffer);
+        let descriptor_set = {
+            let sampler = self.sampler_cache.get(SamplerDesc::nearest())?;
+            let layout = pipeline.layout().descriptor_set_layouts()[0].clone();
+            let mut builder = PersistentDescriptorSet::start(layout);
+            builder
+                .add_sampled_image(ImageView::new(depth_buffer)?, sampler)
+                .map_err(DescriptorSetCreationError::from)?;
+            builder
+                .add_buffer(result.clone())
+                .map_err(DescriptorSetCreationError::from)?;
+            let set = builder.build().map_err(DescriptorSetCreationError::from)?;
+            trace::created(Resource::DescriptorSet);
+            Arc::new(set)
+        };
+        let push_constants = depth_read::single::ty::PushConstants {
+            position: [x as i32, y as i32],
+        };
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            self.device.clone(),
+            self.graphics_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+        builder
+            .bind_pipeline_compute(pipeline.clone())
+            .bind_descriptor_sets(
+                PipelineBindPoint::Compute,
+                pipeline.layout().clone(),
+                0,
+                descriptor_set,
+            )
+            .push_constants(pipeline.layout().clone(), 0, push_constants)
+            .dispatch([1, 1, 1])?;
+        let command_buffer = builder.build()?;
+
+        // Depth buffer is written by the previous frame, so the read is executed after it.
+        let previous_frame_end = self.previous_frame_end.take().unwrap();
+        let result_ready = previous_frame_end
+            .then_execute(self.graphics_queue.clone(), command_buffer)
+            .map_err(DepthReadError::from)
+            .and_then(|future| {
+                let future = future.then_signal_fence_and_flush()?;
+                future.wait(None)?;
+                Ok(())
+            });
+        // All submitted work is finished (or failed), so the next frame waits for nothing.
+        self.previous_frame_end = Some(Box::new(sync::now(self.device.clone())));
+        result_ready?;
+
+        let depth = *result.read()?;
+        Ok(depth)
+    }
+
+    /// Dispatches compute work with given pipeline, descriptor sets and count of work groups.
+    ///
+    /// Work is submitted immediately, and the next frame waits for it to complete,
+    /// so its results (for example, in a buffer) could be used for rendering.
+    /// Resources which are used by both compute and graphics queues must be shared
+    /// between their queue families.
+    ///
+    pub fn dispatch<S>(
+        &mut self,
+        pipeline: Arc<ComputePipeline>,
+        descriptor_sets: S,
+        groups: [u32; 3],
+    ) -> Result<(), DispatchError>
+    where
+        S: DescriptorSetsCollection,
+    {
+        let command_buffer = self.dispatch_command_buffer(pipeline, descriptor_sets, groups)?;
+
+        // Next frame waits on this semaphore, so compute results are visible for rendering.
+        let previous_frame_end = self.previous_frame_end.take().unwrap();
+        let result = previous_frame_end
+            .then_execute(self.compute_queue.clone(), command_buffer)
+            .map_err(DispatchError::from)
+            .and_then(|future| {
+                let future = future.then_signal_semaphore();
+                future.flush()?;
+                Ok(future)
+            });
+        match result {
+            Ok(future) => {
+                self.previous_frame_end = Some(Box::new(future));
+                Ok(())
+            }
+            Err(error) => {
+                self.previous_frame_end = Some(Box::new(sync::now(self.device.clone())));
+                Err(error)
+            }
+        }
+    }
+
+    /// Dispatches compute work which is independent of rendering (async compute),
+    /// for example, particle simulation.
+    ///
+    /// Work is submitted immediately on the compute queue, and the next frame does not wait for it.
+    /// Returned future signals a fence when the work is completed:
+    /// it must be waited on before reading the results on the CPU.
+    ///
+    /// Rendering is not synchronized with this work in any way, so resources which are used
+    /// by rendering too must not be touched by the frame until the returned future is waited on.
+    /// Use [`dispatch`](Self::dispatch) if the next frame should wait for results instead.
+    ///
+    pub fn dispatch_async<S>(
+        &self,
+        pipeline: Arc<ComputePipeline>,
+        descriptor_sets: S,
+        groups: [u32; 3],
+    ) -> Result<FenceSignalFuture<Box<dyn GpuFuture + Send + Sync>>, DispatchError>
+    where
+        S: DescriptorSetsCollection,
+    {
+        let command_buffer = self.dispatch_command_buffer(pipeline, descriptor_sets, groups)?;
+        let future: Box<dyn GpuFuture + Send + Sync> = Box::new(
+            sync::now(self.device.clone())
+                .then_execute(self.compute_queue.clone(), command_buffer)?,
+        );
+        Ok(future.then_signal_fence_and_flush()?)
+    }
+
+    /// Records compute work into command buffer for the compute queue.
+    fn dispatch_command_buffer<S>(
+        &self,
+        pipeline: Arc<ComputePipeline>,
+        descriptor_sets: S,
+        groups: [u32; 3],
+    ) -> Result<PrimaryAutoCommandBuffer, DispatchError>
+    where
+        S: DescriptorSetsCollection,
+    {
+        let mut builder = AutoCommandBufferBuilder::primary(
+            self.device.clone(),
+            self.compute_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+        builder
+            .bind_pipeline_compute(pipeline.clone())
+            .bind_descriptor_sets(
+                PipelineBindPoint::Compute,
+                pipeline.layout().clone(),
+                0,
+                descriptor_sets,
+            )
+            .dispatch(groups)?;
+        Ok(builder.build()?)
     }
 
     /// Resize the underlying window and update Vulkan objects.
     pub fn resize(&mut self) -> Result<(), ResizeError> {
-        let dimensions = self.window().inner_size().into();
+        // Headless renderer has no swapchain, and swapchain is retired
+        // if the lost device could not be recreated.
+        let (surface, swapchain) = match (self.output.surface(), &self.swapchain) {
+            (Some(surface), Some(swapchain)) => (surface, swapchain),
+            _ => return Ok(()),
+        };
+        // Surface could be rotated (on mobile devices), so query current transform again.
+        let capabilities = surface.capabilities(self.device.physical_device())?;
+        let dimensions = capabilities
+            .current_extent
+            .unwrap_or_else(|| surface.window().inner_size().into());
 
-        let (swapchain, swapchain_images) =
-            self.swapchain.recreate().dimensions(dimensions).build()?;
-        self.swapchain = swapchain;
+        let (swapchain, swapchain_images) = swapchain
+            .recreate()
+            .dimensions(dimensions)
+            .transform(capabilities.current_transform)
+            .build()?;
+        self.swapchain_recreated = Some(Size::from(swapchain.dimensions()));
+        self.swapchain = Some(swapchain);
         self.swapchain_images = swapchain_images;
+        self.surface_transform = capabilities.current_transform;
 
         self.recreate_swapchain = false;
         Ok(())
     }
 
+    /// Returns new size of swapchain images if swapchain was recreated
+    /// since the last call of this function.
+    pub fn take_swapchain_recreated(&mut self) -> Option<Size> {
+        self.swapchain_recreated.take()
+    }
+
+    /// Returns `true` if the device was recreated since the last call of this function
+    /// (see [`recreate_device`](Self::recreate_device)).
+    pub fn take_device_recreated(&mut self) -> bool {
+        std::mem::take(&mut self.device_recreated)
+    }
+
+    /// Blocks until GPU finishes all submitted work.
+    ///
+    /// Work of the previous frame which was not submitted yet (if any) is submitted before.
+    ///
+    pub fn wait_idle(&mut self) -> Result<(), WaitIdleError> {
+        if let Some(previous_frame_end) = self.previous_frame_end.as_mut() {
+            previous_frame_end.flush()?;
+        }
+        // Safety: queues of the device are not used from other threads while waiting,
+        // because renderer is borrowed mutably.
+        unsafe { self.device.wait()? };
+        if let Some(previous_frame_end) = self.previous_frame_end.as_mut() {
+            previous_frame_end.cleanup_finished();
+        }
+        Ok(())
+    }
+
+    /// Waits for GPU to finish all submitted work and stops accepting new resources.
+    ///
+    /// Should be called when application is about to be closed,
+    /// after that images could not be registered anymore.
+    ///
+    pub fn shut_down(&mut self) -> Result<(), WaitIdleError> {
+        self.shut_down = true;
+        self.wait_idle()
+    }
+
+    /// If swapchain is out of date and will be recreated before the next frame.
+    pub fn needs_resize(&self) -> bool {
+        self.recreate_swapchain
+    }
+
+    /// Marks swapchain as out of date, so it will be recreated before the next frame.
+    pub fn request_resize(&mut self) {
+        self.recreate_swapchain = true;
+    }
+
+    /// Timing statistics of the last rendered frame.
+    pub fn timing(&self) -> FrameTiming {
+        self.timing
+    }
+
+    /// Limits of the device used for rendering.
+    pub fn limits(&self) -> DeviceLimits {
+        self.device.physical_device().into()
+    }
+
+    /// Retrieves shared sampler for given description, creating it if needed.
+    pub fn sampler(&mut self, desc: SamplerDesc) -> Result<Arc<Sampler>, SamplerCreationError> {
+        self.sampler_cache.get(desc)
+    }
+
+    /// Submits instances of game objects to be rendered in the next frame.
+    ///
+    /// Instances are rendered only once, so they should be submitted before each frame.
+    ///
+    pub fn submit(&mut self, instances: impl IntoIterator<Item = RenderInstance>) {
+        self.instances.extend(instances);
+    }
+
+    /// Submits instances of game objects to be rendered in the next frame
+    /// into given region of the window with its own camera.
+    ///
+    /// Such submissions could be made many times per frame (for example, for split-screen),
+    /// and are drawn after the instances submitted by [`submit`](Self::submit).
+    /// Both viewport and scissor are set for the region, so geometry is clipped by its bounds.
+    ///
+    /// Depth of instances is mapped into given depth range,
+    /// whose bounds are clamped into `0.0..=1.0`.
+    /// For example, 3D HUD could be drawn in front of anything else into `0.0..0.1`
+    /// while the rest of the scene is drawn into `0.1..1.0`.
+    ///
+    pub fn submit_to_viewport(
+        &mut self,
+        instances: impl IntoIterator<Item = RenderInstance>,
+        region: Rect,
+        depth_range: Range<f32>,
+        camera_ubo: CameraUBO,
+    ) {
+        self.viewport_passes.push(ViewportPass {
+            region,
+            depth_range,
+            camera_ubo,
+            instances: instances.into_iter().collect(),
+        });
+    }
+
+    /// Loads mesh from Wavefront OBJ file which replaces the mesh of game objects.
+    pub fn load_obj(&mut self, path: &Path) -> Result<(), ObjectDrawError> {
+        self.object_draw_system.load_obj(path)
+    }
+
+    /// Replaces the mesh of game objects with given vertices and indices,
+    /// which are assembled into triangles with given topology.
+    ///
+    /// Triangle strips could be separated by
+    /// [`PRIMITIVE_RESTART_INDEX`](crate::mesh::PRIMITIVE_RESTART_INDEX)
+    /// if primitive restart is enabled.
+    ///
+    pub fn set_mesh(
+        &mut self,
+        vertices: Vec<Vertex>,
+        indices: Vec<u32>,
+        topology: Topology,
+    ) -> Result<(), ObjectDrawError> {
+        self.object_draw_system
+            .set_mesh(vertices, indices, topology)
+    }
+
+    /// Replaces shaders which render game objects with given SPIR-V binaries.
+    ///
+    /// Shaders must declare the same resources, inputs and outputs
+    /// as built-in `default.vert` and `default.frag` shaders do.
+    ///
+    /// # Safety
+    ///
+    /// Binaries must be valid SPIR-V modules with the interface described above,
+    /// because it is not checked by the pipeline.
+    ///
+    pub unsafe fn set_object_shaders(
+        &mut self,
+        vertex: &[u32],
+        fragment: &[u32],
+    ) -> Result<(), ObjectDrawSystemCreationError> {
+        self.object_draw_system.set_shaders(vertex, fragment)
+    }
+
+    /// Uploads given sRGB image as the texture of game objects with default material.
+    ///
+    /// If `None`, texture is removed and game objects are colored by their vertices only.
+    ///
+    pub fn set_object_texture(
+        &mut self,
+        image: Option<&RgbaImage>,
+    ) -> Result<(), MaterialCreationError> {
+        self.object_draw_system.set_texture(image)
+    }
+
+    /// Creates new material which could be referenced by instances of game objects.
+    pub fn create_material(
+        &mut self,
+        material: Material,
+    ) -> Result<MaterialHandle, MaterialCreationError> {
+        self.object_draw_system.create_material(material)
+    }
+
+    /// Removes material which was created earlier.
+    ///
+    /// Instances which still reference it are drawn with default material.
+    ///
+    pub fn remove_material(&mut self, handle: MaterialHandle) {
+        self.object_draw_system.remove_material(handle)
+    }
+
+    /// Draws outline of given color and relative thickness around game objects
+    /// in the next rendered frame.
+    ///
+    /// Outlines are stencil-based, so depth format with stencil aspect is required
+    /// (see [`Config::set_depth_format`]): if it is not used, warning is logged.
+    ///
+    pub fn draw_outline(&mut self, color: Srgba, thickness: f32) {
+        if !self.object_draw_system.supports_outline() {
+            log::warn!("depth format has no stencil aspect, outline will not be drawn");
+            return;
+        }
+        self.outline = Some(Outline { color, thickness });
+    }
+
+    /// Width of lines which edges of game objects are drawn with in wireframe mode.
+    pub fn debug_line_width(&self) -> f32 {
+        self.object_draw_system.line_width()
+    }
+
+    /// Sets width of lines which edges of game objects are drawn with in wireframe mode
+    /// (see [`Config::set_wireframe`]), clamped to the line width range of the device.
+    ///
+    /// Lines wider than `1.0` require `wide_lines` device feature:
+    /// if it is not supported, width stays `1.0` and warning is logged.
+    ///
+    pub fn set_debug_line_width(&mut self, width: f32) {
+        self.object_draw_system.set_line_width(width);
+    }
+
+    /// Camera uniform buffer object which is used for rendering.
+    pub fn camera_ubo(&self) -> CameraUBO {
+        self.camera_ubo
+    }
+
     pub fn set_camera_ubo(&mut self, ubo: CameraUBO) {
         self.camera_ubo = ubo;
     }
 
+    /// Directional light which lits all the game objects.
+    pub fn light(&self) -> DirectionalLight {
+        self.light
+    }
+
+    pub fn set_light(&mut self, light: DirectionalLight) {
+        self.light = light;
+    }
+
+    /// Color which the window is cleared with before rendering.
+    pub fn clear_color(&self) -> Srgba {
+        self.clear_color
+    }
+
+    pub fn set_clear_color(&mut self, color: Srgba) {
+        self.clear_color = color;
+    }
+
+    /// If game objects are drawn (`true` by default).
+    pub fn draw_objects(&self) -> bool {
+        self.draw_objects
+    }
+
+    /// Enables or disables drawing of game objects, for example, for pure UI applications.
+    ///
+    /// If disabled, submitted game objects are discarded and subpass of game objects
+    /// is left empty, so the frame contains only the clear color and UI.
+    ///
+    pub fn set_draw_objects(&mut self, draw_objects: bool) {
+        self.draw_objects = draw_objects;
+    }
+
+    /// If UI is drawn (`true` by default).
+    pub fn draw_ui(&self) -> bool {
+        self.draw_ui
+    }
+
+    /// Enables or disables drawing of UI, for example, for pure 3D applications.
+    ///
+    /// If disabled, UI subpass is left empty.
+    ///
+    pub fn set_draw_ui(&mut self, draw_ui: bool) {
+        self.draw_ui = draw_ui;
+    }
+
+    /// Scale factor which UI is drawn with.
+    ///
+    /// It is the scale factor of the window (or `1.0` if the renderer is headless)
+    /// unless it was overridden.
+    ///
+    pub fn ui_scale_factor(&self) -> f32 {
+        self.ui_scale_override.unwrap_or_else(|| {
+            self.window()
+                .map_or(1.0, |window| window.scale_factor() as f32)
+        })
+    }
+
+    /// Scale factor of UI which is used instead of the scale factor of the window, if any.
+    pub fn ui_scale_override(&self) -> Option<f32> {
+        self.ui_scale_override
+    }
+
+    pub fn set_ui_scale_override(&mut self, scale_factor: Option<f32>) {
+        self.ui_scale_override = scale_factor;
+    }
+
     /// Create command buffer for transfer operations which will be executed
     /// before actual rendering.
-    fn transfer_cb(
-        &self,
-        image_index: usize,
-    ) -> Result<PrimaryAutoCommandBuffer, TransferCommandBufferCreationError> {
-        let uniform_buffer = self.uniform_buffers[image_index].clone();
+    fn transfer_cb(&self) -> Result<PrimaryAutoCommandBuffer, TransferCommandBufferCreationError> {
+        let uniform_buffer = self.uniform_buffers[self.frame_index].clone();
 
         let mut builder = AutoCommandBufferBuilder::primary(
             self.device.clone(),
             self.transfer_queue.family(),
             CommandBufferUsage::OneTimeSubmit,
         )?;
-        builder.update_buffer(uniform_buffer, Box::new(self.camera_ubo))?;
+        let camera_ubo = self
+            .camera_ubo
+            .pre_rotated(self.surface_transform)
+            .with_light(&self.light);
+        builder.update_buffer(uniform_buffer, Box::new(camera_ubo))?;
         Ok(builder.build()?)
     }
 
+    /// Registers new image to be drawn in UI, blocking until it is uploaded.
+    ///
+    /// Copy of every registered image is kept in CPU memory until it is unregistered,
+    /// so it could be uploaded again if the device is recreated.
+    /// Images which are not drawn anymore should be unregistered to free that memory.
+    ///
     pub fn register_ui_image(
         &mut self,
         image: &RgbaImage,
     ) -> Result<TextureId, ImageRegisterError> {
+        Ok(self.register_ui_image_async(image)?.wait()?)
+    }
+
+    /// Registers new image to be drawn in UI without waiting for its upload.
+    pub fn register_ui_image_async(
+        &mut self,
+        image: &RgbaImage,
+    ) -> Result<UploadTicket<TextureId>, ImageRegisterError> {
+        self.upload_ui_image(image, true, false, self.ui_sampler_desc)
+    }
+
+    /// Registers new image to be drawn in UI with given sampler,
+    /// blocking until it is uploaded.
+    ///
+    /// Useful for pixel-art images, which should be sampled with nearest filtering
+    /// (see [`SamplerDesc::nearest`]). Samplers are shared between images
+    /// with the same description.
+    ///
+    /// Sampler is associated with the registered texture, so the same image
+    /// can be registered again with a different sampler to be drawn both ways.
+    ///
+    pub fn register_ui_image_with_sampler(
+        &mut self,
+        image: &RgbaImage,
+        sampler: SamplerDesc,
+    ) -> Result<TextureId, ImageRegisterError> {
+        Ok(self.upload_ui_image(image, true, false, sampler)?.wait()?)
+    }
+
+    /// Registers new image with generated mipmaps to be drawn in UI,
+    /// blocking until it is uploaded.
+    ///
+    /// Mipmaps make the image look smooth instead of aliased when it is downscaled.
+    /// They are generated by GPU, which needs a graphics queue for the upload.
+    ///
+    pub fn register_ui_image_mipmapped(
+        &mut self,
+        image: &RgbaImage,
+    ) -> Result<TextureId, ImageRegisterError> {
+        Ok(self
+            .upload_ui_image(image, true, true, self.ui_sampler_desc)?
+            .wait()?)
+    }
+
+    /// Loads image from the file, decodes and registers it to be drawn in UI.
+    ///
+    /// Image is treated as sRGB-encoded if `srgb` is `true`, otherwise as linear.
+    ///
+    pub fn load_texture(
+        &mut self,
+        path: &Path,
+        srgb: bool,
+    ) -> Result<TextureId, ImageRegisterError> {
+        let image = image::open(path)?.to_rgba8();
+        Ok(self
+            .upload_ui_image(&image, srgb, false, self.ui_sampler_desc)?
+            .wait()?)
+    }
+
+    /// Uploads image to be drawn in UI with sRGB or linear format and given sampler,
+    /// generating mipmaps for it if requested.
+    fn upload_ui_image(
+        &mut self,
+        image: &RgbaImage,
+        srgb: bool,
+        mipmaps: bool,
+        sampler: SamplerDesc,
+    ) -> Result<UploadTicket<TextureId>, ImageRegisterError> {
+        if self.shut_down {
+            return Err(ImageRegisterError::RendererNotReady);
+        }
+        let limit = self.limits().max_image_dimension_2d;
+        let requested = image.width().max(image.height());
+        if requested > limit {
+            return Err(ImageRegisterError::ExceedsDeviceLimit { limit, requested });
+        }
+
+        let (image_view, future) = self.create_ui_image(image, srgb, mipmaps)?;
+        let sampler_desc = sampler;
+        let sampler = self.sampler_cache.get(sampler_desc)?;
+        let texture_id = self.ui_draw_system.register_texture(image_view, sampler)?;
+        // Image is kept to be uploaded again if the device is lost.
+        let image = UiImage {
+            image: image.clone(),
+            srgb,
+            mipmaps,
+            sampler: sampler_desc,
+        };
+        self.ui_images.insert(texture_id, image);
+        Ok(UploadTicket::new(future, texture_id)?)
+    }
+
+    /// Creates image to be drawn in UI, returning its view and the future of its upload.
+    fn create_ui_image(
+        &self,
+        image: &RgbaImage,
+        srgb: bool,
+        mipmaps: bool,
+    ) -> Result<
+        (
+            Arc<ImageView<Arc<ImmutableImage>>>,
+            Box<dyn GpuFuture + Send + Sync>,
+        ),
+        ImageRegisterError,
+    > {
         let pixels: Vec<_> = image.pixels().flat_map(|p| p.0).collect();
         let (image, future) = ImmutableImage::from_iter(
             pixels,
@@ -294,87 +1464,497 @@ pub fn register_ui_image(
                 height: image.height(),
                 array_layers: 1,
             },
-            MipmapsCount::One,
-            Format::R8G8B8A8_SRGB, // todo: remove hardcoded format
-            self.transfer_queue.clone(),
+            if mipmaps {
+                MipmapsCount::Log2
+            } else {
+                MipmapsCount::One
+            },
+            if srgb {
+                Format::R8G8B8A8_SRGB
+            } else {
+                Format::R8G8B8A8_UNORM
+            },
+            // Mipmaps are generated with blit commands, which are not supported by transfer queue.
+            if mipmaps {
+                self.graphics_queue.clone()
+            } else {
+                self.transfer_queue.clone()
+            },
         )?;
-        future.flush()?;
+        trace::created(Resource::Image);
         let image_view = ImageView::new(image)?;
-        Ok(self.ui_draw_system.register_texture(image_view)?)
+        Ok((image_view, Box::new(future)))
+    }
+
+    /// Unregisters image which was previously registered to be drawn in UI.
+    pub fn unregister_ui_image(&mut self, texture_id: TextureId) {
+        self.ui_images.remove(&texture_id);
+        self.ui_draw_system.unregister_texture(texture_id)
+    }
+
+    /// Renders a frame with degenerate game object (and its outline, if supported)
+    /// to make the driver finish lazy initialization of the built-in pipelines.
+    ///
+    /// All the pipelines are created by [`new`](Self::new), but some drivers compile them
+    /// only on the first use, which causes stutter on the first rendered frames.
+    /// Should be called right after creation of renderer, for example, while loading.
+    /// Rendered frame is cleared and contains nothing visible.
+    ///
+    pub fn warm_up(&mut self) -> Result<(), RenderError> {
+        let instances = [RenderInstance::new(Mat4::from_scale(0.0))];
+        let outline = self.object_draw_system.supports_outline().then(|| Outline {
+            color: Srgba::new(0.0, 0.0, 0.0, 0.0),
+            thickness: 0.0,
+        });
+        self.present_frame_with(|frame, context| {
+            if let Some(Pass::Deferred(mut draw_pass)) = frame.next_pass()? {
+                let command_buffers = context.object_draw_system.draw(
+                    draw_pass.viewport_size(),
+                    Rect::FULL,
+                    0.0..1.0,
+                    context.uniform_buffer.clone(),
+                    &instances,
+                    outline,
+                    context.thread_pool,
+                )?;
+                for command_buffer in command_buffers {
+                    draw_pass.execute(command_buffer)?;
+                }
+            }
+            Ok(())
+        })
     }
 
     /// Render new frame into the underlying window.
     pub fn render(
         &mut self,
-        mut ui: Option<(Vec<ClippedMesh>, Arc<Texture>)>,
+        ui: Option<(Vec<ClippedMesh>, Arc<Texture>)>,
     ) -> Result<(), RenderError> {
-        self.previous_frame_end.as_mut().unwrap().cleanup_finished();
+        let scale_factor = self.ui_scale_factor();
+        let ui = ui.filter(|_| self.draw_ui);
+        let scene = self.take_scene()?;
+        let result =
+            self.present_frame_with(|frame, context| scene.draw(frame, context, ui, scale_factor));
+        self.recycle_scene(scene);
+        match result {
+            Err(error) if error.kind() == ErrorKind::DeviceLost => {
+                log::warn!("device was lost while rendering ({}), recreating it", error);
+                self.recreate_device()?;
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Recreates the device with swapchain, draw systems and all the pipelines,
+    /// for example, after the device was lost because of driver crash or GPU reset.
+    ///
+    /// Window, camera, light, clear color and images registered for UI are preserved,
+    /// so identifiers of UI images stay valid. Other resources created by the old device
+    /// (materials, texture and mesh of game objects, custom shaders, compute pipelines
+    /// and samplers) cannot be used anymore and must be created again:
+    /// [`take_device_recreated`](Self::take_device_recreated) tells when to do it.
+    ///
+    /// [`render`](Self::render) calls this function when it detects that the device was lost.
+    /// Swapchain of the old device is destroyed first, so if the device could not be recreated,
+    /// rendering fails with [`RenderError::SwapchainRetired`] until recreation succeeds.
+    ///
+    pub fn recreate_device(&mut self) -> Result<(), RendererCreationError> {
+        // Work of the lost device can never be completed, so waiting fails right away.
+        // Otherwise all the work is completed, and in both cases it could be released.
+        if let Err(error) = self.wait_idle() {
+            log::warn!("work of the old device was not completed: {}", error);
+        }
+        self.device_lost = true;
+        self.release_lost_work();
+        // Swapchain of another device could not be replaced, and the surface could not have
+        // two swapchains at once, so the old swapchain is destroyed before the new one is created.
+        self.swapchain = None;
+        self.swapchain_images.clear();
+
+        let mut renderer = Self::create(
+            &self.config,
+            self.instance.clone(),
+            self.debug_callback.take(),
+            self.output.clone(),
+        )?;
+        renderer.camera_ubo = self.camera_ubo;
+        renderer.light = self.light;
+        renderer.clear_color = self.clear_color;
+        renderer.demo_scene = self.demo_scene;
+        renderer.draw_objects = self.draw_objects;
+        renderer.draw_ui = self.draw_ui;
+        renderer.low_latency = self.low_latency;
+        renderer.ui_scale_override = self.ui_scale_override;
+        renderer.set_debug_line_width(self.debug_line_width());
+
+        let mut old = std::mem::replace(self, renderer);
+        // Identifiers of UI images are moved into new UI draw system,
+        // then images are uploaded again to replace their descriptor sets.
+        self.ui_draw_system.take_textures(&mut old.ui_draw_system);
+        let ui_images = std::mem::take(&mut old.ui_images);
+        drop(old);
+        for (texture_id, image) in ui_images {
+            match self.reupload_ui_image(texture_id, &image) {
+                Ok(()) => {
+                    self.ui_images.insert(texture_id, image);
+                }
+                Err(error) => {
+                    log::error!(
+                        "failed to upload UI image {:?} again: {}",
+                        texture_id,
+                        error
+                    );
+                    self.ui_draw_system.unregister_texture(texture_id);
+                }
+            }
+        }
+        self.device_recreated = true;
+        log::info!("device was recreated successfully");
+        Ok(())
+    }
+
+    /// Releases futures of the work submitted to the device without waiting for them.
+    ///
+    /// Device must be lost or idle, so that resources used by the work are not in use anymore.
+    ///
+    fn release_lost_work(&mut self) {
+        // Frames in flight are released from the oldest one, because each frame waits
+        // for the previous one: futures of older frames are already cleaned up
+        // when newer frames are released, so nothing is signalled twice.
+        let frames = self.fences.len();
+        for offset in 0..frames {
+            let index = (self.frame_index + offset) % frames;
+            if let Some(fence) = self.fences[index].take() {
+                // SAFETY: device is lost or idle, so resources could be freed right away.
+                unsafe { fence.signal_finished() };
+                // Future is cleaned up even if waiting fails (which is expected for lost device),
+                // and zero timeout makes sure that nothing blocks here.
+                let _ = fence.wait(Some(Duration::from_secs(0)));
+            }
+        }
+        if let Some(future) = self.previous_frame_end.take() {
+            // SAFETY: the same as above.
+            unsafe { future.signal_finished() };
+        }
+        self.previous_frame_end = Some(Box::new(sync::now(self.device.clone())));
+    }
+
+    /// Uploads image registered for UI into the new device, keeping its identifier.
+    fn reupload_ui_image(
+        &mut self,
+        texture_id: TextureId,
+        image: &UiImage,
+    ) -> Result<(), ImageRegisterError> {
+        let (image_view, future) = self.create_ui_image(&image.image, image.srgb, image.mipmaps)?;
+        let sampler = self.sampler_cache.get(image.sampler)?;
+        self.ui_draw_system
+            .replace_texture(texture_id, image_view, sampler)?;
+        UploadTicket::new(future, ())?.wait()?;
+        Ok(())
+    }
+
+    /// Renders new frame into the image of given size instead of the window
+    /// and reads it back into CPU memory.
+    ///
+    /// Frame contains the same game objects as the frame rendered by [`render`](Self::render),
+    /// but without UI. Submitted game objects are kept, so they are still drawn by the next frame.
+    /// Image can be rendered before the window is shown,
+    /// which makes it useful for screenshots and for tests.
+    ///
+    /// Rendering into an image does not require a display
+    /// if the renderer was created with [`new_headless`](Self::new_headless).
+    ///
+    /// This function blocks until GPU finishes rendering of the image,
+    /// so it stalls rendering and should not be called every frame.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if size is zero or exceeds device limits,
+    /// or if swapchain format cannot be converted into 8-bit RGBA image.
+    ///
+    pub fn render_to_image(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage, RenderToImageError> {
+        let limit = self.limits().max_image_dimension_2d;
+        if width == 0 || height == 0 || width > limit || height > limit {
+            return Err(RenderToImageError::InvalidSize {
+                width,
+                height,
+                limit,
+            });
+        }
+        let format = self.frame_system.final_output_format();
+        let swap_blue_red =
+            readback_swaps_blue_red(format).ok_or(RenderToImageError::UnsupportedFormat(format))?;
+
+        let _trace = trace::frame();
+        self.wait_frame_resources()?;
+
+        let image = AttachmentImage::with_usage(
+            self.device.clone(),
+            [width, height],
+            format,
+            ImageUsage {
+                color_attachment: true,
+                transfer_source: true,
+                ..ImageUsage::none()
+            },
+        )?;
+        trace::created(Resource::Image);
+        let buffer = CpuAccessibleBuffer::from_iter(
+            self.device.clone(),
+            BufferUsage::transfer_destination(),
+            true,
+            iter::repeat(0u8).take(width as usize * height as usize * 4),
+        )?;
+        trace::created(Resource::Buffer);
+        let mut builder = AutoCommandBufferBuilder::primary(
+            self.device.clone(),
+            self.graphics_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+        builder.copy_image_to_buffer(image.clone(), buffer.clone())?;
+        let copy_command_buffer = builder.build()?;
+
+        // Frame dependencies are the following:
+        // previous frame end -> transfer -> graphics -> copy -> fence.
+        let scene = self.copy_scene()?;
+        let previous_frame_end = self.previous_frame_end.take().unwrap();
+        let graphics_future = self.record_frame(previous_frame_end, image, |frame, context| {
+            scene.draw(frame, context, None, 1.0)
+        });
+        let copied = graphics_future
+            .map_err(RenderToImageError::from)
+            .and_then(|future| {
+                let future = future
+                    .then_execute(self.graphics_queue.clone(), copy_command_buffer)?
+                    .then_signal_fence_and_flush()?;
+                future.wait(None)?;
+                Ok(())
+            });
+        // All submitted work is finished (or failed), so the next frame waits for nothing.
+        self.previous_frame_end = Some(Box::new(sync::now(self.device.clone())));
+        copied?;
+
+        let pixels = buffer.read()?;
+        Ok(readback_image(width, height, &pixels, swap_blue_red))
+    }
+
+    /// Takes game objects submitted for the next frame.
+    fn take_scene(&mut self) -> Result<SceneDraw, RenderError> {
+        let outline = self.outline.take();
+        // Submitted objects are discarded if they are not drawn, so they do not pile up.
+        // Subpass of game objects is skipped when there is nothing to draw.
+        if !self.draw_objects {
+            self.viewport_passes.clear();
+            self.instances.clear();
+        }
+        let instances = std::mem::take(&mut self.instances);
+        let viewport_passes = std::mem::take(&mut self.viewport_passes);
+        self.scene(instances, viewport_passes, outline)
+    }
+
+    /// Copies game objects submitted for the next frame, so they are still drawn by it.
+    fn copy_scene(&self) -> Result<SceneDraw, RenderError> {
+        if !self.draw_objects {
+            return self.scene(Vec::new(), Vec::new(), None);
+        }
+        let instances = self.instances.clone();
+        let viewport_passes = self.viewport_passes.clone();
+        self.scene(instances, viewport_passes, self.outline)
+    }
+
+    /// Prepares given game objects to be drawn (with the demo scene, if enabled).
+    fn scene(
+        &self,
+        mut instances: Vec<RenderInstance>,
+        viewport_passes: Vec<ViewportPass>,
+        outline: Option<Outline>,
+    ) -> Result<SceneDraw, RenderError> {
+        let viewport_passes = viewport_passes
+            .into_iter()
+            .map(|pass| {
+                let camera_ubo = pass
+                    .camera_ubo
+                    .pre_rotated(self.surface_transform)
+                    .with_light(&self.light);
+                let uniform_buffer = Arc::new(self.viewport_uniform_pool.next(camera_ubo)?);
+                Ok((
+                    self::pre_rotated_region(pass.region, self.surface_transform),
+                    pass.depth_range,
+                    uniform_buffer,
+                    pass.instances,
+                ))
+            })
+            .collect::<Result<Vec<_>, RenderError>>()?;
+        if self.demo_scene && self.draw_objects {
+            instances.push(RenderInstance::default());
+        }
+        Ok(SceneDraw {
+            instances,
+            viewport_passes,
+            outline,
+        })
+    }
+
+    /// Reuses allocation of submitted instances for the next frame.
+    fn recycle_scene(&mut self, scene: SceneDraw) {
+        let mut instances = scene.instances;
+        instances.clear();
+        self.instances = instances;
+    }
+
+    /// Acquires next image, records the frame with given drawing closure,
+    /// then submits and presents it once.
+    ///
+    /// The closure could handle passes of the frame, but must not finish it:
+    /// remaining passes are finished after the closure returns.
+    ///
+    pub fn present_frame<F>(&mut self, draw: F) -> Result<(), RenderError>
+    where
+        F: FnOnce(&mut Frame) -> Result<(), RenderError>,
+    {
+        self.present_frame_with(|frame, _| draw(frame))
+    }
+
+    /// Does the acquire/record/submit/present cycle once,
+    /// providing draw systems of the renderer to the drawing closure.
+    fn present_frame_with<F>(&mut self, draw: F) -> Result<(), RenderError>
+    where
+        F: FnOnce(&mut Frame, DrawContext) -> Result<(), RenderError>,
+    {
+        let _trace = trace::frame();
+        self.wait_frame_resources()?;
+
         if self.recreate_swapchain {
             self.resize()?;
         }
 
-        let (image_index, suboptimal, acquire_future) =
-            match swapchain::acquire_next_image(self.swapchain.clone(), None) {
-                Ok(r) => r,
-                Err(AcquireError::OutOfDate) => {
-                    self.recreate_swapchain = true;
-                    return Ok(());
-                }
-                Err(err) => return Err(RenderError::AcquireNextImage(err)),
-            };
+        if let Some(image) = self.headless_image.clone() {
+            return self.render_headless_frame(image, draw);
+        }
+
+        let swapchain = self
+            .swapchain
+            .clone()
+            .ok_or(RenderError::SwapchainRetired)?;
+        let acquire_start = Instant::now();
+        let acquire_result = swapchain::acquire_next_image(swapchain.clone(), None);
+        self.timing.present_wait = acquire_start.elapsed();
+        let (image_index, suboptimal, acquire_future) = match acquire_result {
+            Ok(r) => r,
+            Err(AcquireError::OutOfDate) => {
+                self.recreate_swapchain = true;
+                return Ok(());
+            }
+            Err(err) => return Err(RenderError::AcquireNextImage(err)),
+        };
         self.recreate_swapchain = suboptimal;
 
-        let transfer_command_buffer = self.transfer_cb(image_index)?;
+        // Frame dependencies are the following:
+        // previous frame end & image acquire -> transfer -> graphics -> present -> fence.
         let previous_frame_end = self.previous_frame_end.take().unwrap();
-        let before_future = previous_frame_end
-            .join(acquire_future)
-            .then_execute(self.transfer_queue.clone(), transfer_command_buffer)?
-            .then_signal_semaphore();
+        let image_acquired = previous_frame_end.join(acquire_future);
+        let image = self.swapchain_images[image_index].clone();
+        let (graphics_future, capture) = self.record_captured_frame(image_acquired, image, draw)?;
 
-        let scale_factor = self.window().scale_factor() as f32;
-        let graphics_future = {
-            let mut frame = self
-                .frame_system
-                .frame(before_future, self.swapchain_images[image_index].clone())?;
-            let mut graphics_future = Box::new(sync::now(self.device.clone())) as Box<_>;
-            while let Some(next_pass) = frame.next_pass()? {
-                match next_pass {
-                    Pass::Deferred(mut draw_pass) => {
-                        let uniform_buffer = self.uniform_buffers[image_index].clone();
-                        let command_buffer = self
-                            .object_draw_system
-                            .draw(draw_pass.viewport_size(), uniform_buffer)?;
-                        draw_pass.execute(command_buffer)?;
-                    }
-                    Pass::UI(mut ui_pass) => {
-                        if let Some((meshes, texture)) = ui.take() {
-                            let command_buffer = self.ui_draw_system.draw(
-                                ui_pass.viewport_size(),
-                                scale_factor,
-                                meshes,
-                                texture,
-                            )?;
-                            ui_pass.execute(command_buffer)?;
-                        }
-                    }
-                    Pass::Finished(future) => {
-                        graphics_future = future;
-                    }
+        // Presentation waits on this semaphore, so the image is rendered before presenting.
+        let rendering_completed = graphics_future.then_signal_semaphore();
+        let presented = Box::new(rendering_completed.then_swapchain_present(
+            self.present_queue.clone(),
+            swapchain,
+            image_index,
+        )) as Box<dyn GpuFuture + Send + Sync>;
+        let future = presented.then_signal_fence_and_flush();
+        self.timing.cpu_frame = acquire_start.elapsed();
+        self.finish_frame(future, capture)
+    }
+
+    /// Records the frame into the image of headless renderer and submits it once.
+    ///
+    /// There is nothing to acquire or present, so the frame only waits for the previous one.
+    ///
+    fn render_headless_frame<F>(
+        &mut self,
+        image: Arc<AttachmentImage>,
+        draw: F,
+    ) -> Result<(), RenderError>
+    where
+        F: FnOnce(&mut Frame, DrawContext) -> Result<(), RenderError>,
+    {
+        let frame_start = Instant::now();
+        self.timing.present_wait = Duration::ZERO;
+        let previous_frame_end = self.previous_frame_end.take().unwrap();
+        let (graphics_future, capture) =
+            self.record_captured_frame(previous_frame_end, image, draw)?;
+        let future = graphics_future.then_signal_fence_and_flush();
+        self.timing.cpu_frame = frame_start.elapsed();
+        self.finish_frame(future, capture)
+    }
+
+    /// Records the frame into given image after given future,
+    /// copying the image into the host-visible buffer if the capture was requested.
+    fn record_captured_frame<B, I, F>(
+        &mut self,
+        before_future: B,
+        image: Arc<I>,
+        draw: F,
+    ) -> Result<(Box<dyn GpuFuture + Send + Sync>, Option<Capture>), RenderError>
+    where
+        B: GpuFuture + Send + Sync + 'static,
+        I: ImageAccess + Send + Sync + 'static,
+        F: FnOnce(&mut Frame, DrawContext) -> Result<(), RenderError>,
+    {
+        let mut capture = None;
+        let recorded = self
+            .record_frame(before_future, image.clone(), draw)
+            .and_then(|graphics_future| match self.capture.take() {
+                Some(path) => {
+                    let extent = image.dimensions().width_height();
+                    let (command_buffer, buffer) = self.capture_cb(image)?;
+                    capture = Some(Capture {
+                        path,
+                        extent,
+                        buffer,
+                    });
+                    let future = graphics_future
+                        .then_execute(self.graphics_queue.clone(), command_buffer)?;
+                    Ok(Box::new(future) as Box<dyn GpuFuture + Send + Sync>)
                 }
+                None => Ok(graphics_future),
+            });
+        match recorded {
+            Ok(graphics_future) => Ok((graphics_future, capture)),
+            Err(error) => {
+                // End of previous frame was consumed, so the next frame waits for nothing.
+                self.previous_frame_end = Some(Box::new(sync::now(self.device.clone())));
+                Err(error)
             }
-            graphics_future
-        };
+        }
+    }
 
-        let future = graphics_future
-            .then_swapchain_present(
-                self.present_queue.clone(),
-                self.swapchain.clone(),
-                image_index,
-            )
-            .then_signal_fence_and_flush();
+    /// Stores the fence of submitted frame into per-frame resources
+    /// and saves the capture of the frame, if any.
+    fn finish_frame(
+        &mut self,
+        future: Result<FenceSignalFuture<Box<dyn GpuFuture + Send + Sync>>, FlushError>,
+        capture: Option<Capture>,
+    ) -> Result<(), RenderError> {
+        // Per-frame resources are indexed by frame in flight rather than by swapchain image.
+        let frame_index = self.frame_index;
+        self.frame_index = (self.frame_index + 1) % self.uniform_buffers.len();
         match future {
             Ok(future) => {
-                self.previous_frame_end = Some(Box::new(future));
+                let future = Arc::new(future);
+                self.fences[frame_index] = Some(future.clone());
+                self.previous_frame_end = Some(Box::new(future.clone()));
+                if let Some(capture) = capture {
+                    future.wait(None).map_err(RenderError::FenceWait)?;
+                    self.save_capture(&capture)?;
+                }
                 Ok(())
             }
             Err(FlushError::OutOfDate) => {
@@ -388,4 +1968,309 @@ pub fn render(
             }
         }
     }
+
+    /// Cleans up finished resources and waits for the frame
+    /// which used the same per-frame resources to be completed.
+    fn wait_frame_resources(&mut self) -> Result<(), RenderError> {
+        let cleanup_start = Instant::now();
+        self.previous_frame_end.as_mut().unwrap().cleanup_finished();
+        self.timing.cleanup = cleanup_start.elapsed();
+
+        let wait_start = Instant::now();
+        // In low latency mode the previous frame must be completed too,
+        // so CPU does not run ahead of GPU.
+        if self.low_latency {
+            let frames = self.fences.len();
+            let previous_index = (self.frame_index + frames - 1) % frames;
+            if let Some(fence) = &self.fences[previous_index] {
+                fence.wait(None).map_err(RenderError::FenceWait)?;
+            }
+        }
+        if let Some(fence) = self.fences[self.frame_index].take() {
+            fence.wait(None).map_err(RenderError::FenceWait)?;
+            if let Some(gpu_busy) = self
+                .gpu_timer
+                .as_ref()
+                .and_then(|timer| timer.elapsed(self.frame_index))
+            {
+                self.timing.gpu_busy = gpu_busy;
+            }
+        }
+        self.timing.cpu_wait = wait_start.elapsed();
+        Ok(())
+    }
+
+    /// Creates command buffer which copies given image
+    /// into the new host-visible buffer.
+    fn capture_cb<I>(
+        &self,
+        image: Arc<I>,
+    ) -> Result<(PrimaryAutoCommandBuffer, Arc<CpuAccessibleBuffer<[u8]>>), CaptureError>
+    where
+        I: ImageAccess + Send + Sync + 'static,
+    {
+        let [width, height] = image.dimensions().width_height();
+        let buffer = CpuAccessibleBuffer::from_iter(
+            self.device.clone(),
+            BufferUsage::transfer_destination(),
+            true,
+            iter::repeat(0u8).take(width as usize * height as usize * 4),
+        )?;
+        trace::created(Resource::Buffer);
+        let mut builder = AutoCommandBufferBuilder::primary(
+            self.device.clone(),
+            self.graphics_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+        builder.copy_image_to_buffer(image, buffer.clone())?;
+        let command_buffer = builder.build()?;
+        Ok((command_buffer, buffer))
+    }
+
+    /// Saves captured image of the frame as PNG image.
+    fn save_capture(&self, capture: &Capture) -> Result<(), CaptureError> {
+        let Capture {
+            path,
+            extent: [width, height],
+            buffer,
+        } = capture;
+        let format = self.frame_system.final_output_format();
+        let swap_blue_red =
+            readback_swaps_blue_red(format).ok_or(CaptureError::UnsupportedFormat(format))?;
+        let pixels = buffer.read()?;
+        let image = readback_image(*width, *height, &pixels, swap_blue_red);
+        image.save_with_format(path, image::ImageFormat::Png)?;
+        log::info!("frame was captured into {}", path.display());
+        Ok(())
+    }
+
+    /// Records the frame into given image after given future,
+    /// executing uniform buffer transfer first.
+    ///
+    /// Returned future is the end of graphics work of the frame.
+    fn record_frame<B, I, F>(
+        &mut self,
+        before_future: B,
+        image: Arc<I>,
+        draw: F,
+    ) -> Result<Box<dyn GpuFuture + Send + Sync>, RenderError>
+    where
+        B: GpuFuture + Send + Sync + 'static,
+        I: ImageAccess + Send + Sync + 'static,
+        F: FnOnce(&mut Frame, DrawContext) -> Result<(), RenderError>,
+    {
+        // Graphics submission waits on this semaphore, so uniform buffer transfer
+        // is completed before object draw system reads it.
+        let transfer_command_buffer = self.transfer_cb()?;
+        let transfer_completed = before_future
+            .then_execute(self.transfer_queue.clone(), transfer_command_buffer)?
+            .then_signal_semaphore();
+
+        let timestamp_queries = self
+            .gpu_timer
+            .as_ref()
+            .map(|timer| timer.queries(self.frame_index));
+        let mut frame = self.frame_system.frame(
+            transfer_completed,
+            image,
+            self.output_encoding.clear_color(self.clear_color),
+            timestamp_queries,
+        )?;
+        let context = DrawContext {
+            object_draw_system: &mut self.object_draw_system,
+            ui_draw_system: &mut self.ui_draw_system,
+            uniform_buffer: self.uniform_buffers[self.frame_index].clone(),
+            thread_pool: &self.thread_pool,
+        };
+        draw(&mut frame, context)?;
+
+        let mut graphics_future = None;
+        while let Some(next_pass) = frame.next_pass()? {
+            if let Pass::Finished(future) = next_pass {
+                graphics_future = Some(future);
+            }
+        }
+        graphics_future.ok_or(RenderError::FrameFinishedByDraw)
+    }
+}
+
+/// Checks if image of given format can be read back as 8-bit RGBA image.
+///
+/// Returns `Some(true)` if blue and red channels must be swapped, or `None` if not supported.
+fn readback_swaps_blue_red(format: Format) -> Option<bool> {
+    match format {
+        Format::R8G8B8A8_UNORM | Format::R8G8B8A8_SRGB => Some(false),
+        Format::B8G8R8A8_UNORM | Format::B8G8R8A8_SRGB => Some(true),
+        _ => None,
+    }
+}
+
+/// Converts pixels read back from the image into RGBA image.
+///
+/// Buffer image copy is tightly packed, so rows of pixels have no padding.
+fn readback_image(width: u32, height: u32, pixels: &[u8], swap_blue_red: bool) -> RgbaImage {
+    let mut pixels = pixels.to_vec();
+    if swap_blue_red {
+        pixels
+            .chunks_exact_mut(4)
+            .for_each(|pixel| pixel.swap(0, 2));
+    }
+    RgbaImage::from_raw(width, height, pixels).expect("buffer size should match size of the image")
+}
+
+/// Image registered for UI which is kept to be uploaded again if the device is lost.
+struct UiImage {
+    image: RgbaImage,
+    srgb: bool,
+    mipmaps: bool,
+    sampler: SamplerDesc,
+}
+
+/// Resources of the renderer which are provided for drawing of the frame.
+struct DrawContext<'a> {
+    object_draw_system: &'a mut ObjectDrawSystem,
+    ui_draw_system: &'a mut UiDrawSystem,
+    uniform_buffer: Arc<DeviceLocalBuffer<CameraUBO>>,
+    thread_pool: &'a ThreadPool,
+}
+
+/// Game objects submitted for the frame.
+struct SceneDraw {
+    instances: Vec<RenderInstance>,
+    viewport_passes: Vec<(
+        Rect,
+        Range<f32>,
+        Arc<CpuBufferPoolSubbuffer<CameraUBO, Arc<StdMemoryPool>>>,
+        Vec<RenderInstance>,
+    )>,
+    outline: Option<Outline>,
+}
+
+impl SceneDraw {
+    /// Draws game objects and UI (if any) into the frame, leaving it unfinished.
+    fn draw(
+        &self,
+        frame: &mut Frame,
+        context: DrawContext,
+        mut ui: Option<(Vec<ClippedMesh>, Arc<Texture>)>,
+        scale_factor: f32,
+    ) -> Result<(), RenderError> {
+        while let Some(next_pass) = frame.next_pass()? {
+            match next_pass {
+                Pass::Deferred(_)
+                    if self.instances.is_empty() && self.viewport_passes.is_empty() => {}
+                Pass::Deferred(mut draw_pass) => {
+                    let viewport_size = draw_pass.viewport_size();
+                    if !self.instances.is_empty() {
+                        let command_buffers = context.object_draw_system.draw(
+                            viewport_size,
+                            Rect::FULL,
+                            0.0..1.0,
+                            context.uniform_buffer.clone(),
+                            &self.instances,
+                            self.outline,
+                            context.thread_pool,
+                        )?;
+                        for command_buffer in command_buffers {
+                            draw_pass.execute(command_buffer)?;
+                        }
+                    }
+                    for (region, depth_range, uniform_buffer, instances) in &self.viewport_passes {
+                        let command_buffers = context.object_draw_system.draw(
+                            viewport_size,
+                            *region,
+                            depth_range.clone(),
+                            uniform_buffer.clone(),
+                            instances,
+                            self.outline,
+                            context.thread_pool,
+                        )?;
+                        for command_buffer in command_buffers {
+                            draw_pass.execute(command_buffer)?;
+                        }
+                    }
+                }
+                Pass::UI(mut ui_pass) => {
+                    if let Some((meshes, texture)) = ui.take() {
+                        let command_buffer = context.ui_draw_system.draw(
+                            ui_pass.viewport_size(),
+                            scale_factor,
+                            meshes,
+                            texture,
+                        )?;
+                        ui_pass.execute(command_buffer)?;
+                    }
+                    // UI pass is the last one, the frame is finished by the caller.
+                    break;
+                }
+                Pass::Finished(_) => unreachable!("frame is finished after UI pass"),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Renderer {
+    fn drop(&mut self) {
+        // Lost device will never finish its work, so there is nothing to wait for.
+        if self.device_lost {
+            return;
+        }
+        // GPU resources must not be freed while they are still in use.
+        if let Err(error) = self.wait_idle() {
+            log::error!("waiting for GPU to finish its work failed: {}", error);
+        }
+    }
+}
+
+/// Frame capture requested by the user, waiting to be saved.
+struct Capture {
+    path: PathBuf,
+    extent: [u32; 2],
+    buffer: Arc<CpuAccessibleBuffer<[u8]>>,
+}
+
+/// Output which frames of the renderer are rendered into.
+#[derive(Clone)]
+enum Output {
+    /// Swapchain of the window surface: frames are presented into the window.
+    Window(Arc<Surface<Window>>),
+    /// Image of given size without window (headless rendering).
+    Image { width: u32, height: u32 },
+}
+
+impl Output {
+    /// Surface of the window, or `None` if rendering is headless.
+    fn surface(&self) -> Option<&Arc<Surface<Window>>> {
+        match self {
+            Self::Window(surface) => Some(surface),
+            Self::Image { .. } => None,
+        }
+    }
+}
+
+/// Instances of game objects submitted to be rendered into the region of the window.
+#[derive(Clone)]
+struct ViewportPass {
+    region: Rect,
+    depth_range: Range<f32>,
+    camera_ubo: CameraUBO,
+    instances: Vec<RenderInstance>,
+}
+
+/// Applies pre-rotation of the surface (on mobile devices) to the region of the window,
+/// so it covers the same part of the rendered image as the pre-rotated projection.
+fn pre_rotated_region(region: Rect, transform: SurfaceTransform) -> Rect {
+    let Rect {
+        x,
+        y,
+        width,
+        height,
+    } = region;
+    match transform {
+        SurfaceTransform::Rotate90 => Rect::new(1.0 - y - height, x, height, width),
+        SurfaceTransform::Rotate180 => Rect::new(1.0 - x - width, 1.0 - y - height, width, height),
+        SurfaceTransform::Rotate270 => Rect::new(y, 1.0 - x - width, height, width),
+        _ => region,
+    }
 }
@@ -0,0 +1,46 @@
+#![cfg(test)]
+
+use super::*;
+
+/// Creates headless renderer of given size,
+/// or returns `None` if there is no suitable Vulkan device on this system.
+fn headless_renderer(width: u32, height: u32) -> Option<Renderer> {
+    match Renderer::new_headless(&Config::default(), width, height) {
+        Ok(renderer) => Some(renderer),
+        Err(
+            RendererCreationError::VulkanUnavailable(_)
+            | RendererCreationError::NoSuitablePhysicalDevice,
+        ) => None,
+        Err(error) => panic!("headless renderer creation failure: {}", error),
+    }
+}
+
+#[test]
+fn test_headless_frame() {
+    let mut renderer = match headless_renderer(64, 32) {
+        Some(renderer) => renderer,
+        None => return,
+    };
+    assert!(renderer.window().is_none());
+    renderer.resize().unwrap();
+
+    let path = std::env::temp_dir().join(format!(
+        "titan_test_headless_frame_{}.png",
+        std::process::id()
+    ));
+    renderer.capture_frame(&path).unwrap();
+    renderer.present_frame(|_| Ok(())).unwrap();
+    let captured = image::open(&path).unwrap().to_rgba8();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(captured.dimensions(), (64, 32));
+}
+
+#[test]
+fn test_headless_render_to_image() {
+    let mut renderer = match headless_renderer(64, 32) {
+        Some(renderer) => renderer,
+        None => return,
+    };
+    let image = renderer.render_to_image(16, 8).unwrap();
+    assert_eq!(image.dimensions(), (16, 8));
+}
@@ -0,0 +1,118 @@
+//! Sampler utilities for game engine.
+
+use std::sync::Arc;
+
+use vulkano::device::Device;
+use vulkano::sampler::{Sampler, SamplerCreationError};
+
+pub use vulkano::sampler::{Filter, MipmapMode, SamplerAddressMode};
+
+/// Description of the sampler which is used as a key of [`SamplerCache`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SamplerDesc {
+    /// Filter used when texture is magnified.
+    pub mag_filter: Filter,
+    /// Filter used when texture is minified.
+    pub min_filter: Filter,
+    /// Filter used between mipmap levels.
+    pub mipmap_mode: MipmapMode,
+    /// Address mode used for all texture coordinates.
+    pub address_mode: SamplerAddressMode,
+    /// Max anisotropy level, `1.0` means that anisotropic filtering is disabled.
+    pub max_anisotropy: f32,
+    /// Max level of detail (mipmap level) which can be used by the sampler.
+    pub max_lod: f32,
+}
+
+impl SamplerDesc {
+    /// Linear filtering without mipmaps and anisotropy, clamped to edge.
+    pub const fn linear() -> Self {
+        Self {
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Linear,
+            mipmap_mode: MipmapMode::Linear,
+            address_mode: SamplerAddressMode::ClampToEdge,
+            max_anisotropy: 1.0,
+            max_lod: 0.0,
+        }
+    }
+
+    /// Nearest filtering without mipmaps and anisotropy, clamped to edge.
+    pub const fn nearest() -> Self {
+        Self {
+            mag_filter: Filter::Nearest,
+            min_filter: Filter::Nearest,
+            mipmap_mode: MipmapMode::Nearest,
+            ..Self::linear()
+        }
+    }
+
+    /// Trilinear filtering with given max anisotropy level, repeated by address.
+    pub const fn anisotropic(max_anisotropy: f32, max_lod: f32) -> Self {
+        Self {
+            address_mode: SamplerAddressMode::Repeat,
+            max_anisotropy,
+            max_lod,
+            ..Self::linear()
+        }
+    }
+}
+
+impl Default for SamplerDesc {
+    fn default() -> Self {
+        Self::linear()
+    }
+}
+
+/// Cache of samplers which allows to share the same sampler object between resources.
+pub struct SamplerCache {
+    device: Arc<Device>,
+    samplers: Vec<(SamplerDesc, Arc<Sampler>)>,
+}
+
+impl SamplerCache {
+    /// Creates an empty sampler cache for given device.
+    pub fn new(device: Arc<Device>) -> Self {
+        Self {
+            device,
+            samplers: Vec::new(),
+        }
+    }
+
+    /// Retrieves sampler for given description, creating it if needed.
+    ///
+    /// Anisotropy level is clamped to device limits and is ignored
+    /// if `sampler_anisotropy` feature is not enabled on the device.
+    ///
+    pub fn get(&mut self, desc: SamplerDesc) -> Result<Arc<Sampler>, SamplerCreationError> {
+        if let Some((_, sampler)) = self.samplers.iter().find(|(key, _)| *key == desc) {
+            return Ok(sampler.clone());
+        }
+
+        let max_anisotropy = if self.device.enabled_features().sampler_anisotropy {
+            let limit = self
+                .device
+                .physical_device()
+                .properties()
+                .max_sampler_anisotropy;
+            desc.max_anisotropy.clamp(1.0, limit)
+        } else {
+            1.0
+        };
+        let sampler = Sampler::new(
+            self.device.clone(),
+            desc.mag_filter,
+            desc.min_filter,
+            desc.mipmap_mode,
+            desc.address_mode,
+            desc.address_mode,
+            desc.address_mode,
+            0.0,
+            max_anisotropy,
+            0.0,
+            desc.max_lod,
+        )?;
+        self.samplers.push((desc, sampler.clone()));
+        Ok(sampler)
+    }
+}
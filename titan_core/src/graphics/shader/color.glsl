@@ -0,0 +1,52 @@
+// Color space utilities which are shared between fragment shaders.
+
+// How output color is encoded, must match format and color space of the render target:
+// 0 - render target encodes linear color by itself (sRGB formats),
+// 1 - shader encodes linear color with sRGB transfer function (UNORM formats),
+// 2 - shader encodes linear color with perceptual quantizer (HDR10 color space).
+layout(constant_id = 0) const int output_encoding = 0;
+
+// Luminance of SDR white in nits which linear color of `1.0` is mapped to in HDR.
+const float SDR_WHITE_NITS = 203.0;
+
+// Converts color from BT.709 primaries (used by sRGB) into BT.2020 primaries (used by HDR10).
+const mat3 BT709_TO_BT2020 = mat3(
+    0.6274, 0.0691, 0.0164,
+    0.3293, 0.9195, 0.0880,
+    0.0433, 0.0114, 0.8956
+);
+
+vec3 srgbToLinear(vec3 color) {
+    vec3 low = color / 12.92;
+    vec3 high = pow((color + 0.055) / 1.055, vec3(2.4));
+    return mix(low, high, step(0.04045, color));
+}
+
+vec3 linearToSrgb(vec3 color) {
+    vec3 low = color * 12.92;
+    vec3 high = 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055;
+    return mix(low, high, step(0.0031308, color));
+}
+
+vec3 linearToPq(vec3 color) {
+    const float m1 = 0.1593017578125;
+    const float m2 = 78.84375;
+    const float c1 = 0.8359375;
+    const float c2 = 18.8515625;
+    const float c3 = 18.6875;
+
+    vec3 luminance = max(BT709_TO_BT2020 * color, 0.0) * (SDR_WHITE_NITS / 10000.0);
+    vec3 power = pow(luminance, vec3(m1));
+    return pow((c1 + c2 * power) / (1.0 + c3 * power), vec3(m2));
+}
+
+// Encodes linear color for the render target.
+vec3 encodeOutput(vec3 color) {
+    if (output_encoding == 1) {
+        return linearToSrgb(clamp(color, 0.0, 1.0));
+    }
+    if (output_encoding == 2) {
+        return linearToPq(color);
+    }
+    return color;
+}
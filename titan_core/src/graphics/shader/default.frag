@@ -1,9 +1,59 @@
 #version 450
 
+#include "color.glsl"
+
+layout(set = 0, binding = 0) uniform CameraUBO {
+    mat4 projection;
+    mat4 view;
+    vec4 lightDirection;
+    vec4 lightColor;
+    vec4 ambientColor;
+} ubo;
+
+layout(set = 1, binding = 0) uniform MaterialUBO {
+    vec4 color;
+    vec4 emissive;
+    float metallic;
+    float roughness;
+} material;
+
+// Texture is white if game objects are not textured, so only vertex color is used.
+layout(set = 1, binding = 1) uniform sampler2D baseTexture;
+
+// Linear color which is multiplied with color of the object (white means no tint).
+layout(push_constant) uniform PushConstants {
+    vec4 tint;
+} pushConstants;
+
 layout(location = 0) in vec4 color;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec3 viewDirection;
+layout(location = 3) in vec2 uv;
 
 layout(location = 0) out vec4 outColor;
 
 void main() {
-    outColor = color;
+    vec4 baseColor = color * material.color * texture(baseTexture, uv) * pushConstants.tint;
+    vec3 n = normalize(normal);
+    vec3 v = normalize(viewDirection);
+    // Direction towards the light source.
+    vec3 l = -normalize(ubo.lightDirection.xyz);
+    vec3 h = normalize(l + v);
+
+    // Blinn-Phong model driven by metallic-roughness parameters:
+    // metals have no diffuse term and tint their specular by the base color.
+    float roughness = clamp(material.roughness, 0.05, 1.0);
+    float shininess = 2.0 / (roughness * roughness * roughness * roughness) - 2.0;
+    vec3 specularColor = mix(vec3(0.04), baseColor.rgb, material.metallic);
+    vec3 diffuseColor = baseColor.rgb * (1.0 - material.metallic);
+
+    float diffuse = max(dot(n, l), 0.0);
+    float specular = diffuse > 0.0 ? pow(max(dot(n, h), 0.0), shininess) : 0.0;
+    // Normalization keeps energy of the highlight roughly constant for any roughness.
+    specular *= (shininess + 8.0) / 8.0;
+
+    vec3 lit = (diffuseColor * diffuse + specularColor * specular) * ubo.lightColor.rgb
+        + diffuseColor * ubo.ambientColor.rgb
+        + material.emissive.rgb;
+    outColor = vec4(encodeOutput(lit), baseColor.a);
 }
@@ -1,21 +1,39 @@
 #version 450
 
-layout(binding = 0) uniform CameraUBO {
+layout(set = 0, binding = 0) uniform CameraUBO {
     mat4 projection;
-    mat4 model;
     mat4 view;
+    vec4 lightDirection;
+    vec4 lightColor;
+    vec4 ambientColor;
 } ubo;
 
+layout(set = 0, binding = 1) readonly buffer Instances {
+    mat4 models[];
+} instances;
+
 layout(location = 0) in vec3 position;
-layout(location = 1) in vec4 color;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec4 color;
+layout(location = 3) in vec2 uv;
 
 layout(location = 0) out vec4 outColor;
+layout(location = 1) out vec3 outNormal;
+layout(location = 2) out vec3 outViewDirection;
+layout(location = 3) out vec2 outUV;
 
 out gl_PerVertex {
     vec4 gl_Position;
 };
 
 void main() {
-    gl_Position = ubo.projection * ubo.view * ubo.model * vec4(position, 1.0);
+    mat4 model = instances.models[gl_InstanceIndex];
+    vec4 worldPosition = model * vec4(position, 1.0);
+    vec3 cameraPosition = inverse(ubo.view)[3].xyz;
+
+    gl_Position = ubo.projection * ubo.view * worldPosition;
     outColor = color;
+    outNormal = mat3(transpose(inverse(model))) * normal;
+    outViewDirection = cameraPosition - worldPosition.xyz;
+    outUV = uv;
 }
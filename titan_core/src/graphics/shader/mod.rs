@@ -19,6 +19,45 @@ pub mod fragment {
     }
 }
 
+/// Shaders which are used in outline rendering.
+pub mod outline {
+    /// Outline vertex shader utilities.
+    pub mod vertex {
+        vulkano_shaders::shader! {
+            ty: "vertex",
+            path: "src/graphics/shader/outline.vert",
+        }
+    }
+
+    /// Outline fragment shader utilities.
+    pub mod fragment {
+        vulkano_shaders::shader! {
+            ty: "fragment",
+            path: "src/graphics/shader/outline.frag",
+        }
+    }
+}
+
+/// Shaders which are used to read depth of the rendered frame.
+pub mod depth_read {
+    /// Depth read compute shader utilities for single-sampled depth buffer.
+    pub mod single {
+        vulkano_shaders::shader! {
+            ty: "compute",
+            path: "src/graphics/shader/depth_read.comp",
+        }
+    }
+
+    /// Depth read compute shader utilities for multisampled depth buffer.
+    pub mod multisampled {
+        vulkano_shaders::shader! {
+            ty: "compute",
+            path: "src/graphics/shader/depth_read.comp",
+            define: [("MULTISAMPLED", "")],
+        }
+    }
+}
+
 /// Shaders which are used in UI rendering.
 pub mod ui {
     /// UI vertex shader utilities.
@@ -0,0 +1,15 @@
+#version 450
+
+#include "color.glsl"
+
+layout(push_constant) uniform PushConstants {
+    vec4 color; // in linear space
+    float thickness;
+} pushConstants;
+
+layout(location = 0) out vec4 outColor;
+
+void main() {
+    vec4 color = pushConstants.color;
+    outColor = vec4(encodeOutput(color.rgb), color.a);
+}
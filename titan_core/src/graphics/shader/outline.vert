@@ -0,0 +1,30 @@
+#version 450
+
+layout(set = 0, binding = 0) uniform CameraUBO {
+    mat4 projection;
+    mat4 view;
+    vec4 lightDirection;
+    vec4 lightColor;
+    vec4 ambientColor;
+} ubo;
+
+layout(set = 0, binding = 1) readonly buffer Instances {
+    mat4 models[];
+} instances;
+
+layout(push_constant) uniform PushConstants {
+    vec4 color;
+    float thickness;
+} pushConstants;
+
+layout(location = 0) in vec3 position;
+
+out gl_PerVertex {
+    vec4 gl_Position;
+};
+
+void main() {
+    vec3 scaled = position * (1.0 + pushConstants.thickness);
+    mat4 model = instances.models[gl_InstanceIndex];
+    gl_Position = ubo.projection * ubo.view * model * vec4(scaled, 1.0);
+}
@@ -1,5 +1,7 @@
 #version 450
 
+#include "color.glsl"
+
 layout(location = 0) in vec4 color;
 layout(location = 1) in vec2 uv;
 
@@ -8,5 +10,7 @@ layout(location = 0) out vec4 outColor;
 layout(binding = 0, set = 0) uniform sampler2D fontTexture;
 
 void main() {
-    outColor = color * texture(fontTexture, uv);
+    // Colors of UI are in sRGB space, while textures are sampled in linear space.
+    vec4 linearColor = vec4(srgbToLinear(color.rgb), color.a) * texture(fontTexture, uv);
+    outColor = vec4(encodeOutput(linearColor.rgb), linearColor.a);
 }
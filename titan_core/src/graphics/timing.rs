@@ -0,0 +1,91 @@
+//! Utilities for measuring timings of rendering.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use vulkano::device::Device;
+use vulkano::query::{QueryPool, QueryPoolCreationError, QueryResultFlags, QueryType};
+
+/// Timing statistics of the last rendered frame.
+///
+/// If `cpu_wait` is large compared to frame time, rendering is GPU-bound;
+/// otherwise it is CPU-bound.
+///
+#[derive(Debug, Default, Copy, Clone)]
+pub struct FrameTiming {
+    /// Time which CPU was blocked waiting on GPU to finish previous frame in flight.
+    pub cpu_wait: Duration,
+    /// Time which GPU was busy executing graphics commands of the frame.
+    ///
+    /// Always zero if device does not support timestamps.
+    pub gpu_busy: Duration,
+    /// Time which CPU was blocked waiting on presentation engine for the next image.
+    pub present_wait: Duration,
+    /// Time which CPU spent on the frame from acquiring the image until its submission,
+    /// including recording of command buffers.
+    pub cpu_frame: Duration,
+    /// Time which CPU spent cleaning up resources of already finished GPU work.
+    pub cleanup: Duration,
+}
+
+/// Timer which measures GPU execution time with timestamp queries.
+///
+/// Contains two queries (start and end) for each frame in flight.
+///
+pub struct GpuTimer {
+    query_pool: Arc<QueryPool>,
+    timestamp_period: f32,
+}
+
+impl GpuTimer {
+    /// Creates new GPU timer for given count of frames in flight.
+    ///
+    /// Returns `None` if device does not support timestamps on graphics queues.
+    ///
+    pub fn new(
+        device: &Arc<Device>,
+        frames_in_flight: usize,
+    ) -> Result<Option<Self>, QueryPoolCreationError> {
+        let properties = device.physical_device().properties();
+        if !properties.timestamp_compute_and_graphics {
+            return Ok(None);
+        }
+        let query_count = frames_in_flight as u32 * 2;
+        let query_pool = QueryPool::new(device.clone(), QueryType::Timestamp, query_count)?;
+        Ok(Some(Self {
+            query_pool,
+            timestamp_period: properties.timestamp_period,
+        }))
+    }
+
+    /// Query pool and index of the first query for given frame in flight.
+    pub fn queries(&self, frame_index: usize) -> (Arc<QueryPool>, u32) {
+        (self.query_pool.clone(), frame_index as u32 * 2)
+    }
+
+    /// Retrieves GPU execution time for given frame in flight without waiting.
+    ///
+    /// Returns `None` if results are not available (yet).
+    ///
+    pub fn elapsed(&self, frame_index: usize) -> Option<Duration> {
+        let first = frame_index as u32 * 2;
+        let range = self.query_pool.queries_range(first..first + 2)?;
+        let mut timestamps = [0u64; 2];
+        let flags = QueryResultFlags {
+            wait: false,
+            with_availability: false,
+            partial: false,
+        };
+        match range.get_results(&mut timestamps, flags) {
+            Ok(true) => (),
+            Ok(false) => return None,
+            Err(error) => {
+                log::warn!("failed to get timestamp query results: {}", error);
+                return None;
+            }
+        }
+        let ticks = timestamps[1].wrapping_sub(timestamps[0]);
+        let nanos = ticks as f64 * self.timestamp_period as f64;
+        Some(Duration::from_nanos(nanos as u64))
+    }
+}
@@ -0,0 +1,77 @@
+//! Tracing of GPU resource creation while frames are rendered.
+//!
+//! Resources should be created before rendering and reused between frames,
+//! so resources created while rendering could be the cause of frame time spikes.
+//! Tracing is available only in debug builds.
+//!
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static RENDERING: AtomicBool = AtomicBool::new(false);
+
+static BUFFERS: AtomicUsize = AtomicUsize::new(0);
+static IMAGES: AtomicUsize = AtomicUsize::new(0);
+static DESCRIPTOR_SETS: AtomicUsize = AtomicUsize::new(0);
+
+/// Kind of GPU resource which creation is traced.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Resource {
+    Buffer,
+    Image,
+    DescriptorSet,
+}
+
+/// Enables or disables tracing, which is always disabled in release builds.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled && cfg!(debug_assertions), Ordering::Relaxed);
+}
+
+/// Notifies that resource of given kind was created.
+pub fn created(resource: Resource) {
+    if !RENDERING.load(Ordering::Relaxed) {
+        return;
+    }
+    let counter = match resource {
+        Resource::Buffer => &BUFFERS,
+        Resource::Image => &IMAGES,
+        Resource::DescriptorSet => &DESCRIPTOR_SETS,
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Marks the start of frame rendering which ends when returned guard is dropped.
+pub fn frame() -> FrameTrace {
+    if ENABLED.load(Ordering::Relaxed) {
+        RENDERING.store(true, Ordering::Relaxed);
+    }
+    FrameTrace(())
+}
+
+/// Guard of frame rendering, which warns about resources created while rendering when dropped.
+#[must_use = "frame rendering ends when guard is dropped"]
+pub struct FrameTrace(());
+
+impl Drop for FrameTrace {
+    fn drop(&mut self) {
+        self::end_frame()
+    }
+}
+
+fn end_frame() {
+    if !RENDERING.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    let buffers = BUFFERS.swap(0, Ordering::Relaxed);
+    let images = IMAGES.swap(0, Ordering::Relaxed);
+    let descriptor_sets = DESCRIPTOR_SETS.swap(0, Ordering::Relaxed);
+    if buffers + images + descriptor_sets > 0 {
+        log::warn!(
+            "resources were created while rendering the frame: \
+            {} buffers, {} images, {} descriptor sets",
+            buffers,
+            images,
+            descriptor_sets,
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! Utilities for asynchronous upload of resources into GPU memory.
+
+use std::time::Duration;
+
+use vulkano::sync::{FenceSignalFuture, FlushError, GpuFuture};
+
+/// Ticket of resource upload which was submitted to GPU.
+///
+/// Resource handle can be retrieved by blocking with [`wait`](UploadTicket::wait)
+/// or by checking for completion with [`poll`](UploadTicket::poll).
+/// Resource must not be used until upload is completed.
+///
+#[must_use = "upload ticket must be waited or polled to retrieve the resource"]
+pub struct UploadTicket<T> {
+    future: FenceSignalFuture<Box<dyn GpuFuture + Send + Sync>>,
+    value: Option<T>,
+}
+
+impl<T> UploadTicket<T> {
+    /// Submits upload future to GPU and creates ticket for the resource handle.
+    pub fn new<F>(future: F, value: T) -> Result<Self, FlushError>
+    where
+        F: GpuFuture + Send + Sync + 'static,
+    {
+        let future = Box::new(future) as Box<dyn GpuFuture + Send + Sync>;
+        let future = future.then_signal_fence_and_flush()?;
+        Ok(Self {
+            future,
+            value: Some(value),
+        })
+    }
+
+    /// Blocks current thread until upload is completed and returns resource handle.
+    pub fn wait(mut self) -> Result<T, FlushError> {
+        self.future.wait(None)?;
+        Ok(self.value.take().unwrap())
+    }
+
+    /// Checks if upload is completed without blocking.
+    ///
+    /// Returns `None` if upload is still in progress
+    /// or resource handle was already retrieved by previous call.
+    ///
+    pub fn poll(&mut self) -> Option<Result<T, FlushError>> {
+        match self.future.wait(Some(Duration::ZERO)) {
+            Ok(()) => self.value.take().map(Ok),
+            Err(FlushError::Timeout) => None,
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
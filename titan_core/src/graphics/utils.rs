@@ -2,15 +2,17 @@
 
 use std::sync::Arc;
 
+use palette::Srgba;
 use vulkano::device::physical::{PhysicalDevice, PhysicalDeviceType, QueueFamily};
 use vulkano::device::{DeviceExtensions, Features};
 use vulkano::format::Format;
-use vulkano::instance::{ApplicationInfo, Instance, InstanceCreationError};
+use vulkano::image::{SampleCount, SampleCounts};
+use vulkano::instance::{ApplicationInfo, Instance, InstanceCreationError, InstanceExtensions};
 use vulkano::swapchain::{Capabilities, ColorSpace, Surface};
 use vulkano_win::required_extensions;
 use winit::window::Window;
 
-use crate::config::{Config, ENGINE_NAME, ENGINE_VERSION};
+use crate::config::{Config, DepthFormat, PreferredDevice, ENGINE_NAME, ENGINE_VERSION};
 
 /// Convert [`semver::Version`] Version struct into [`vulkano::Version`] struct.
 #[inline(always)]
@@ -39,6 +41,11 @@ pub fn create_instance(config: &Config) -> Result<Arc<Instance>, InstanceCreatio
         if config.enable_validation() {
             extensions.ext_debug_utils = true;
         }
+        // HDR color spaces of the surface are reported only with this extension.
+        if config.hdr() {
+            extensions.ext_swapchain_colorspace = InstanceExtensions::supported_by_core()
+                .map_or(false, |supported| supported.ext_swapchain_colorspace);
+        }
         extensions
     };
     let layers = config
@@ -54,19 +61,24 @@ pub struct SuitablePhysicalDevice<'a> {
     pub graphics_family: QueueFamily<'a>,
     pub present_family: Option<QueueFamily<'a>>,
     pub transfer_family: Option<QueueFamily<'a>>,
+    pub compute_family: Option<QueueFamily<'a>>,
 }
 
 /// Filter suitable physical device from all of them.
 ///
 /// Will check for provided extensions and features support.
+/// Preferred device is chosen if it is suitable, otherwise the device with the best score.
+///
+/// If surface is not provided (headless rendering), presentation support is not checked.
 ///
 pub fn suitable_physical_device<'a>(
     physical_devices: impl ExactSizeIterator<Item = PhysicalDevice<'a>>,
-    surface: &Arc<Surface<Window>>,
+    surface: Option<&Arc<Surface<Window>>>,
     required_extensions: &DeviceExtensions,
     required_features: &Features,
+    preferred_device: &PreferredDevice,
 ) -> Option<SuitablePhysicalDevice<'a>> {
-    physical_devices
+    let mut suitable_devices: Vec<_> = physical_devices
         .filter(|physical_device| {
             let extensions = physical_device.supported_extensions();
             let features = physical_device.supported_features();
@@ -77,12 +89,28 @@ pub fn suitable_physical_device<'a>(
             let graphics_family = physical_device
                 .queue_families()
                 .find(QueueFamily::supports_graphics);
-            let present_family = physical_device
-                .queue_families()
-                .find(|&queue| surface.is_supported(queue).unwrap_or(false));
+            let present_family = surface.and_then(|surface| {
+                physical_device
+                    .queue_families()
+                    .find(|&queue| surface.is_supported(queue).unwrap_or(false))
+            });
             let transfer_family = physical_device
                 .queue_families()
                 .find(QueueFamily::explicitly_supports_transfers);
+            // Prefer dedicated (async) compute queue family, or any compute capable one
+            // if graphics queue family does not support compute operations.
+            let compute_family = physical_device
+                .queue_families()
+                .find(|&queue| queue.supports_compute() && !queue.supports_graphics())
+                .or_else(|| {
+                    graphics_family
+                        .filter(|queue| !queue.supports_compute())
+                        .and_then(|_| {
+                            physical_device
+                                .queue_families()
+                                .find(QueueFamily::supports_compute)
+                        })
+                });
             match (graphics_family, present_family, transfer_family) {
                 (Some(graphics_family), Some(present_family), Some(transfer_family)) => {
                     Some(SuitablePhysicalDevice {
@@ -90,6 +118,7 @@ pub fn suitable_physical_device<'a>(
                         graphics_family,
                         present_family: Some(present_family),
                         transfer_family: Some(transfer_family),
+                        compute_family,
                     })
                 }
                 (Some(graphics_family), Some(present_family), None) => {
@@ -98,6 +127,16 @@ pub fn suitable_physical_device<'a>(
                         graphics_family,
                         present_family: Some(present_family),
                         transfer_family: None,
+                        compute_family,
+                    })
+                }
+                (Some(graphics_family), None, transfer_family) if surface.is_none() => {
+                    Some(SuitablePhysicalDevice {
+                        physical_device,
+                        graphics_family,
+                        present_family: None,
+                        transfer_family,
+                        compute_family,
                     })
                 }
                 (Some(graphics_family), None, None) => Some(SuitablePhysicalDevice {
@@ -105,10 +144,32 @@ pub fn suitable_physical_device<'a>(
                     graphics_family,
                     present_family: None,
                     transfer_family: None,
+                    compute_family,
                 }),
                 _ => None,
             }
         })
+        .collect();
+
+    let is_preferred = |suitable: &SuitablePhysicalDevice| {
+        let physical_device = &suitable.physical_device;
+        match preferred_device {
+            PreferredDevice::Auto => false,
+            PreferredDevice::Index(index) => physical_device.index() == *index,
+            PreferredDevice::Name(name) => &physical_device.properties().device_name == name,
+        }
+    };
+    if let Some(position) = suitable_devices.iter().position(is_preferred) {
+        return Some(suitable_devices.swap_remove(position));
+    }
+    if *preferred_device != PreferredDevice::Auto {
+        log::warn!(
+            "preferred device {:?} was not found or is not suitable, choosing automatically",
+            preferred_device,
+        );
+    }
+    suitable_devices
+        .into_iter()
         .max_by_key(|suitable| self::score(&suitable.physical_device))
 }
 
@@ -148,17 +209,180 @@ pub fn suitable_depth_stencil_format(physical_device: PhysicalDevice) -> Format
         .unwrap_or(&Format::D16_UNORM)
 }
 
-/// Image format which is suitable for rendering backend.
-pub const SUITABLE_IMAGE_FORMAT: (Format, ColorSpace) =
-    (Format::B8G8R8A8_SRGB, ColorSpace::SrgbNonLinear);
+impl From<DepthFormat> for Format {
+    fn from(depth_format: DepthFormat) -> Self {
+        match depth_format {
+            DepthFormat::D16Unorm => Format::D16_UNORM,
+            DepthFormat::D32Sfloat => Format::D32_SFLOAT,
+            DepthFormat::D16UnormS8Uint => Format::D16_UNORM_S8_UINT,
+            DepthFormat::D24UnormS8Uint => Format::D24_UNORM_S8_UINT,
+            DepthFormat::D32SfloatS8Uint => Format::D32_SFLOAT_S8_UINT,
+        }
+    }
+}
+
+impl TryFrom<Format> for DepthFormat {
+    type Error = Format;
+
+    /// Converts format back into depth format, returning it as is if it is not a depth format.
+    fn try_from(format: Format) -> Result<Self, Self::Error> {
+        match format {
+            Format::D16_UNORM => Ok(DepthFormat::D16Unorm),
+            Format::D32_SFLOAT => Ok(DepthFormat::D32Sfloat),
+            Format::D16_UNORM_S8_UINT => Ok(DepthFormat::D16UnormS8Uint),
+            Format::D24_UNORM_S8_UINT => Ok(DepthFormat::D24UnormS8Uint),
+            Format::D32_SFLOAT_S8_UINT => Ok(DepthFormat::D32SfloatS8Uint),
+            format => Err(format),
+        }
+    }
+}
+
+/// Retrieves depth stencil format preferred by config, if supported by physical device.
+///
+/// If preferred format is not supported or there is no preference,
+/// returns [`suitable_depth_stencil_format`].
+pub fn depth_stencil_format(physical_device: PhysicalDevice, config: &Config) -> Format {
+    if let Some(format) = config.depth_format() {
+        let format = Format::from(format);
+        let properties = format.properties(physical_device);
+        if properties.optimal_tiling_features.depth_stencil_attachment {
+            return format;
+        }
+        log::warn!(
+            "depth format {:?} is not supported by the device, falling back to automatic choice",
+            format,
+        );
+    }
+    self::suitable_depth_stencil_format(physical_device)
+}
+
+/// Retrieves sample count of multisample anti-aliasing preferred by config.
+///
+/// If preferred sample count is not supported by the device for both color and depth
+/// attachments, the highest supported sample count below it is returned.
+///
+pub fn msaa_sample_count(physical_device: PhysicalDevice, config: &Config) -> SampleCount {
+    let properties = physical_device.properties();
+    let color = properties.framebuffer_color_sample_counts;
+    let depth = properties.framebuffer_depth_sample_counts;
+    let supported = |counts: SampleCounts, samples: SampleCount| match samples {
+        SampleCount::Sample1 => counts.sample1,
+        SampleCount::Sample2 => counts.sample2,
+        SampleCount::Sample4 => counts.sample4,
+        SampleCount::Sample8 => counts.sample8,
+        _ => false,
+    };
+
+    let preferred = match config.msaa_samples() {
+        8 => SampleCount::Sample8,
+        4 => SampleCount::Sample4,
+        2 => SampleCount::Sample2,
+        _ => SampleCount::Sample1,
+    };
+    let candidates = [
+        SampleCount::Sample8,
+        SampleCount::Sample4,
+        SampleCount::Sample2,
+    ];
+    let samples = candidates
+        .into_iter()
+        .filter(|&samples| samples as u32 <= preferred as u32)
+        .find(|&samples| supported(color, samples) && supported(depth, samples))
+        .unwrap_or(SampleCount::Sample1);
+    if samples != preferred {
+        log::warn!(
+            "MSAA sample count {:?} is not supported by the device, using {:?}",
+            preferred,
+            samples,
+        );
+    }
+    samples
+}
+
+/// Image formats which are suitable for rendering backend, in order of preference.
+pub const SUITABLE_IMAGE_FORMATS: [(Format, ColorSpace); 4] = [
+    (Format::B8G8R8A8_SRGB, ColorSpace::SrgbNonLinear),
+    (Format::R8G8B8A8_SRGB, ColorSpace::SrgbNonLinear),
+    (Format::B8G8R8A8_UNORM, ColorSpace::SrgbNonLinear),
+    (Format::R8G8B8A8_UNORM, ColorSpace::SrgbNonLinear),
+];
 
-/// Retrieves suitable image format if supported by physical device.
+/// HDR image formats which are preferred over [`SUITABLE_IMAGE_FORMATS`] if HDR is enabled.
+pub const SUITABLE_HDR_IMAGE_FORMATS: [(Format, ColorSpace); 2] = [
+    (Format::A2B10G10R10_UNORM_PACK32, ColorSpace::Hdr10St2084),
+    (Format::A2R10G10B10_UNORM_PACK32, ColorSpace::Hdr10St2084),
+];
+
+/// Retrieves the first suitable image format which is supported by physical device.
 ///
+/// HDR formats are tried first only if `hdr` is `true`.
 /// If none of suitable image formats are supported, returns first supported format.
-pub fn suitable_image_format(capabilities: &Capabilities) -> (Format, ColorSpace) {
+///
+pub fn suitable_image_format(capabilities: &Capabilities, hdr: bool) -> (Format, ColorSpace) {
     let formats = &capabilities.supported_formats;
-    *formats
-        .iter()
-        .find(|&&format| SUITABLE_IMAGE_FORMAT == format)
-        .unwrap_or_else(|| &formats[0])
+    let hdr_formats = hdr
+        .then(|| SUITABLE_HDR_IMAGE_FORMATS)
+        .into_iter()
+        .flatten();
+    let suitable = hdr_formats
+        .chain(SUITABLE_IMAGE_FORMATS)
+        .find(|format| formats.contains(format));
+    if hdr && !suitable.map_or(false, |format| SUITABLE_HDR_IMAGE_FORMATS.contains(&format)) {
+        log::warn!("HDR is not supported by the surface, using SDR image format");
+    }
+    let (format, color_space) = suitable.unwrap_or_else(|| {
+        log::warn!("none of suitable image formats are supported, colors may be wrong");
+        formats[0]
+    });
+    log::info!(
+        "using image format {:?} with color space {:?}",
+        format,
+        color_space,
+    );
+    (format, color_space)
+}
+
+/// How shaders encode output color for the render target.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OutputEncoding {
+    /// Render target encodes linear color by itself (sRGB formats).
+    Hardware = 0,
+    /// Shader encodes linear color with sRGB transfer function (UNORM formats).
+    Srgb = 1,
+    /// Shader encodes linear color with perceptual quantizer (HDR10 color space).
+    Pq = 2,
+}
+
+impl OutputEncoding {
+    /// Chooses encoding of output color for given format and color space of the render target.
+    pub fn of(format: Format, color_space: ColorSpace) -> Self {
+        if color_space == ColorSpace::Hdr10St2084 {
+            return Self::Pq;
+        }
+        match format {
+            Format::B8G8R8A8_SRGB | Format::R8G8B8A8_SRGB | Format::A8B8G8R8_SRGB_PACK32 => {
+                Self::Hardware
+            }
+            _ => Self::Srgb,
+        }
+    }
+
+    /// Converts color into clear value of the render target, which is written as is
+    /// unless the render target encodes linear color by itself.
+    ///
+    /// For HDR10 color space sRGB encoded color is used as an approximation.
+    ///
+    pub fn clear_color(self, color: Srgba) -> [f32; 4] {
+        let (red, green, blue, alpha) = match self {
+            Self::Hardware => color.into_linear().into_components(),
+            Self::Srgb | Self::Pq => color.into_components(),
+        };
+        [red, green, blue, alpha]
+    }
+}
+
+impl Default for OutputEncoding {
+    fn default() -> Self {
+        Self::Hardware
+    }
 }
@@ -4,8 +4,43 @@
 
 use epaint::Rgba;
 use palette::Srgba;
+use thiserror::Error;
 use ultraviolet::{Vec2, Vec3};
-use vulkano::pipeline::vertex::{VertexMember, VertexMemberTy};
+use vulkano::pipeline::shader::GraphicsEntryPoint;
+use vulkano::pipeline::vertex::{
+    BuffersDefinition, IncompatibleVertexDefinitionError, Vertex as VulkanoVertex,
+    VertexDefinition, VertexMember, VertexMemberTy,
+};
+
+/// Error that can happen when vertex layout does not match vertex input of the shader.
+#[derive(Debug, Error)]
+#[error("vertex type `{vertex}` does not match vertex input of the shader: {source}")]
+pub struct VertexLayoutError {
+    /// Name of the vertex type.
+    pub vertex: &'static str,
+    /// Actual mismatch between vertex type and the shader.
+    pub source: IncompatibleVertexDefinitionError,
+}
+
+/// Validates that vertex type matches vertex input interface (locations and formats)
+/// of given vertex shader entry point.
+///
+/// Should be called before pipeline creation to get a descriptive error
+/// instead of a generic pipeline creation failure.
+///
+pub fn validate_vertex_input<V>(entry_point: &GraphicsEntryPoint) -> Result<(), VertexLayoutError>
+where
+    V: VulkanoVertex,
+{
+    BuffersDefinition::new()
+        .vertex::<V>()
+        .definition(entry_point.input_interface())
+        .map(|_| ())
+        .map_err(|source| VertexLayoutError {
+            vertex: std::any::type_name::<V>(),
+            source,
+        })
+}
 
 /// Wrapper for external 3-dimensional vector struct.
 #[derive(Default, Copy, Clone)]
@@ -103,17 +138,23 @@ fn format() -> (VertexMemberTy, usize) {
 pub struct Vertex {
     /// Vertex position in the world.
     pub position: Position3,
+    /// Normal vector of this vertex.
+    pub normal: Position3,
+    /// UV position on the texture.
+    pub uv: Position2,
     /// Color of this vertex.
     pub color: Color,
 }
 
-vulkano::impl_vertex!(Vertex, position, color);
+vulkano::impl_vertex!(Vertex, position, normal, uv, color);
 
 impl Vertex {
-    /// Creates new vertex with given position and color.
-    pub fn new(position: Vec3, color: Srgba) -> Self {
+    /// Creates new vertex with given position, normal, UV and color.
+    pub fn new(position: Vec3, normal: Vec3, uv: Vec2, color: Srgba) -> Self {
         Self {
             position: Position3(position),
+            normal: Position3(normal),
+            uv: Position2(uv),
             color: Color(color),
         }
     }
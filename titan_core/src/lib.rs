@@ -1,9 +1,21 @@
 //! API for simple game engine based on Rust and Vulkan API.
 
 pub use app::init;
+pub use graphics::camera;
+pub use graphics::compute;
+pub use graphics::error::{ErrorKind, RenderError};
+pub use graphics::light;
+pub use graphics::mesh;
+pub use graphics::{
+    DrawPass, Filter, Frame, FrameTiming, Material, MaterialHandle, MipmapMode, Pass,
+    RenderInstance, SamplerAddressMode, SamplerDesc,
+};
 
 pub mod app;
+#[cfg(feature = "audio")]
+pub mod audio;
 pub mod config;
+pub mod scene;
 pub mod window;
 
 mod graphics;
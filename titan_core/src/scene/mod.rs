@@ -0,0 +1,86 @@
+//! Utilities for scenes (levels) of your game.
+
+use egui::TextureId;
+use image::RgbaImage;
+use thiserror::Error;
+use titan_ecs::World;
+
+use crate::graphics::error::ImageRegisterError;
+
+/// Error that can happen when loading the scene.
+#[derive(Debug, Error)]
+pub enum SceneLoadError {
+    #[error(r#"scene "{0}" was not registered"#)]
+    NotRegistered(String),
+
+    #[error("failed to upload UI image of the scene: {0}")]
+    ImageRegister(#[from] ImageRegisterError),
+}
+
+/// Named group of entities and resources of your game (for example, a level).
+///
+/// GPU resources of the scene are uploaded when the scene is loaded
+/// and freed when another scene is loaded instead.
+///
+pub struct Scene {
+    name: String,
+    world: World,
+    ui_images: Vec<RgbaImage>,
+    ui_textures: Vec<TextureId>,
+}
+
+impl Scene {
+    /// Creates new empty scene with given name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            world: World::default(),
+            ui_images: Vec::new(),
+            ui_textures: Vec::new(),
+        }
+    }
+
+    /// Name of the scene.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Storage for entities and components of the scene.
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// Mutable storage for entities and components of the scene.
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// Adds image to be drawn in UI while this scene is loaded.
+    ///
+    /// Returns index of the image which could be used to retrieve its texture.
+    ///
+    pub fn add_ui_image(&mut self, image: RgbaImage) -> usize {
+        self.ui_images.push(image);
+        self.ui_images.len() - 1
+    }
+
+    /// Retrieves texture of the image with given index.
+    ///
+    /// Returns `None` if the scene is not loaded or there is no such image.
+    ///
+    pub fn ui_texture(&self, index: usize) -> Option<TextureId> {
+        self.ui_textures.get(index).copied()
+    }
+
+    pub(crate) fn ui_images(&self) -> &[RgbaImage] {
+        &self.ui_images
+    }
+
+    pub(crate) fn set_ui_textures(&mut self, textures: Vec<TextureId>) {
+        self.ui_textures = textures;
+    }
+
+    pub(crate) fn take_ui_textures(&mut self) -> Vec<TextureId> {
+        std::mem::take(&mut self.ui_textures)
+    }
+}
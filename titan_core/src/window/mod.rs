@@ -1,8 +1,18 @@
 //! Utilities for window handling of game engine.
 
+use std::cell::Cell;
+use std::rc::Rc;
+
 use egui::CtxRef;
+use winit::dpi::{LogicalSize, PhysicalSize};
+use winit::monitor::{MonitorHandle, VideoMode};
+use winit::window::{Fullscreen, Window};
+
+use crate::{app::DeltaTime, config::FullscreenMode};
 
-use crate::app::DeltaTime;
+#[cfg(feature = "gamepad")]
+pub use gilrs::{Axis as GamepadAxis, Button as GamepadButton, GamepadId};
+pub use winit::event::{ElementState, MouseButton, VirtualKeyCode};
 
 /// General event of game engine window.
 pub enum Event {
@@ -12,18 +22,161 @@ pub enum Event {
     /// Called when game window was resized.
     Resized(Size),
 
+    /// Called when swapchain was recreated with new size of its images.
+    ///
+    /// Unlike [`Resized`](Event::Resized), this event is delivered after the actual
+    /// recreation of render targets, so resources which depend on their size
+    /// should be recreated on this event.
+    ///
+    SwapchainRecreated(Size),
+
+    /// Called when graphics device was recreated after it was lost
+    /// (for example, because of driver crash or GPU reset).
+    ///
+    /// Materials (their handles must not be used anymore), texture and mesh of game objects,
+    /// custom object shaders, compute pipelines and samplers belong to the lost device,
+    /// so they should be created again on this event.
+    /// Images registered for UI are uploaded again by the engine.
+    ///
+    DeviceRecreated,
+
     /// Called when game window needs updating.
+    ///
+    /// All the input events of the frame are delivered before this event.
+    /// Systems of the application schedule are run on the active scene after this event.
+    /// If fixed time step is enabled (see [`Config::fixed_timestep`]),
+    /// this event is delivered zero or more times per frame with constant delta time.
+    ///
+    /// [`Config::fixed_timestep`]: crate::config::Config::fixed_timestep
+    ///
     Update(DeltaTime),
 
+    /// Called once per frame before rendering if fixed time step is enabled.
+    ///
+    /// Contains how far (in range `0.0..1.0`) the time of the frame is between
+    /// the previous and the next fixed update, so the state of the game
+    /// could be interpolated for smooth rendering.
+    ///
+    Render(f32),
+
+    /// Called when game window was switched into (`true`) or out of (`false`) fullscreen mode.
+    FullscreenChanged(bool),
+
+    /// Called when game window was maximized (`true`) or restored (`false`).
+    Maximized(bool),
+
+    /// Called when game window gained (`true`) or lost (`false`) keyboard focus
+    /// (useful for pausing the game).
+    Focused(bool),
+
+    /// Called when cursor entered (`true`) or left (`false`) game window.
+    CursorEntered(bool),
+
     /// Called when game UI needs updating.
     UI(CtxRef),
 
+    /// Called when character was received from the keyboard (useful for text input).
+    ///
+    /// Not delivered if the game UI wants keyboard input (for example, text field is focused),
+    /// so the same input is not handled twice.
+    TextInput(char),
+
+    /// Called when key on the keyboard was pressed or released.
+    ///
+    /// Not delivered if the game UI wants keyboard input.
+    Keyboard {
+        key: VirtualKeyCode,
+        state: ElementState,
+    },
+
+    /// Called when mouse button was pressed or released.
+    ///
+    /// Not delivered if the game UI wants pointer input (for example, cursor is over the window).
+    MouseButton {
+        button: MouseButton,
+        state: ElementState,
+    },
+
+    /// Called when cursor was moved to the new position (in physical pixels)
+    /// relative to the top left corner of the window.
+    ///
+    /// Not delivered if the game UI wants pointer input.
+    CursorMoved(f64, f64),
+
+    /// Called when gamepad was connected.
+    ///
+    /// Available only with `gamepad` feature enabled.
+    ///
+    #[cfg(feature = "gamepad")]
+    GamepadConnected(GamepadId),
+
+    /// Called when gamepad was disconnected.
+    ///
+    /// Available only with `gamepad` feature enabled.
+    ///
+    #[cfg(feature = "gamepad")]
+    GamepadDisconnected(GamepadId),
+
+    /// Called when button of the gamepad was pressed or released.
+    ///
+    /// Available only with `gamepad` feature enabled.
+    ///
+    #[cfg(feature = "gamepad")]
+    GamepadButton {
+        id: GamepadId,
+        button: GamepadButton,
+        state: ElementState,
+    },
+
+    /// Called when axis of the gamepad (stick or trigger) changed its value in range `-1.0..=1.0`.
+    ///
+    /// Available only with `gamepad` feature enabled.
+    ///
+    #[cfg(feature = "gamepad")]
+    GamepadAxis {
+        id: GamepadId,
+        axis: GamepadAxis,
+        value: f32,
+    },
+
+    /// Called when user requested to close game window (for example, with the close button).
+    ///
+    /// Application exits after this event unless the request was [cancelled](CloseRequest::cancel),
+    /// so the game could ask user to save their work before.
+    ///
+    CloseRequested(CloseRequest),
+
     /// Called when game window will be destroyed.
     Destroyed,
 }
 
-/// Size of game engine window.
-#[derive(Default, Copy, Clone)]
+/// Request to close game window which could be cancelled by the game.
+pub struct CloseRequest {
+    cancelled: Rc<Cell<bool>>,
+}
+
+impl CloseRequest {
+    pub(crate) fn new() -> (Self, Rc<Cell<bool>>) {
+        let cancelled = Rc::new(Cell::new(false));
+        let request = Self {
+            cancelled: cancelled.clone(),
+        };
+        (request, cancelled)
+    }
+
+    /// Cancels closing of game window, so application continues to run.
+    pub fn cancel(&self) {
+        self.cancelled.set(true);
+    }
+
+    /// Returns if closing of game window was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.get()
+    }
+}
+
+/// Size of game engine window in physical pixels.
+#[derive(Debug, Default, Copy, Clone)]
 pub struct Size {
     pub width: u32,
     pub height: u32,
@@ -34,6 +187,28 @@ impl Size {
     pub const fn new(width: u32, height: u32) -> Self {
         Self { width, height }
     }
+
+    /// Aspect ratio (width / height) of this size.
+    pub fn aspect_ratio(self) -> f32 {
+        self.width as f32 / self.height as f32
+    }
+
+    /// Converts this (physical) size into logical size with given scale factor of the window.
+    pub fn to_logical(self, scale_factor: f64) -> LogicalSize<f64> {
+        PhysicalSize::from(self).to_logical(scale_factor)
+    }
+}
+
+impl From<PhysicalSize<u32>> for Size {
+    fn from(size: PhysicalSize<u32>) -> Self {
+        Self::new(size.width, size.height)
+    }
+}
+
+impl From<Size> for PhysicalSize<u32> {
+    fn from(size: Size) -> Self {
+        PhysicalSize::new(size.width, size.height)
+    }
 }
 
 impl From<[u32; 2]> for Size {
@@ -59,3 +234,117 @@ fn from(size: Size) -> Self {
         (size.width, size.height)
     }
 }
+
+/// Rectangle region of game engine window, relative to its size.
+///
+/// All the values are fractions of the window size in range `0.0..=1.0`,
+/// where origin is the top left corner of the window.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    /// Rectangle which covers the whole window.
+    pub const FULL: Self = Self::new(0.0, 0.0, 1.0, 1.0);
+
+    /// Creates new rectangle region of window.
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+impl Default for Rect {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+/// Information about the monitor which is connected to the system.
+#[derive(Debug, Clone)]
+pub struct MonitorInfo {
+    /// Human-readable name of the monitor, if available.
+    pub name: Option<String>,
+    /// Position of the top left corner of the monitor on the desktop in physical pixels.
+    pub position: (i32, i32),
+    /// Size of the monitor in physical pixels.
+    pub size: Size,
+    /// Scale factor of the monitor.
+    pub scale_factor: f64,
+    /// Video modes which could be used for exclusive fullscreen on this monitor.
+    pub video_modes: Vec<VideoModeInfo>,
+}
+
+impl From<MonitorHandle> for MonitorInfo {
+    fn from(monitor: MonitorHandle) -> Self {
+        let position = monitor.position();
+        Self {
+            name: monitor.name(),
+            position: (position.x, position.y),
+            size: monitor.size().into(),
+            scale_factor: monitor.scale_factor(),
+            video_modes: monitor.video_modes().map(VideoModeInfo::from).collect(),
+        }
+    }
+}
+
+/// Video mode of the monitor which could be used for exclusive fullscreen.
+#[derive(Debug, Copy, Clone)]
+pub struct VideoModeInfo {
+    /// Resolution of the video mode in physical pixels.
+    pub size: Size,
+    /// Bit depth of the video mode.
+    pub bit_depth: u16,
+    /// Refresh rate of the video mode in hertz.
+    pub refresh_rate: u16,
+}
+
+impl From<VideoMode> for VideoModeInfo {
+    fn from(video_mode: VideoMode) -> Self {
+        Self {
+            size: video_mode.size().into(),
+            bit_depth: video_mode.bit_depth(),
+            refresh_rate: video_mode.refresh_rate(),
+        }
+    }
+}
+
+/// Resolves fullscreen mode of the window for monitors which are currently available.
+///
+/// Returns `None` if there is no monitor with given index.
+/// Exclusive mode falls back to borderless one if there is no video mode with given index.
+///
+pub(crate) fn fullscreen_of(window: &Window, mode: FullscreenMode) -> Option<Fullscreen> {
+    match mode {
+        FullscreenMode::Borderless { monitor } => {
+            let monitor = window.available_monitors().nth(monitor)?;
+            Some(Fullscreen::Borderless(Some(monitor)))
+        }
+        FullscreenMode::Exclusive {
+            monitor,
+            video_mode,
+        } => {
+            let monitor = window.available_monitors().nth(monitor)?;
+            match monitor.video_modes().nth(video_mode) {
+                Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+                None => {
+                    log::warn!(
+                        "video mode {} is not available on monitor {:?}, using borderless",
+                        video_mode,
+                        monitor.name(),
+                    );
+                    Some(Fullscreen::Borderless(Some(monitor)))
+                }
+            }
+        }
+    }
+}
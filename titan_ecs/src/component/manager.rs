@@ -2,14 +2,39 @@
 
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
+use std::iter;
 
 use super::{super::Entity, Component, ComponentStorage};
 
+/// Type-erased component storage, so components of any type could be detached from the entity.
+trait AnyStorage: Any + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn remove_entity(&mut self, entity: Entity);
+}
+
+impl<T> AnyStorage for ComponentStorage<T>
+where
+    T: Component,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn remove_entity(&mut self, entity: Entity) {
+        self.remove(entity);
+    }
+}
+
 /// Manager of all components of ECS.
 #[derive(Default)]
 #[repr(transparent)]
 pub struct ComponentManager {
-    _storages: HashMap<TypeId, Box<dyn Any>>,
+    _storages: HashMap<TypeId, Box<dyn AnyStorage>>,
 }
 
 impl ComponentManager {
@@ -48,6 +73,13 @@ pub fn remove<T>(&mut self, entity: Entity) -> Option<T>
         storage.remove(entity)
     }
 
+    /// Removes components of all types attached to the entity.
+    pub fn remove_all(&mut self, entity: Entity) {
+        for storage in self._storages.values_mut() {
+            storage.remove_entity(entity);
+        }
+    }
+
     /// Returns `true` if component of type `T` was already attached to the entity.
     pub fn attached<T>(&self, entity: Entity) -> bool
     where
@@ -76,13 +108,87 @@ pub fn get_mut<T>(&mut self, entity: Entity) -> Option<&mut T>
         storage.get_mut(entity)
     }
 
+    /// Returns iterator over all entities which have component of type `T`,
+    /// where components could be mutated.
+    pub fn query_mut<T>(&mut self) -> impl Iterator<Item = (Entity, &mut T)>
+    where
+        T: Component,
+    {
+        self.get_storage_mut::<T>()
+            .into_iter()
+            .flat_map(ComponentStorage::iter_mut)
+    }
+
+    /// Returns iterator over all entities which have components of both types `A` and `B`.
+    ///
+    /// Entities of the smaller storage are walked and components of the other are looked up.
+    ///
+    pub fn query2<A, B>(&self) -> impl Iterator<Item = (Entity, &A, &B)>
+    where
+        A: Component,
+        B: Component,
+    {
+        let (a, b) = match (self.get_storage::<A>(), self.get_storage::<B>()) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return Box::new(iter::empty()) as Box<dyn Iterator<Item = (Entity, &A, &B)> + '_>,
+        };
+        let entities: Box<dyn Iterator<Item = Entity> + '_> = if a.len() <= b.len() {
+            Box::new(a.entities())
+        } else {
+            Box::new(b.entities())
+        };
+        Box::new(entities.filter_map(move |entity| Some((entity, a.get(entity)?, b.get(entity)?))))
+    }
+
+    /// Returns iterator over all entities which have components of both types `A` and `B`,
+    /// where components of type `B` could be mutated.
+    ///
+    /// Components of type `B` are walked and components of type `A` are looked up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `A` and `B` are the same type.
+    ///
+    pub fn query2_mut<A, B>(&mut self) -> impl Iterator<Item = (Entity, &A, &mut B)>
+    where
+        A: Component,
+        B: Component,
+    {
+        let (a_typeid, b_typeid) = (TypeId::of::<A>(), TypeId::of::<B>());
+        assert_ne!(a_typeid, b_typeid, "query of the same component types");
+
+        // Storages are distinct entries of the map, so they could be borrowed at once.
+        let (mut a, mut b) = (None, None);
+        for (typeid, storage) in self._storages.iter_mut() {
+            if *typeid == a_typeid {
+                a = Some(storage);
+            } else if *typeid == b_typeid {
+                b = Some(storage);
+            }
+        }
+        let a = a.map(|storage| {
+            let storage: &ComponentStorage<A> =
+                storage.as_any().downcast_ref().expect("downcast error");
+            storage
+        });
+        let b = b.map(|storage| {
+            let storage: &mut ComponentStorage<B> =
+                storage.as_any_mut().downcast_mut().expect("downcast error");
+            storage
+        });
+        a.zip(b).into_iter().flat_map(|(a, b)| {
+            b.iter_mut()
+                .filter_map(move |(entity, component)| Some((entity, a.get(entity)?, component)))
+        })
+    }
+
     fn get_storage<T>(&self) -> Option<&ComponentStorage<T>>
     where
         T: Component,
     {
         let typeid = TypeId::of::<T>();
         let boxed = self._storages.get(&typeid)?;
-        Some(boxed.downcast_ref().expect("downcast error"))
+        Some(boxed.as_any().downcast_ref().expect("downcast error"))
     }
 
     fn get_storage_mut<T>(&mut self) -> Option<&mut ComponentStorage<T>>
@@ -91,7 +197,7 @@ fn get_storage_mut<T>(&mut self) -> Option<&mut ComponentStorage<T>>
     {
         let typeid = TypeId::of::<T>();
         let boxed = self._storages.get_mut(&typeid)?;
-        Some(boxed.downcast_mut().expect("downcast error"))
+        Some(boxed.as_any_mut().downcast_mut().expect("downcast error"))
     }
 
     fn create_storage<T>(&mut self) -> &mut ComponentStorage<T>
@@ -102,6 +208,6 @@ fn create_storage<T>(&mut self) -> &mut ComponentStorage<T>
         let boxed = Box::new(ComponentStorage::<T>::new());
         self._storages.insert(typeid, boxed);
         let boxed = self._storages.get_mut(&typeid).unwrap();
-        boxed.downcast_mut().expect("downcast error")
+        boxed.as_any_mut().downcast_mut().expect("downcast error")
     }
 }
@@ -58,6 +58,16 @@ pub fn remove(&mut self, entity: Entity) -> Option<T> {
         self.components.remove(id)
     }
 
+    /// Returns count of components in this storage.
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Returns `true` if there are no components in this storage.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
     /// Returns `true` if component was already attached to the entity.
     pub fn attached(&self, entity: Entity) -> bool {
         self.entity_to_component.contains_key(entity)
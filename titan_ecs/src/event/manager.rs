@@ -0,0 +1,76 @@
+//! Utilities for managing event storages.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+use super::{Event, Events};
+
+/// Type-erased storage of events which can be updated without knowing its type.
+trait EventStorage: Any {
+    fn update(&mut self);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<E> EventStorage for Events<E>
+where
+    E: Event,
+{
+    fn update(&mut self) {
+        Events::update(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Manager of all events of ECS.
+#[derive(Default)]
+pub struct EventManager {
+    storages: HashMap<TypeId, Box<dyn EventStorage>>,
+}
+
+impl EventManager {
+    /// Creates new event manager.
+    pub fn new() -> Self {
+        Self {
+            storages: HashMap::new(),
+        }
+    }
+
+    /// Sends event of type `E`.
+    pub fn send<E>(&mut self, event: E)
+    where
+        E: Event,
+    {
+        let typeid = TypeId::of::<E>();
+        let boxed = self
+            .storages
+            .entry(typeid)
+            .or_insert_with(|| Box::new(Events::<E>::new()));
+        let events: &mut Events<E> = boxed.as_any_mut().downcast_mut().expect("downcast error");
+        events.send(event)
+    }
+
+    /// Retrieves storage of events of type `E`, if any event of this type was sent.
+    pub fn get<E>(&self) -> Option<&Events<E>>
+    where
+        E: Event,
+    {
+        let typeid = TypeId::of::<E>();
+        let boxed = self.storages.get(&typeid)?;
+        Some(boxed.as_any().downcast_ref().expect("downcast error"))
+    }
+
+    /// Swaps buffers of all event storages.
+    pub fn update(&mut self) {
+        for storage in self.storages.values_mut() {
+            storage.update();
+        }
+    }
+}
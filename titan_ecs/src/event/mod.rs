@@ -0,0 +1,119 @@
+//! Utilities for *events* (messages between systems) in ECS.
+
+use std::any::Any;
+
+pub use manager::*;
+
+mod manager;
+mod tests;
+
+/// Objects of this trait represent *event* of ECS.
+///
+/// Events are sent by some systems and read by others.
+///
+pub trait Event: Any + Send + Sync {}
+
+impl<T> Event for T where T: Any + Send + Sync {}
+
+/// Double-buffered storage of events of type `E`.
+///
+/// Events sent during one update are readable during this and the next update,
+/// then they are cleared.
+///
+pub struct Events<E>
+where
+    E: Event,
+{
+    /// Events which were sent before the last update.
+    previous: Vec<(usize, E)>,
+    /// Events which were sent after the last update.
+    current: Vec<(usize, E)>,
+    /// Count of all events which were ever sent.
+    count: usize,
+}
+
+impl<E> Events<E>
+where
+    E: Event,
+{
+    /// Creates an empty event storage.
+    pub fn new() -> Self {
+        Self {
+            previous: Vec::new(),
+            current: Vec::new(),
+            count: 0,
+        }
+    }
+
+    /// Sends new event so it can be read by event readers.
+    pub fn send(&mut self, event: E) {
+        self.current.push((self.count, event));
+        self.count += 1;
+    }
+
+    /// Swaps event buffers, clearing events which were sent before the previous update.
+    pub fn update(&mut self) {
+        self.previous = std::mem::take(&mut self.current);
+    }
+
+    /// Returns immutable iterator over all events which are stored now.
+    pub fn iter(&self) -> impl Iterator<Item = &E> {
+        self.previous
+            .iter()
+            .chain(self.current.iter())
+            .map(|(_, event)| event)
+    }
+}
+
+impl<E> Default for Events<E>
+where
+    E: Event,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reader of events of type `E` which tracks events that were already read.
+pub struct EventReader<E>
+where
+    E: Event,
+{
+    /// Count of events which were read by this reader.
+    read_count: usize,
+    _marker: std::marker::PhantomData<fn() -> E>,
+}
+
+impl<E> EventReader<E>
+where
+    E: Event,
+{
+    /// Creates new event reader which will read all events that are stored now.
+    pub fn new() -> Self {
+        Self {
+            read_count: 0,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns iterator over events which were not read by this reader yet.
+    pub fn read<'a>(&mut self, events: &'a Events<E>) -> impl Iterator<Item = &'a E> {
+        let read_count = self.read_count;
+        self.read_count = events.count;
+        events
+            .previous
+            .iter()
+            .chain(events.current.iter())
+            .filter(move |(id, _)| *id >= read_count)
+            .map(|(_, event)| event)
+    }
+}
+
+impl<E> Default for EventReader<E>
+where
+    E: Event,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,51 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_reader() {
+    let mut events = Events::new();
+    let mut reader = EventReader::new();
+
+    events.send(1);
+    events.send(2);
+    assert_eq!(reader.read(&events).copied().collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(reader.read(&events).count(), 0);
+
+    events.send(3);
+    assert_eq!(reader.read(&events).copied().collect::<Vec<_>>(), [3]);
+}
+
+#[test]
+fn test_double_buffering() {
+    let mut events = Events::new();
+    let mut reader = EventReader::new();
+
+    events.send("foo");
+    events.update();
+    events.send("bar");
+    assert_eq!(events.iter().copied().collect::<Vec<_>>(), ["foo", "bar"]);
+
+    events.update();
+    assert_eq!(events.iter().copied().collect::<Vec<_>>(), ["bar"]);
+    assert_eq!(reader.read(&events).copied().collect::<Vec<_>>(), ["bar"]);
+
+    events.update();
+    assert_eq!(events.iter().count(), 0);
+}
+
+#[test]
+fn test_manager() {
+    let mut manager = EventManager::new();
+    assert!(manager.get::<u32>().is_none());
+
+    manager.send(42u32);
+    manager.send("hit");
+    manager.update();
+    manager.update();
+    manager.send(24u32);
+
+    let events = manager.get::<u32>().unwrap();
+    assert_eq!(events.iter().copied().collect::<Vec<_>>(), [24]);
+    assert_eq!(manager.get::<&str>().unwrap().iter().count(), 0);
+}
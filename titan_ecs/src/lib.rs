@@ -2,13 +2,18 @@
 
 pub use component::Component;
 pub use entity::Entity;
-pub use system::System;
+pub use event::{Event, EventReader, Events};
+pub use system::{Schedule, System};
+pub use transform::Transform;
 pub use world::World;
 
 use component::ComponentManager;
 use entity::EntityStorage;
+use event::EventManager;
 
 mod component;
 mod entity;
+mod event;
 mod system;
+mod transform;
 mod world;
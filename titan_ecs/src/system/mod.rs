@@ -1,14 +1,57 @@
 //! Utilities for *systems* in ECS.
 
-use signature::Signature;
+use crate::World;
 
-mod signature;
+mod tests;
 
 /// Objects of this trait represent *system* of ECS.
+///
+/// System contains the logic of the game which handles entities and their components.
+///
 pub trait System {
-    /// Component types which will be handled by this system.
-    type Type: Signature;
+    /// Runs the system once with provided storage of entities and components.
+    fn run(&mut self, world: &mut World);
+}
+
+/// Ordered collection of systems which are executed one after another.
+#[derive(Default)]
+pub struct Schedule {
+    /// Systems in order of their execution.
+    systems: Vec<Box<dyn System>>,
+}
+
+impl Schedule {
+    /// Creates new schedule without any systems.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the system to the end of the schedule, so it is executed after all the previous ones.
+    pub fn add_system<S>(&mut self, system: S) -> &mut Self
+    where
+        S: System + 'static,
+    {
+        self.systems.push(Box::new(system));
+        self
+    }
+
+    /// Returns count of systems in the schedule.
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    /// Returns `true` if the schedule contains no systems.
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
 
-    /// Handles state of the current system with provided components.
-    fn handle(&mut self, components: impl Iterator<Item = Self::Type>);
+    /// Runs all the systems once in order of their insertion.
+    ///
+    /// Each system observes all the changes of the world made by the previous systems.
+    ///
+    pub fn run(&mut self, world: &mut World) {
+        for system in &mut self.systems {
+            system.run(world);
+        }
+    }
 }
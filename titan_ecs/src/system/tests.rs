@@ -0,0 +1,72 @@
+#![cfg(test)]
+
+use super::*;
+use crate::Entity;
+
+#[derive(Debug, PartialEq)]
+struct Counter(i32);
+
+#[derive(Debug, PartialEq)]
+struct Observed(i32);
+
+/// Increments counter of the entity.
+struct Increment(Entity);
+
+impl System for Increment {
+    fn run(&mut self, world: &mut World) {
+        world.get_mut::<Counter>(self.0).unwrap().0 += 1;
+    }
+}
+
+/// Copies counter of the entity into its observed value.
+struct Observe(Entity);
+
+impl System for Observe {
+    fn run(&mut self, world: &mut World) {
+        let counter = world.get::<Counter>(self.0).unwrap().0;
+        world.insert(self.0, Observed(counter));
+    }
+}
+
+#[test]
+fn test_insertion_order() {
+    let mut world = World::default();
+    let entity = world.spawn();
+    world.insert(entity, Counter(0));
+
+    let mut schedule = Schedule::new();
+    schedule
+        .add_system(Increment(entity))
+        .add_system(Observe(entity));
+    assert_eq!(schedule.len(), 2);
+
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Observed>(entity), Some(&Observed(1)));
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Observed>(entity), Some(&Observed(2)));
+}
+
+#[test]
+fn test_reversed_order() {
+    let mut world = World::default();
+    let entity = world.spawn();
+    world.insert(entity, Counter(0));
+
+    let mut schedule = Schedule::new();
+    schedule
+        .add_system(Observe(entity))
+        .add_system(Increment(entity));
+
+    // Observer runs before increment, so it sees the value of the previous run.
+    schedule.run(&mut world);
+    assert_eq!(world.get::<Observed>(entity), Some(&Observed(0)));
+    assert_eq!(world.get::<Counter>(entity), Some(&Counter(1)));
+}
+
+#[test]
+fn test_empty() {
+    let mut world = World::default();
+    let mut schedule = Schedule::default();
+    assert!(schedule.is_empty());
+    schedule.run(&mut world);
+}
@@ -0,0 +1,121 @@
+//! Utilities for spatial transformation of *entities* in ECS.
+
+use ultraviolet::{Mat4, Rotor3, Vec3};
+
+/// Component which represents position, orientation and scale of the entity in the world.
+///
+/// Matrix of the transform is cached: it is marked as dirty by the setters
+/// and recomputed by [`update_matrix`](Transform::update_matrix).
+/// [`World::model_matrices`](crate::World::model_matrices) updates cached matrices
+/// of all transforms in the world, so it is the caching path used for rendering.
+///
+/// Cache is not updated through shared reference, because components must be [`Sync`]
+/// and the transform must stay [`Copy`].
+///
+#[derive(Debug, Copy, Clone)]
+pub struct Transform {
+    /// Position of the entity.
+    translation: Vec3,
+    /// Orientation of the entity.
+    rotation: Rotor3,
+    /// Scale of the entity along each axis.
+    scale: Vec3,
+    /// Cached matrix of the transform, valid only if not dirty.
+    matrix: Mat4,
+    /// Whether the transform was changed since the matrix was computed.
+    dirty: bool,
+}
+
+impl Transform {
+    /// Creates new transform with given translation, rotation and scale.
+    pub fn new(translation: Vec3, rotation: Rotor3, scale: Vec3) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+            matrix: self::compute_matrix(translation, rotation, scale),
+            dirty: false,
+        }
+    }
+
+    /// Position of the entity.
+    pub fn translation(&self) -> Vec3 {
+        self.translation
+    }
+
+    /// Sets position of the entity.
+    pub fn set_translation(&mut self, translation: Vec3) {
+        self.translation = translation;
+        self.dirty = true;
+    }
+
+    /// Orientation of the entity.
+    pub fn rotation(&self) -> Rotor3 {
+        self.rotation
+    }
+
+    /// Sets orientation of the entity.
+    pub fn set_rotation(&mut self, rotation: Rotor3) {
+        self.rotation = rotation;
+        self.dirty = true;
+    }
+
+    /// Scale of the entity along each axis.
+    pub fn scale(&self) -> Vec3 {
+        self.scale
+    }
+
+    /// Sets scale of the entity along each axis.
+    pub fn set_scale(&mut self, scale: Vec3) {
+        self.scale = scale;
+        self.dirty = true;
+    }
+
+    /// Checks if the transform was changed since its matrix was cached.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// 4x4 matrix of this transform (scale, then rotation, then translation).
+    ///
+    /// Cached matrix is returned if the transform is not dirty,
+    /// otherwise the matrix is computed without caching: the transform stays dirty.
+    /// Use [`update_matrix`](Self::update_matrix) to cache computed matrix.
+    ///
+    pub fn matrix(&self) -> Mat4 {
+        if self.dirty {
+            self::compute_matrix(self.translation, self.rotation, self.scale)
+        } else {
+            self.matrix
+        }
+    }
+
+    /// Recomputes cached matrix of this transform if it is dirty and returns it.
+    pub fn update_matrix(&mut self) -> Mat4 {
+        if self.dirty {
+            self.matrix = self::compute_matrix(self.translation, self.rotation, self.scale);
+            self.dirty = false;
+        }
+        self.matrix
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::new(Vec3::zero(), Rotor3::identity(), Vec3::one())
+    }
+}
+
+impl PartialEq for Transform {
+    fn eq(&self, other: &Self) -> bool {
+        self.translation == other.translation
+            && self.rotation == other.rotation
+            && self.scale == other.scale
+    }
+}
+
+fn compute_matrix(translation: Vec3, rotation: Rotor3, scale: Vec3) -> Mat4 {
+    Mat4::from_translation(translation)
+        * rotation.into_matrix().into_homogeneous()
+        * Mat4::from_nonuniform_scale(scale)
+}
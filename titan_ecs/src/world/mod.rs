@@ -0,0 +1,169 @@
+//! Utilities for storage of ECS.
+
+use ultraviolet::Mat4;
+
+use super::ComponentManager;
+use super::Transform;
+use super::{Component, Entity, EntityStorage};
+use super::{Event, EventManager, EventReader, Events};
+
+mod tests;
+
+/// Storage for entities, components and events of ECS.
+///
+/// Systems are stored separately in [`Schedule`](crate::Schedule) which runs them on the world.
+///
+#[derive(Default)]
+pub struct World {
+    /// Storage for all entities.
+    entities: EntityStorage,
+    /// Map with typeid of components and their storages.
+    component_manager: ComponentManager,
+    /// Map with typeid of events and their storages.
+    event_manager: EventManager,
+}
+
+impl World {
+    /// Creates new entity without any components.
+    pub fn spawn(&mut self) -> Entity {
+        self.entities.insert(())
+    }
+
+    /// Destroys the entity and removes all the components attached to it.
+    ///
+    /// Returns `false` if the entity was already destroyed.
+    ///
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if self.entities.remove(entity).is_none() {
+            return false;
+        }
+        self.component_manager.remove_all(entity);
+        true
+    }
+
+    /// Returns `true` if the entity exists, i.e. was spawned and not despawned yet.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.contains_key(entity)
+    }
+
+    /// Attaches component of type `C` to the entity.
+    /// If component was already attached, it will be replaced by value.
+    ///
+    /// Returns previously attached component, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entity does not exist.
+    ///
+    pub fn insert<C>(&mut self, entity: Entity, component: C) -> Option<C>
+    where
+        C: Component,
+    {
+        assert!(self.contains(entity), "entity does not exist");
+        self.component_manager.insert(entity, component)
+    }
+
+    /// Detaches component of type `C` from the entity.
+    ///
+    /// Returns component that was previously attached to the entity.
+    ///
+    pub fn remove<C>(&mut self, entity: Entity) -> Option<C>
+    where
+        C: Component,
+    {
+        self.component_manager.remove(entity)
+    }
+
+    /// Retrieves an immutable reference to component of type `C` attached to the entity.
+    pub fn get<C>(&self, entity: Entity) -> Option<&C>
+    where
+        C: Component,
+    {
+        self.component_manager.get(entity)
+    }
+
+    /// Retrieves a mutable reference to component of type `C` attached to the entity.
+    pub fn get_mut<C>(&mut self, entity: Entity) -> Option<&mut C>
+    where
+        C: Component,
+    {
+        self.component_manager.get_mut(entity)
+    }
+
+    /// Returns iterator over all entities which have component of type `C`,
+    /// where components could be mutated.
+    pub fn query_mut<C>(&mut self) -> impl Iterator<Item = (Entity, &mut C)>
+    where
+        C: Component,
+    {
+        self.component_manager.query_mut()
+    }
+
+    /// Collects model matrices of all entities which have [`Transform`] component.
+    ///
+    /// Cached matrices of dirty transforms are updated.
+    ///
+    pub fn model_matrices(&mut self) -> Vec<(Entity, Mat4)> {
+        self.query_mut::<Transform>()
+            .map(|(entity, transform)| (entity, transform.update_matrix()))
+            .collect()
+    }
+
+    /// Returns iterator over all entities which have components of both types `A` and `B`.
+    pub fn query2<A, B>(&self) -> impl Iterator<Item = (Entity, &A, &B)>
+    where
+        A: Component,
+        B: Component,
+    {
+        self.component_manager.query2()
+    }
+
+    /// Returns iterator over all entities which have components of both types `A` and `B`,
+    /// where components of type `B` could be mutated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `A` and `B` are the same type.
+    ///
+    pub fn query2_mut<A, B>(&mut self) -> impl Iterator<Item = (Entity, &A, &mut B)>
+    where
+        A: Component,
+        B: Component,
+    {
+        self.component_manager.query2_mut()
+    }
+
+    /// Sends event of type `E` which can be read by systems.
+    pub fn send_event<E>(&mut self, event: E)
+    where
+        E: Event,
+    {
+        self.event_manager.send(event)
+    }
+
+    /// Retrieves storage of events of type `E`, if any event of this type was sent.
+    pub fn events<E>(&self) -> Option<&Events<E>>
+    where
+        E: Event,
+    {
+        self.event_manager.get()
+    }
+
+    /// Returns iterator over events of type `E` which were not read by the reader yet.
+    pub fn read_events<'a, E>(&'a self, reader: &mut EventReader<E>) -> impl Iterator<Item = &'a E>
+    where
+        E: Event,
+    {
+        self.events()
+            .map(|events| reader.read(events))
+            .into_iter()
+            .flatten()
+    }
+
+    /// Swaps buffers of all events: events sent before previous call are cleared.
+    ///
+    /// Should be called once per frame: application calls it after scheduled systems are run.
+    pub fn update_events(&mut self) {
+        self.event_manager.update()
+    }
+}
@@ -0,0 +1,175 @@
+#![cfg(test)]
+
+use ultraviolet::Vec3;
+
+use super::*;
+
+#[derive(Debug, PartialEq)]
+struct Position(i32);
+
+#[derive(Debug, PartialEq)]
+struct Velocity(i32);
+
+#[test]
+fn test_insertion() {
+    let mut world = World::default();
+
+    let entity = world.spawn();
+    assert_eq!(world.insert(entity, Position(1)), None);
+    assert_eq!(world.insert(entity, Position(2)), Some(Position(1)));
+    assert_eq!(world.get(entity), Some(&Position(2)));
+
+    world.get_mut::<Position>(entity).unwrap().0 += 1;
+    assert_eq!(world.remove(entity), Some(Position(3)));
+    assert_eq!(world.get::<Position>(entity), None);
+}
+
+#[test]
+fn test_isolation() {
+    let mut world = World::default();
+
+    let entities: Vec<_> = (0..1000)
+        .map(|int| {
+            let entity = world.spawn();
+            world.insert(entity, Position(int));
+            if int % 2 == 0 {
+                world.insert(entity, Velocity(-int));
+            }
+            entity
+        })
+        .collect();
+
+    for (entity, int) in entities.iter().copied().zip(0..) {
+        assert_eq!(world.get(entity), Some(&Position(int)));
+        let velocity = (int % 2 == 0).then(|| Velocity(-int));
+        assert_eq!(world.get(entity), velocity.as_ref());
+    }
+
+    // Removal of components of one type does not affect components of other types.
+    for &entity in &entities {
+        world.remove::<Velocity>(entity);
+    }
+    for (entity, int) in entities.iter().copied().zip(0..) {
+        assert_eq!(world.get(entity), Some(&Position(int)));
+        assert_eq!(world.get::<Velocity>(entity), None);
+    }
+}
+
+#[test]
+fn test_query() {
+    let mut world = World::default();
+
+    let both: Vec<_> = (0..10)
+        .map(|int| {
+            let entity = world.spawn();
+            world.insert(entity, Position(int));
+            world.insert(entity, Velocity(1));
+            entity
+        })
+        .collect();
+    for int in 0..100 {
+        let entity = world.spawn();
+        world.insert(entity, Position(int));
+    }
+    let velocity_only = world.spawn();
+    world.insert(velocity_only, Velocity(0));
+
+    let mut queried: Vec<_> = world
+        .query2::<Position, Velocity>()
+        .map(|(e, ..)| e)
+        .collect();
+    queried.sort();
+    let mut expected = both.clone();
+    expected.sort();
+    assert_eq!(queried, expected);
+
+    for (_, position, velocity) in world.query2_mut::<Position, Velocity>() {
+        velocity.0 += position.0;
+    }
+    for (entity, position, velocity) in world.query2::<Position, Velocity>() {
+        assert_eq!(velocity.0, position.0 + 1);
+        assert!(both.contains(&entity));
+    }
+    assert_eq!(world.query2_mut::<Velocity, Position>().count(), both.len());
+}
+
+#[test]
+fn test_query_missing_storage() {
+    let mut world = World::default();
+
+    let entity = world.spawn();
+    world.insert(entity, Position(0));
+    assert_eq!(world.query2::<Position, Velocity>().count(), 0);
+    assert_eq!(world.query2_mut::<Position, Velocity>().count(), 0);
+}
+
+#[test]
+fn test_despawn() {
+    let mut world = World::default();
+
+    let entity = world.spawn();
+    let other = world.spawn();
+    world.insert(entity, Position(0));
+    world.insert(entity, Velocity(0));
+    world.insert(other, Position(1));
+
+    assert!(world.despawn(entity));
+    assert!(!world.despawn(entity));
+    assert!(!world.contains(entity));
+    assert_eq!(world.get::<Position>(entity), None);
+    assert_eq!(world.get::<Velocity>(entity), None);
+    assert_eq!(world.get(other), Some(&Position(1)));
+}
+
+#[test]
+#[should_panic]
+fn test_insertion_despawned() {
+    let mut world = World::default();
+
+    let entity = world.spawn();
+    world.despawn(entity);
+    world.insert(entity, Position(0));
+}
+
+#[test]
+fn test_model_matrices() {
+    let mut world = World::default();
+
+    let entity = world.spawn();
+    let other = world.spawn();
+    world.insert(entity, Transform::default());
+    world.insert(other, Position(0));
+    assert_eq!(world.model_matrices(), vec![(entity, Mat4::identity())]);
+
+    let translation = Vec3::new(1.0, 2.0, 3.0);
+    let transform = world.get_mut::<Transform>(entity).unwrap();
+    transform.set_translation(translation);
+    assert!(transform.is_dirty());
+    assert_eq!(transform.matrix(), Mat4::from_translation(translation));
+
+    let matrices = world.model_matrices();
+    assert_eq!(
+        matrices,
+        vec![(entity, Mat4::from_translation(translation))]
+    );
+    assert!(!world.get::<Transform>(entity).unwrap().is_dirty());
+}
+
+#[test]
+fn test_transform_matrix_cache() {
+    let translation = Vec3::new(1.0, 2.0, 3.0);
+    let mut transform = Transform::default();
+    transform.set_translation(translation);
+    assert!(transform.is_dirty());
+
+    // Matrix computed through shared reference is correct, but it is not cached.
+    assert_eq!(transform.matrix(), Mat4::from_translation(translation));
+    assert!(transform.is_dirty());
+
+    assert_eq!(
+        transform.update_matrix(),
+        Mat4::from_translation(translation)
+    );
+    assert!(!transform.is_dirty());
+    assert_eq!(transform.matrix(), Mat4::from_translation(translation));
+}
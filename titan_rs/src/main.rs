@@ -7,7 +7,7 @@
 
 use egui::{TopBottomPanel, Window};
 
-use titan_core::{app::DeltaTime, config::Config, window::Event};
+use titan_core::{app::DeltaTime, config::Config, window::Event, Pass};
 
 mod logger;
 
@@ -22,7 +22,8 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
 
     let version = APP_VERSION_STR.parse().unwrap();
     let enable_validation = cfg!(debug_assertions);
-    let config = Config::new(APP_NAME.to_string(), version, enable_validation);
+    let mut config = Config::new(APP_NAME.to_string(), version, enable_validation);
+    config.set_demo_scene(true);
 
     let mut delta_time = DeltaTime::ZERO;
     let mut duration = DeltaTime::ZERO;
@@ -31,6 +32,14 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
 
     let mut application = titan_core::init(config)?;
 
+    // Window is only cleared while resources are loading.
+    application.present_frame(|frame| {
+        if let Some(Pass::Deferred(pass)) = frame.next_pass()? {
+            log::debug!("loading frame of size {:?}", pass.viewport_size());
+        }
+        Ok(())
+    })?;
+
     let image_data = include_bytes!("../res/angry flop.jpg");
     let image = image::io::Reader::new(Cursor::new(image_data))
         .with_guessed_format()?
@@ -78,5 +87,6 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         Event::Destroyed => {
             log::debug!("destroyed");
         }
+        _ => (),
     })
 }